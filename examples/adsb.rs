@@ -0,0 +1,33 @@
+use anyhow::{Context, Result};
+
+use futuresdr::blocks::adsb::DecoderBuilder;
+use futuresdr::blocks::adsb::DemodulatorBuilder;
+use futuresdr::blocks::adsb::MagnitudeBuilder;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::MessageDebug;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+// decodes a recording of 8-bit IQ samples at 2 Msps, e.g.
+// rtl_sdr -f 1090M -s 2M -g 40 adsb.bin
+fn main() -> Result<()> {
+    let file = std::env::args()
+        .nth(1)
+        .context("usage: adsb <file with u8 IQ samples at 2 Msps>")?;
+
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(FileSource::new(1, file));
+    let mag = fg.add_block(MagnitudeBuilder::new().build());
+    let demod = fg.add_block(DemodulatorBuilder::new().build());
+    let decoder = fg.add_block(DecoderBuilder::new().build());
+    let snk = fg.add_block(MessageDebug::new());
+
+    fg.connect_stream(src, "out", mag, "in")?;
+    fg.connect_stream(mag, "out", demod, "in")?;
+    fg.connect_message(demod, "out", decoder, "in")?;
+    fg.connect_message(decoder, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use futuresdr::anyhow::{Context, Result};
+use futuresdr::blocks::audio::FileSource;
+use futuresdr::blocks::ax25::DecoderBuilder;
+use futuresdr::blocks::ax25::DemodulatorBuilder;
+use futuresdr::blocks::MessageDebug;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+// decodes APRS packets from a mono recording of the audio output of a receiver
+fn main() -> Result<()> {
+    let file = std::env::args()
+        .nth(1)
+        .context("usage: ax25-decode <wav file>")?;
+
+    let mut fg = Flowgraph::new();
+
+    let src = FileSource::new(&file);
+    let inner = src.as_async::<FileSource>().unwrap();
+    let demod = DemodulatorBuilder::new(inner.sample_rate() as f32).build();
+
+    let src = fg.add_block(src);
+    let demod = fg.add_block(demod);
+    let decoder = fg.add_block(DecoderBuilder::new().build());
+    let snk = fg.add_block(MessageDebug::new());
+
+    fg.connect_stream(src, "out", demod, "in")?;
+    fg.connect_message(demod, "out", decoder, "in")?;
+    fg.connect_message(decoder, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
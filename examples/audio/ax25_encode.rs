@@ -0,0 +1,33 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ax25::ModulatorBuilder;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::FileSink;
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+// writes one APRS packet as unsigned 8-bit PCM, convert it, e.g., with
+// sox -t u8 -r 48000 -c 1 packet.raw packet.wav
+fn main() -> Result<()> {
+    let packet = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "N0CALL-7>APRS,WIDE1-1:>Hello from FutureSDR".to_string());
+
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(MessageBurstBuilder::new(Pmt::String(packet), 1).build());
+    let modulator = fg.add_block(ModulatorBuilder::new(SAMPLE_RATE as f32).build());
+    let convert = fg.add_block(Apply::new(|i: &f32| -> u8 { (i * 127.0 + 128.0) as u8 }));
+    let snk = fg.add_block(FileSink::new(1, "packet.raw"));
+
+    fg.connect_message(src, "out", modulator, "in")?;
+    fg.connect_stream(modulator, "out", convert, "in")?;
+    fg.connect_stream(convert, "out", snk, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    Ok(())
+}
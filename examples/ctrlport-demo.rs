@@ -1,5 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::convert::TryFrom;
 
 use futuresdr::runtime::AsyncKernel;
 use futuresdr::runtime::Block;
@@ -9,6 +10,7 @@ use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::MessageIo;
 use futuresdr::runtime::MessageIoBuilder;
 use futuresdr::runtime::Pmt;
+use futuresdr::runtime::PmtKind;
 use futuresdr::runtime::Runtime;
 use futuresdr::runtime::StreamIoBuilder;
 
@@ -34,6 +36,8 @@ impl CtrlPortDemo {
             MessageIoBuilder::new()
                 .add_output("out")
                 .add_sync_input("in", Self::handler)
+                .add_sync_input("set", Self::set)
+                .input_kinds("set", &[PmtKind::U64])
                 .build(),
             Self { counter: 5 },
         )
@@ -48,6 +52,17 @@ impl CtrlPortDemo {
         self.counter += 1;
         Ok(Pmt::U64(self.counter - 1))
     }
+
+    // fails with a PmtConversionError (HTTP 400) for anything but an integer
+    fn set(
+        &mut self,
+        _mio: &mut MessageIo<CtrlPortDemo>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        self.counter = u64::try_from(p)?;
+        Ok(Pmt::U64(self.counter))
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use std::f32::consts::PI;
+use std::time::Duration;
+
+use futuresdr::blocks::zigbee::Demodulator;
+use futuresdr::blocks::zigbee::DemodulatorBuilder;
+use futuresdr::blocks::zigbee::Mac;
+use futuresdr::blocks::zigbee::MacBuilder;
+use futuresdr::blocks::zigbee::Modulator;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::num_complex::Complex;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const N_FRAMES: usize = 20;
+const SNR_DB: f32 = 6.0;
+
+// AWGN channel with a constant phase offset
+fn channel(snr_db: f32, phase: f32) -> impl FnMut(&Complex<f32>) -> Complex<f32> + Send {
+    let sigma = (10f32.powf(-snr_db / 10.0) / 2.0).sqrt();
+    let rotation = Complex::from_polar(1.0, phase);
+
+    move |i: &Complex<f32>| -> Complex<f32> {
+        let u1 = rand::random::<f32>().max(f32::MIN_POSITIVE);
+        let u2 = rand::random::<f32>();
+        let noise = Complex::from_polar(sigma * (-2.0 * u1.ln()).sqrt(), 2.0 * PI * u2);
+        i * rotation + noise
+    }
+}
+
+fn main() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        MessageSourceBuilder::new(
+            Pmt::String("Hello from FutureSDR!".to_string()),
+            Duration::from_millis(50),
+        )
+        .n_messages(N_FRAMES)
+        .build(),
+    );
+    let mac_tx = fg.add_block(MacBuilder::new().src_address(0x0001).build());
+    let modulator = fg.add_block(Modulator::new());
+    let channel = fg.add_block(Apply::new(channel(SNR_DB, 1.0)));
+    let demodulator = fg.add_block(DemodulatorBuilder::new().build());
+    let mac_rx = fg.add_block(MacBuilder::new().src_address(0x0002).build());
+    let snk = fg.add_block(MessageSink::new());
+
+    fg.connect_message(src, "out", mac_tx, "tx")?;
+    fg.connect_message(mac_tx, "tx", modulator, "in")?;
+    fg.connect_stream(modulator, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", demodulator, "in")?;
+    fg.connect_message(demodulator, "out", mac_rx, "rx")?;
+    fg.connect_message(mac_rx, "rx", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let demod = fg.block_async::<Demodulator>(demodulator).unwrap();
+    let mac = fg.block_async::<Mac>(mac_rx).unwrap();
+    let snk = fg.block_async::<MessageSink>(snk).unwrap();
+
+    println!("frames sent:        {}", N_FRAMES);
+    println!("frames demodulated: {}", demod.received());
+    println!("crc errors:         {}", mac.crc_errors());
+    println!("frames received:    {}", snk.received());
+
+    Ok(())
+}
@@ -56,7 +56,7 @@ impl Call {
             request,
             link.callback(|response: Response<Result<String, Error>>| {
                 if response.status().is_success() {
-                    Msg::Result(response.into_body().unwrap())
+                    Msg::Result(super::format_response(response.into_body().unwrap()))
                 } else {
                     Msg::Error
                 }
@@ -10,3 +10,11 @@ pub use poll::Poll;
 pub use poll_periodic::PollPeriodic;
 pub use radio::Radio;
 pub use radio::RadioItem;
+
+// the control port responds with the Pmt returned by the handler as JSON
+fn format_response(body: String) -> String {
+    match serde_json::from_str::<futuresdr_pmt::Pmt>(&body) {
+        Ok(p) => format!("{:?}", p),
+        Err(_) => body,
+    }
+}
@@ -45,7 +45,7 @@ impl PollPeriodic {
                 request,
                 link.callback(|response: Response<Result<String, Error>>| {
                     if response.status().is_success() {
-                        Msg::Update(response.into_body().unwrap())
+                        Msg::Update(super::format_response(response.into_body().unwrap()))
                     } else {
                         Msg::Error
                     }
@@ -106,7 +106,7 @@ impl Radio {
             request,
             link.callback(|response: Response<Result<String, Error>>| {
                 if response.status().is_success() {
-                    Msg::Result(response.into_body().unwrap())
+                    Msg::Result(super::format_response(response.into_body().unwrap()))
                 } else {
                     Msg::Error
                 }
@@ -58,7 +58,7 @@ impl Slider {
             request,
             link.callback(|response: Response<Result<String, Error>>| {
                 if response.status().is_success() {
-                    Msg::Result(response.into_body().unwrap())
+                    Msg::Result(super::format_response(response.into_body().unwrap()))
                 } else {
                     Msg::Error
                 }
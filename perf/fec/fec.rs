@@ -0,0 +1,93 @@
+use clap::{value_t, App, Arg};
+use std::time;
+
+use futuresdr::anyhow::{bail, Context, Result};
+use futuresdr::blocks::fec::ConvolutionalCode;
+use futuresdr::blocks::fec::ReedSolomon;
+use futuresdr::blocks::fec::Viterbi;
+use futuresdr::blocks::fec::PUNCTURE_3_4;
+
+fn main() -> Result<()> {
+    let matches = App::new("FEC Benchmark")
+        .arg(
+            Arg::with_name("run")
+                .short("r")
+                .long("run")
+                .takes_value(true)
+                .value_name("RUN")
+                .default_value("0")
+                .help("Sets run number."),
+        )
+        .arg(
+            Arg::with_name("codec")
+                .short("c")
+                .long("codec")
+                .takes_value(true)
+                .value_name("CODEC")
+                .default_value("viterbi")
+                .help("Sets the codec (viterbi, viterbi34, rs)."),
+        )
+        .arg(
+            Arg::with_name("size")
+                .short("n")
+                .long("size")
+                .takes_value(true)
+                .value_name("SIZE")
+                .default_value("1000000")
+                .help("Sets the number of bits (viterbi) or bytes (rs) to decode."),
+        )
+        .get_matches();
+
+    let run = value_t!(matches.value_of("run"), u32).context("no run")?;
+    let codec = value_t!(matches.value_of("codec"), String).context("no codec")?;
+    let size = value_t!(matches.value_of("size"), usize).context("no size")?;
+
+    let elapsed = match codec.as_str() {
+        "viterbi" => viterbi(ConvolutionalCode::k7(), size)?,
+        "viterbi34" => viterbi(ConvolutionalCode::k7().puncturing(&PUNCTURE_3_4)?, size)?,
+        "rs" => reed_solomon(size)?,
+        _ => bail!("unknown codec {}", codec),
+    };
+
+    println!("{},{},{},{}", run, codec, size, elapsed.as_secs_f64());
+
+    Ok(())
+}
+
+fn viterbi(code: ConvolutionalCode, size: usize) -> Result<time::Duration> {
+    const BLOCK_LEN: usize = 1024;
+
+    let bits: Vec<u8> = (0..BLOCK_LEN)
+        .map(|_| rand::random::<bool>() as u8)
+        .collect();
+    let soft: Vec<f32> = code
+        .encode(&bits)
+        .iter()
+        .map(|b| if *b == 1 { 1.0 } else { -1.0 })
+        .collect();
+    let viterbi = Viterbi::new(code);
+
+    let now = time::Instant::now();
+    for _ in 0..(size + BLOCK_LEN - 1) / BLOCK_LEN {
+        let decoded = viterbi.decode(&soft);
+        assert_eq!(decoded, bits);
+    }
+    Ok(now.elapsed())
+}
+
+fn reed_solomon(size: usize) -> Result<time::Duration> {
+    let rs = ReedSolomon::new();
+    let data: Vec<u8> = (0..rs.max_data_len()).map(|_| rand::random()).collect();
+    let codeword = rs.encode(&data)?;
+
+    let now = time::Instant::now();
+    for i in 0..(size + data.len() - 1) / data.len() {
+        let mut c = codeword.clone();
+        for j in 0..rs.n_parity() / 2 {
+            c[(i + j * 13) % codeword.len()] ^= 0xa5;
+        }
+        rs.decode(&mut c)?;
+        assert_eq!(c, codeword);
+    }
+    Ok(now.elapsed())
+}
@@ -0,0 +1,12 @@
+#!/bin/bash
+
+outfile=perf-data/results.csv
+rm -f ${outfile}
+
+echo "run,codec,size,time" > ${outfile}
+
+files=$(ls perf-data/nr_*.csv 2>/dev/null || echo)
+for f in ${files}
+do
+	cat $f >> ${outfile}
+done
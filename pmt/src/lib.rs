@@ -1,5 +1,14 @@
+use num_complex::Complex32;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 
+pub mod wire;
+
+// New variants are appended, so that formats that encode the variant index
+// stay compatible.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum Pmt {
     Null,
@@ -9,6 +18,14 @@ pub enum Pmt {
     Double(f64),
     VecF32(Vec<f32>),
     Blob(Vec<u8>),
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    VecComplex32(Vec<Complex32>),
+    VecU64(Vec<u64>),
+    MapStrPmt(HashMap<String, Pmt>),
+    VecPmt(Vec<Pmt>),
 }
 
 impl Pmt {
@@ -23,36 +40,64 @@ impl Pmt {
         }
     }
 
+    pub fn kind(&self) -> Option<PmtKind> {
+        match self {
+            Pmt::Null => None,
+            Pmt::String(_) => Some(PmtKind::String),
+            Pmt::U32(_) => Some(PmtKind::U32),
+            Pmt::U64(_) => Some(PmtKind::U64),
+            Pmt::Double(_) => Some(PmtKind::Double),
+            Pmt::VecF32(_) => Some(PmtKind::VecF32),
+            Pmt::Blob(_) => Some(PmtKind::Blob),
+            Pmt::Bool(_) => Some(PmtKind::Bool),
+            Pmt::I32(_) => Some(PmtKind::I32),
+            Pmt::I64(_) => Some(PmtKind::I64),
+            Pmt::F32(_) => Some(PmtKind::F32),
+            Pmt::VecComplex32(_) => Some(PmtKind::VecComplex32),
+            Pmt::VecU64(_) => Some(PmtKind::VecU64),
+            Pmt::MapStrPmt(_) => Some(PmtKind::MapStrPmt),
+            Pmt::VecPmt(_) => Some(PmtKind::VecPmt),
+        }
+    }
+
+    // Scalars are parsed as usual (e.g., "true", "-12", "1.5"). Vectors and
+    // blobs are comma-separated lists, optionally in brackets (e.g., "[1, 2]";
+    // complex numbers like "1+2i"). Maps and lists of Pmts are parsed from
+    // their JSON serialization (e.g., {"freq": {"U64": 100}}).
     pub fn from_string(s: &str, t: &PmtKind) -> Option<Pmt> {
         match t {
-            PmtKind::U32 => {
-                if let Ok(v) = s.parse::<u32>() {
-                    Some(Pmt::U32(v))
-                } else {
-                    None
-                }
-            }
-            PmtKind::U64 => {
-                if let Ok(v) = s.parse::<u64>() {
-                    Some(Pmt::U64(v))
-                } else {
-                    None
-                }
-            }
-            PmtKind::Double => {
-                if let Ok(v) = s.parse::<f64>() {
-                    Some(Pmt::Double(v))
-                } else {
-                    None
-                }
-            }
             PmtKind::String => Some(Pmt::String(s.to_string())),
-            _ => None,
+            PmtKind::Bool => s.trim().parse().ok().map(Pmt::Bool),
+            PmtKind::U32 => s.trim().parse().ok().map(Pmt::U32),
+            PmtKind::U64 => s.trim().parse().ok().map(Pmt::U64),
+            PmtKind::I32 => s.trim().parse().ok().map(Pmt::I32),
+            PmtKind::I64 => s.trim().parse().ok().map(Pmt::I64),
+            PmtKind::F32 => s.trim().parse().ok().map(Pmt::F32),
+            PmtKind::Double => s.trim().parse().ok().map(Pmt::Double),
+            PmtKind::VecF32 => parse_list(s).map(Pmt::VecF32),
+            PmtKind::VecComplex32 => parse_list(s).map(Pmt::VecComplex32),
+            PmtKind::VecU64 => parse_list(s).map(Pmt::VecU64),
+            PmtKind::Blob => parse_list(s).map(Pmt::Blob),
+            PmtKind::MapStrPmt => serde_json::from_str(s).ok().map(Pmt::MapStrPmt),
+            PmtKind::VecPmt => serde_json::from_str(s).ok().map(Pmt::VecPmt),
         }
     }
 }
 
-#[derive(Clone, PartialEq)]
+fn parse_list<T: FromStr>(s: &str) -> Option<Vec<T>> {
+    let s = s.trim();
+    let s = s
+        .strip_prefix('[')
+        .and_then(|s| s.strip_suffix(']'))
+        .unwrap_or(s)
+        .trim();
+    if s.is_empty() {
+        return Some(Vec::new());
+    }
+    s.split(',').map(|v| v.trim().parse().ok()).collect()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub enum PmtKind {
     String,
     U32,
@@ -60,12 +105,134 @@ pub enum PmtKind {
     Double,
     VecF32,
     Blob,
+    Bool,
+    I32,
+    I64,
+    F32,
+    VecComplex32,
+    VecU64,
+    MapStrPmt,
+    VecPmt,
 }
 
+// Pmt does not hold the requested type
+#[derive(Debug, Clone, PartialEq)]
+pub struct PmtConversionError;
+
+impl fmt::Display for PmtConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pmt does not hold the requested type")
+    }
+}
+
+impl std::error::Error for PmtConversionError {}
+
+macro_rules! impl_from {
+    ($t:ty, $v:ident) => {
+        impl From<$t> for Pmt {
+            fn from(v: $t) -> Self {
+                Pmt::$v(v)
+            }
+        }
+    };
+}
+
+impl_from!(bool, Bool);
+impl_from!(u32, U32);
+impl_from!(u64, U64);
+impl_from!(i32, I32);
+impl_from!(i64, I64);
+impl_from!(f32, F32);
+impl_from!(f64, Double);
+impl_from!(String, String);
+impl_from!(Vec<f32>, VecF32);
+impl_from!(Vec<Complex32>, VecComplex32);
+impl_from!(Vec<u64>, VecU64);
+impl_from!(Vec<u8>, Blob);
+impl_from!(HashMap<String, Pmt>, MapStrPmt);
+impl_from!(Vec<Pmt>, VecPmt);
+
+impl From<&str> for Pmt {
+    fn from(v: &str) -> Self {
+        Pmt::String(v.to_string())
+    }
+}
+
+impl From<()> for Pmt {
+    fn from(_: ()) -> Self {
+        Pmt::Null
+    }
+}
+
+// Scalars also convert from narrower variants without loss (e.g., u64 from U32).
+macro_rules! impl_try_from {
+    ($t:ty, $($v:ident),+) => {
+        impl TryFrom<&Pmt> for $t {
+            type Error = PmtConversionError;
+
+            fn try_from(p: &Pmt) -> Result<$t, Self::Error> {
+                match p {
+                    $(Pmt::$v(v) => Ok(v.clone().into()),)+
+                    _ => Err(PmtConversionError),
+                }
+            }
+        }
+
+        impl TryFrom<Pmt> for $t {
+            type Error = PmtConversionError;
+
+            fn try_from(p: Pmt) -> Result<$t, Self::Error> {
+                match p {
+                    $(Pmt::$v(v) => Ok(v.into()),)+
+                    _ => Err(PmtConversionError),
+                }
+            }
+        }
+    };
+}
+
+impl_try_from!(bool, Bool);
+impl_try_from!(u32, U32);
+impl_try_from!(u64, U32, U64);
+impl_try_from!(i32, I32);
+impl_try_from!(i64, I32, I64);
+impl_try_from!(f32, F32);
+impl_try_from!(f64, F32, Double);
+impl_try_from!(String, String);
+impl_try_from!(Vec<f32>, VecF32);
+impl_try_from!(Vec<Complex32>, VecComplex32);
+impl_try_from!(Vec<u64>, VecU64);
+impl_try_from!(Vec<u8>, Blob);
+impl_try_from!(HashMap<String, Pmt>, MapStrPmt);
+impl_try_from!(Vec<Pmt>, VecPmt);
+
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn all() -> Vec<Pmt> {
+        let mut map = HashMap::new();
+        map.insert("freq".to_string(), Pmt::U64(2_480_000_000));
+        map.insert("name".to_string(), Pmt::String("foo".to_string()));
+        vec![
+            Pmt::Null,
+            Pmt::String("foo".to_string()),
+            Pmt::U32(123),
+            Pmt::U64(u64::MAX),
+            Pmt::Double(1.5),
+            Pmt::VecF32(vec![1.0, -2.5]),
+            Pmt::Blob(vec![0, 1, 255]),
+            Pmt::Bool(true),
+            Pmt::I32(-123),
+            Pmt::I64(i64::MIN),
+            Pmt::F32(0.25),
+            Pmt::VecComplex32(vec![Complex32::new(1.0, -2.0)]),
+            Pmt::VecU64(vec![1, u64::MAX]),
+            Pmt::MapStrPmt(map.clone()),
+            Pmt::VecPmt(vec![Pmt::Null, Pmt::I32(-1), Pmt::MapStrPmt(map)]),
+        ]
+    }
+
     #[test]
     fn pmt() {
         let p = Pmt::Null;
@@ -78,14 +245,82 @@ mod test {
 
     #[test]
     fn pmt_serde() {
-        let p = Pmt::Null;
-        let mut s = flexbuffers::FlexbufferSerializer::new();
-        p.serialize(&mut s).unwrap();
+        for p in all() {
+            let mut s = flexbuffers::FlexbufferSerializer::new();
+            p.serialize(&mut s).unwrap();
+
+            let r = flexbuffers::Reader::get_root(s.view()).unwrap();
+            let p2 = Pmt::deserialize(r).unwrap();
+            assert_eq!(p, p2);
+
+            let j = serde_json::to_string(&p).unwrap();
+            assert_eq!(p, serde_json::from_str::<Pmt>(&j).unwrap());
+        }
+    }
+
+    #[test]
+    fn pmt_from_string() {
+        let tests = [
+            ("foo", PmtKind::String, Pmt::String("foo".to_string())),
+            ("true", PmtKind::Bool, Pmt::Bool(true)),
+            ("123", PmtKind::U32, Pmt::U32(123)),
+            ("123", PmtKind::U64, Pmt::U64(123)),
+            ("-123", PmtKind::I32, Pmt::I32(-123)),
+            (" -123 ", PmtKind::I64, Pmt::I64(-123)),
+            ("0.25", PmtKind::F32, Pmt::F32(0.25)),
+            ("1.5", PmtKind::Double, Pmt::Double(1.5)),
+            ("[1, -2.5]", PmtKind::VecF32, Pmt::VecF32(vec![1.0, -2.5])),
+            ("1,2", PmtKind::VecU64, Pmt::VecU64(vec![1, 2])),
+            ("", PmtKind::VecU64, Pmt::VecU64(vec![])),
+            ("0, 255", PmtKind::Blob, Pmt::Blob(vec![0, 255])),
+            (
+                "1+2i, -1.5-0.5i",
+                PmtKind::VecComplex32,
+                Pmt::VecComplex32(vec![Complex32::new(1.0, 2.0), Complex32::new(-1.5, -0.5)]),
+            ),
+            (
+                r#"["Null", {"U32": 1}]"#,
+                PmtKind::VecPmt,
+                Pmt::VecPmt(vec![Pmt::Null, Pmt::U32(1)]),
+            ),
+        ];
+        for (s, k, p) in tests.iter() {
+            assert_eq!(Pmt::from_string(s, k).as_ref(), Some(p));
+            assert_eq!(p.kind().as_ref(), Some(k));
+        }
+
+        let p = Pmt::from_string(r#"{"a": {"Bool": false}}"#, &PmtKind::MapStrPmt).unwrap();
+        let m = HashMap::<String, Pmt>::try_from(p).unwrap();
+        assert_eq!(m["a"], Pmt::Bool(false));
 
-        let r = flexbuffers::Reader::get_root(s.view()).unwrap();
-        let p2 = Pmt::deserialize(r).unwrap();
+        assert_eq!(Pmt::from_string("-1", &PmtKind::U32), None);
+        assert_eq!(Pmt::from_string("1,x", &PmtKind::VecF32), None);
+        assert_eq!(Pmt::from_string("yes", &PmtKind::Bool), None);
+    }
 
-        assert_eq!(p, p2);
+    #[test]
+    fn pmt_conversion() {
+        assert_eq!(Pmt::from(true), Pmt::Bool(true));
+        assert_eq!(Pmt::from(-1i64), Pmt::I64(-1));
+        assert_eq!(Pmt::from("foo"), Pmt::String("foo".to_string()));
+        assert_eq!(Pmt::from(vec![1u8, 2]), Pmt::Blob(vec![1, 2]));
+        assert_eq!(Pmt::from(()), Pmt::Null);
+
+        assert_eq!(u32::try_from(Pmt::U32(1)), Ok(1));
+        assert_eq!(u64::try_from(&Pmt::U32(1)), Ok(1));
+        assert_eq!(i64::try_from(Pmt::I32(-1)), Ok(-1));
+        assert_eq!(f64::try_from(Pmt::F32(0.5)), Ok(0.5));
+        assert_eq!(u32::try_from(Pmt::U64(1)), Err(PmtConversionError));
+        assert_eq!(bool::try_from(Pmt::Null), Err(PmtConversionError));
+        assert_eq!(
+            Vec::<Complex32>::try_from(Pmt::VecComplex32(vec![Complex32::new(0.0, 1.0)])),
+            Ok(vec![Complex32::new(0.0, 1.0)])
+        );
+
+        for p in all() {
+            let v = Vec::<Pmt>::try_from(Pmt::VecPmt(vec![p.clone()])).unwrap();
+            assert_eq!(v, vec![p]);
+        }
     }
 
     #[allow(clippy::many_single_char_names)]
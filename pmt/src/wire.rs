@@ -0,0 +1,519 @@
+// Binary wire format of Pmts
+//
+// A message is a version byte (VERSION) followed by one value. A value is a tag
+// byte followed by its payload. All numbers are little endian; lengths and
+// counts are u32.
+//
+//   tag  variant        payload
+//   0    Null           -
+//   1    String         len, UTF-8 bytes
+//   2    U32            u32
+//   3    U64            u64
+//   4    Double         f64
+//   5    VecF32         count, padding, count x f32
+//   6    Blob           len, bytes
+//   7    Bool           u8 (0 or 1)
+//   8    I32            i32
+//   9    I64            i64
+//   10   F32            f32
+//   11   VecComplex32   count, padding, count x (re f32, im f32)
+//   12   VecU64         count, count x u64
+//   13   MapStrPmt      count, count x (key len, UTF-8 key bytes, value)
+//   14   VecPmt         count, count x value
+//
+// The padding consists of 0-3 zero bytes, so that the f32 data starts at an
+// offset that is a multiple of four from the start of the message. This allows
+// to decode VecF32 payloads without copying (see `decode_ref`). Tags follow the
+// variant order of Pmt. Versions are not forward compatible: decoders reject
+// messages with a different version byte.
+use num_complex::Complex32;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::fmt;
+
+use crate::Pmt;
+
+pub const VERSION: u8 = 1;
+
+// limit the nesting of maps and lists when decoding untrusted input
+const MAX_DEPTH: usize = 64;
+
+const NULL: u8 = 0;
+const STRING: u8 = 1;
+const U32: u8 = 2;
+const U64: u8 = 3;
+const DOUBLE: u8 = 4;
+const VEC_F32: u8 = 5;
+const BLOB: u8 = 6;
+const BOOL: u8 = 7;
+const I32: u8 = 8;
+const I64: u8 = 9;
+const F32: u8 = 10;
+const VEC_COMPLEX32: u8 = 11;
+const VEC_U64: u8 = 12;
+const MAP_STR_PMT: u8 = 13;
+const VEC_PMT: u8 = 14;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireError {
+    Version(u8),
+    Tag(u8),
+    Truncated,
+    Utf8,
+    Bool(u8),
+    Depth,
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Version(v) => write!(f, "unsupported wire format version {}", v),
+            WireError::Tag(t) => write!(f, "unknown Pmt tag {}", t),
+            WireError::Truncated => write!(f, "message truncated"),
+            WireError::Utf8 => write!(f, "string is not valid UTF-8"),
+            WireError::Bool(b) => write!(f, "invalid bool value {}", b),
+            WireError::Depth => write!(f, "maps and lists nested too deep"),
+            WireError::TrailingBytes(n) => write!(f, "{} trailing bytes after the Pmt", n),
+        }
+    }
+}
+
+impl std::error::Error for WireError {}
+
+// Pmt that borrows Strings, Blobs, and (if aligned) VecF32s from the message
+#[derive(Debug, Clone, PartialEq)]
+pub enum PmtRef<'a> {
+    Null,
+    String(&'a str),
+    U32(u32),
+    U64(u64),
+    Double(f64),
+    VecF32(Cow<'a, [f32]>),
+    Blob(&'a [u8]),
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    VecComplex32(Vec<Complex32>),
+    VecU64(Vec<u64>),
+    MapStrPmt(HashMap<&'a str, PmtRef<'a>>),
+    VecPmt(Vec<PmtRef<'a>>),
+}
+
+impl<'a> PmtRef<'a> {
+    pub fn to_pmt(&self) -> Pmt {
+        match self {
+            PmtRef::Null => Pmt::Null,
+            PmtRef::String(s) => Pmt::String(s.to_string()),
+            PmtRef::U32(v) => Pmt::U32(*v),
+            PmtRef::U64(v) => Pmt::U64(*v),
+            PmtRef::Double(v) => Pmt::Double(*v),
+            PmtRef::VecF32(v) => Pmt::VecF32(v.to_vec()),
+            PmtRef::Blob(b) => Pmt::Blob(b.to_vec()),
+            PmtRef::Bool(v) => Pmt::Bool(*v),
+            PmtRef::I32(v) => Pmt::I32(*v),
+            PmtRef::I64(v) => Pmt::I64(*v),
+            PmtRef::F32(v) => Pmt::F32(*v),
+            PmtRef::VecComplex32(v) => Pmt::VecComplex32(v.clone()),
+            PmtRef::VecU64(v) => Pmt::VecU64(v.clone()),
+            PmtRef::MapStrPmt(m) => {
+                Pmt::MapStrPmt(m.iter().map(|(k, v)| (k.to_string(), v.to_pmt())).collect())
+            }
+            PmtRef::VecPmt(v) => Pmt::VecPmt(v.iter().map(|p| p.to_pmt()).collect()),
+        }
+    }
+}
+
+// Panics if a string, vector, or map has more than u32::MAX elements.
+pub fn encode(p: &Pmt) -> Vec<u8> {
+    let mut b = vec![VERSION];
+    encode_value(p, &mut b);
+    b
+}
+
+pub fn decode(b: &[u8]) -> Result<Pmt, WireError> {
+    decode_ref(b).map(|p| p.to_pmt())
+}
+
+pub fn decode_ref(b: &[u8]) -> Result<PmtRef<'_>, WireError> {
+    let mut r = Reader { buf: b, pos: 0 };
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(WireError::Version(version));
+    }
+    let p = r.value(0)?;
+    if r.pos != b.len() {
+        return Err(WireError::TrailingBytes(b.len() - r.pos));
+    }
+    Ok(p)
+}
+
+fn encode_len(n: usize, b: &mut Vec<u8>) {
+    let n = u32::try_from(n).expect("Pmt too large for the wire format");
+    b.extend_from_slice(&n.to_le_bytes());
+}
+
+fn encode_padding(b: &mut Vec<u8>) {
+    b.resize(b.len() + (4 - b.len() % 4) % 4, 0);
+}
+
+fn encode_value(p: &Pmt, b: &mut Vec<u8>) {
+    match p {
+        Pmt::Null => b.push(NULL),
+        Pmt::String(s) => {
+            b.push(STRING);
+            encode_len(s.len(), b);
+            b.extend_from_slice(s.as_bytes());
+        }
+        Pmt::U32(v) => {
+            b.push(U32);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::U64(v) => {
+            b.push(U64);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::Double(v) => {
+            b.push(DOUBLE);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::VecF32(v) => {
+            b.push(VEC_F32);
+            encode_len(v.len(), b);
+            encode_padding(b);
+            for x in v {
+                b.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        Pmt::Blob(v) => {
+            b.push(BLOB);
+            encode_len(v.len(), b);
+            b.extend_from_slice(v);
+        }
+        Pmt::Bool(v) => {
+            b.push(BOOL);
+            b.push(*v as u8);
+        }
+        Pmt::I32(v) => {
+            b.push(I32);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::I64(v) => {
+            b.push(I64);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::F32(v) => {
+            b.push(F32);
+            b.extend_from_slice(&v.to_le_bytes());
+        }
+        Pmt::VecComplex32(v) => {
+            b.push(VEC_COMPLEX32);
+            encode_len(v.len(), b);
+            encode_padding(b);
+            for x in v {
+                b.extend_from_slice(&x.re.to_le_bytes());
+                b.extend_from_slice(&x.im.to_le_bytes());
+            }
+        }
+        Pmt::VecU64(v) => {
+            b.push(VEC_U64);
+            encode_len(v.len(), b);
+            for x in v {
+                b.extend_from_slice(&x.to_le_bytes());
+            }
+        }
+        Pmt::MapStrPmt(m) => {
+            b.push(MAP_STR_PMT);
+            encode_len(m.len(), b);
+            for (k, v) in m {
+                encode_len(k.len(), b);
+                b.extend_from_slice(k.as_bytes());
+                encode_value(v, b);
+            }
+        }
+        Pmt::VecPmt(v) => {
+            b.push(VEC_PMT);
+            encode_len(v.len(), b);
+            for x in v {
+                encode_value(x, b);
+            }
+        }
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], WireError> {
+        let end = self.pos.checked_add(n).ok_or(WireError::Truncated)?;
+        let b = self.buf.get(self.pos..end).ok_or(WireError::Truncated)?;
+        self.pos = end;
+        Ok(b)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], WireError> {
+        let mut a = [0; N];
+        a.copy_from_slice(self.bytes(N)?);
+        Ok(a)
+    }
+
+    fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn len(&mut self) -> Result<usize, WireError> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    // payload of `n` elements of `size` bytes, after the padding
+    fn elements(&mut self, n: usize, size: usize, padded: bool) -> Result<&'a [u8], WireError> {
+        if padded {
+            self.bytes((4 - self.pos % 4) % 4)?;
+        }
+        self.bytes(n.checked_mul(size).ok_or(WireError::Truncated)?)
+    }
+
+    fn str(&mut self) -> Result<&'a str, WireError> {
+        let n = self.len()?;
+        std::str::from_utf8(self.bytes(n)?).map_err(|_| WireError::Utf8)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<PmtRef<'a>, WireError> {
+        let p = match self.u8()? {
+            NULL => PmtRef::Null,
+            STRING => PmtRef::String(self.str()?),
+            U32 => PmtRef::U32(u32::from_le_bytes(self.array()?)),
+            U64 => PmtRef::U64(u64::from_le_bytes(self.array()?)),
+            DOUBLE => PmtRef::Double(f64::from_le_bytes(self.array()?)),
+            VEC_F32 => {
+                let n = self.len()?;
+                PmtRef::VecF32(f32_slice(self.elements(n, 4, true)?))
+            }
+            BLOB => {
+                let n = self.len()?;
+                PmtRef::Blob(self.bytes(n)?)
+            }
+            BOOL => match self.u8()? {
+                0 => PmtRef::Bool(false),
+                1 => PmtRef::Bool(true),
+                b => return Err(WireError::Bool(b)),
+            },
+            I32 => PmtRef::I32(i32::from_le_bytes(self.array()?)),
+            I64 => PmtRef::I64(i64::from_le_bytes(self.array()?)),
+            F32 => PmtRef::F32(f32::from_le_bytes(self.array()?)),
+            VEC_COMPLEX32 => {
+                let n = self.len()?;
+                let v = f32_slice(self.elements(n, 8, true)?);
+                PmtRef::VecComplex32(
+                    v.chunks_exact(2)
+                        .map(|c| Complex32::new(c[0], c[1]))
+                        .collect(),
+                )
+            }
+            VEC_U64 => {
+                let n = self.len()?;
+                PmtRef::VecU64(
+                    self.elements(n, 8, false)?
+                        .chunks_exact(8)
+                        .map(|c| u64::from_le_bytes(c.try_into().unwrap()))
+                        .collect(),
+                )
+            }
+            MAP_STR_PMT => {
+                if depth >= MAX_DEPTH {
+                    return Err(WireError::Depth);
+                }
+                let n = self.len()?;
+                let mut m = HashMap::new();
+                for _ in 0..n {
+                    let k = self.str()?;
+                    m.insert(k, self.value(depth + 1)?);
+                }
+                PmtRef::MapStrPmt(m)
+            }
+            VEC_PMT => {
+                if depth >= MAX_DEPTH {
+                    return Err(WireError::Depth);
+                }
+                let n = self.len()?;
+                // do not trust the count for the allocation, each value has at least one byte
+                let mut v = Vec::with_capacity(std::cmp::min(n, self.buf.len() - self.pos));
+                for _ in 0..n {
+                    v.push(self.value(depth + 1)?);
+                }
+                PmtRef::VecPmt(v)
+            }
+            t => return Err(WireError::Tag(t)),
+        };
+        Ok(p)
+    }
+}
+
+// borrow the f32s if the data is aligned and the host is little endian
+fn f32_slice(b: &[u8]) -> Cow<'_, [f32]> {
+    if cfg!(target_endian = "little") {
+        // every bit pattern is a valid f32
+        let (pre, v, post) = unsafe { b.align_to::<f32>() };
+        if pre.is_empty() && post.is_empty() {
+            return Cow::Borrowed(v);
+        }
+    }
+    Cow::Owned(
+        b.chunks_exact(4)
+            .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn random_string(rng: &mut StdRng) -> String {
+        let n = rng.gen_range(0..8);
+        (0..n).map(|_| rng.gen::<char>()).collect()
+    }
+
+    fn random_vec<T>(rng: &mut StdRng, f: impl Fn(&mut StdRng) -> T) -> Vec<T> {
+        let n = rng.gen_range(0..16);
+        (0..n).map(|_| f(rng)).collect()
+    }
+
+    fn random_pmt(rng: &mut StdRng, depth: usize) -> Pmt {
+        let max = if depth < 3 { 15 } else { 13 };
+        match rng.gen_range(0..max) {
+            0 => Pmt::Null,
+            1 => Pmt::String(random_string(rng)),
+            2 => Pmt::U32(rng.gen()),
+            3 => Pmt::U64(rng.gen()),
+            4 => Pmt::Double(rng.gen_range(-1e9..1e9)),
+            5 => Pmt::VecF32(random_vec(rng, |r| r.gen_range(-1e3..1e3))),
+            6 => Pmt::Blob(random_vec(rng, |r| r.gen())),
+            7 => Pmt::Bool(rng.gen()),
+            8 => Pmt::I32(rng.gen()),
+            9 => Pmt::I64(rng.gen()),
+            10 => Pmt::F32(rng.gen_range(-1e3..1e3)),
+            11 => Pmt::VecComplex32(random_vec(rng, |r| {
+                Complex32::new(r.gen_range(-1.0..1.0), r.gen_range(-1.0..1.0))
+            })),
+            12 => Pmt::VecU64(random_vec(rng, |r| r.gen())),
+            13 => Pmt::MapStrPmt(
+                (0..rng.gen_range(0..4))
+                    .map(|_| (random_string(rng), random_pmt(rng, depth + 1)))
+                    .collect(),
+            ),
+            _ => Pmt::VecPmt(
+                (0..rng.gen_range(0..4))
+                    .map(|_| random_pmt(rng, depth + 1))
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn wire_format() {
+        assert_eq!(encode(&Pmt::Null), vec![VERSION, NULL]);
+        assert_eq!(encode(&Pmt::U32(1)), vec![VERSION, U32, 1, 0, 0, 0]);
+        assert_eq!(
+            encode(&Pmt::VecF32(vec![1.0])),
+            vec![VERSION, VEC_F32, 1, 0, 0, 0, 0, 0, 0, 0, 128, 63]
+        );
+        assert_eq!(
+            encode(&Pmt::Blob(vec![7])),
+            vec![VERSION, BLOB, 1, 0, 0, 0, 7]
+        );
+    }
+
+    #[test]
+    fn wire_round_trip() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..10_000 {
+            let p = random_pmt(&mut rng, 0);
+            let b = encode(&p);
+            assert_eq!(decode(&b), Ok(p.clone()));
+            assert_eq!(decode_ref(&b).unwrap().to_pmt(), p);
+        }
+    }
+
+    #[test]
+    fn wire_zero_copy() {
+        let v: Vec<f32> = (0..100).map(|i| i as f32).collect();
+        let b = encode(&Pmt::VecPmt(vec![
+            Pmt::Blob(vec![1, 2, 3]),
+            Pmt::VecF32(v.clone()),
+        ]));
+        // copy to a buffer with known alignment
+        let mut aligned = vec![0f32; b.len() / 4 + 1];
+        let a = unsafe { std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, b.len()) };
+        a.copy_from_slice(&b);
+
+        match decode_ref(a).unwrap() {
+            PmtRef::VecPmt(p) => {
+                assert!(matches!(p[0], PmtRef::Blob(x) if x == [1, 2, 3]));
+                match &p[1] {
+                    PmtRef::VecF32(Cow::Borrowed(x)) if cfg!(target_endian = "little") => {
+                        assert_eq!(*x, &v[..])
+                    }
+                    PmtRef::VecF32(x) => assert_eq!(&x[..], &v[..]),
+                    p => panic!("unexpected {:?}", p),
+                }
+            }
+            p => panic!("unexpected {:?}", p),
+        }
+    }
+
+    #[test]
+    fn wire_invalid() {
+        assert_eq!(decode(&[]), Err(WireError::Truncated));
+        assert_eq!(decode(&[2, NULL]), Err(WireError::Version(2)));
+        assert_eq!(decode(&[VERSION, 200]), Err(WireError::Tag(200)));
+        assert_eq!(decode(&[VERSION, BOOL, 2]), Err(WireError::Bool(2)));
+        assert_eq!(
+            decode(&[VERSION, NULL, 0]),
+            Err(WireError::TrailingBytes(1))
+        );
+        assert_eq!(
+            decode(&[VERSION, STRING, 2, 0, 0, 0, 0xff, 0xff]),
+            Err(WireError::Utf8)
+        );
+        assert_eq!(
+            decode(&[VERSION, BLOB, 255, 255, 255, 255]),
+            Err(WireError::Truncated)
+        );
+
+        let mut nested = vec![VERSION];
+        for _ in 0..100 {
+            nested.extend_from_slice(&[VEC_PMT, 1, 0, 0, 0]);
+        }
+        nested.push(NULL);
+        assert_eq!(decode(&nested), Err(WireError::Depth));
+    }
+
+    #[test]
+    fn wire_fuzz() {
+        // truncated and corrupted messages must not panic
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..10_000 {
+            let mut b = encode(&random_pmt(&mut rng, 0));
+            if rng.gen_bool(0.5) {
+                b.truncate(rng.gen_range(0..b.len()));
+            } else {
+                let i = rng.gen_range(1..b.len());
+                b[i] = rng.gen();
+            }
+            let _ = decode(&b);
+        }
+        for _ in 0..10_000 {
+            let mut b: Vec<u8> = random_vec(&mut rng, |r| r.gen());
+            b.insert(0, VERSION);
+            let _ = decode(&b);
+        }
+    }
+}
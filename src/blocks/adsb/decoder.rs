@@ -0,0 +1,290 @@
+use anyhow::Result;
+use futures::FutureExt;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+const LONG_FRAME_BYTES: usize = 14;
+const CPR_MAX_AGE: Duration = Duration::from_secs(10);
+const CHARSET: &[u8; 64] = b"#ABCDEFGHIJKLMNOPQRSTUVWXYZ##### ###############0123456789######";
+
+// Mode S CRC, parity is in the last 24 bits, so a valid frame yields 0
+pub fn crc24(data: &[u8]) -> u32 {
+    let mut crc = 0u32;
+    for b in data {
+        crc ^= (*b as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= 0x1ff_f409;
+            }
+        }
+    }
+    crc & 0xff_ffff
+}
+
+fn callsign(me: &[u8]) -> String {
+    let v = me[1..7].iter().fold(0u64, |a, b| (a << 8) | *b as u64);
+    let s: String = (0..8)
+        .map(|i| CHARSET[((v >> (42 - 6 * i)) & 0x3f) as usize] as char)
+        .collect();
+    s.trim_end_matches([' ', '#']).to_string()
+}
+
+// 12-bit altitude field, only 25 ft resolution (Q bit set) is supported
+fn altitude(a: u16) -> Option<i32> {
+    if a & 0x10 == 0 {
+        return None;
+    }
+    let n = ((a & 0xfe0) >> 1) | (a & 0xf);
+    Some(n as i32 * 25 - 1000)
+}
+
+fn modulo(a: f64, b: f64) -> f64 {
+    a - b * (a / b).floor()
+}
+
+// number of longitude zones
+fn nl(lat: f64) -> f64 {
+    let lat = lat.abs();
+    if lat < 1e-9 {
+        return 59.0;
+    } else if (lat - 87.0).abs() < 1e-9 {
+        return 2.0;
+    } else if lat > 87.0 {
+        return 1.0;
+    }
+
+    let a = 1.0 - (PI / 30.0).cos();
+    let b = (PI / 180.0 * lat).cos().powi(2);
+    (2.0 * PI / (1.0 - a / b).acos()).floor()
+}
+
+// globally unambiguous decoding of an even/odd CPR pair
+fn cpr_global(even: (u32, u32), odd: (u32, u32), odd_is_newest: bool) -> Option<(f64, f64)> {
+    let scale = (1u32 << 17) as f64;
+    let (lat_e, lon_e) = (even.0 as f64 / scale, even.1 as f64 / scale);
+    let (lat_o, lon_o) = (odd.0 as f64 / scale, odd.1 as f64 / scale);
+
+    let j = (59.0 * lat_e - 60.0 * lat_o + 0.5).floor();
+    let mut rlat_e = 360.0 / 60.0 * (modulo(j, 60.0) + lat_e);
+    let mut rlat_o = 360.0 / 59.0 * (modulo(j, 59.0) + lat_o);
+    if rlat_e >= 270.0 {
+        rlat_e -= 360.0;
+    }
+    if rlat_o >= 270.0 {
+        rlat_o -= 360.0;
+    }
+
+    if nl(rlat_e) != nl(rlat_o) {
+        return None;
+    }
+
+    let (lat, lon_cpr, ni) = if odd_is_newest {
+        (rlat_o, lon_o, (nl(rlat_o) - 1.0).max(1.0))
+    } else {
+        (rlat_e, lon_e, nl(rlat_e).max(1.0))
+    };
+    let n = nl(lat);
+    let m = (lon_e * (n - 1.0) - lon_o * n + 0.5).floor();
+    let mut lon = 360.0 / ni * (modulo(m, ni) + lon_cpr);
+    if lon >= 180.0 {
+        lon -= 360.0;
+    }
+
+    Some((lat, lon))
+}
+
+#[derive(Default)]
+struct Aircraft {
+    even: Option<(u32, u32, Instant)>,
+    odd: Option<(u32, u32, Instant)>,
+}
+
+pub struct Decoder {
+    syndromes: HashMap<u32, usize>,
+    aircraft: HashMap<u32, Aircraft>,
+    n_decoded: u64,
+    n_corrected: u64,
+    n_crc_errors: u64,
+}
+
+impl Decoder {
+    pub fn new() -> Block {
+        // syndromes of single-bit errors, the downlink format bits are left alone
+        let mut syndromes = HashMap::new();
+        for bit in 5..LONG_FRAME_BYTES * 8 {
+            let mut frame = [0u8; LONG_FRAME_BYTES];
+            frame[bit / 8] = 0x80 >> (bit % 8);
+            syndromes.insert(crc24(&frame), bit);
+        }
+
+        Block::new_async(
+            BlockMetaBuilder::new("Decoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", Decoder::handler)
+                .build(),
+            Decoder {
+                syndromes,
+                aircraft: HashMap::new(),
+                n_decoded: 0,
+                n_corrected: 0,
+                n_crc_errors: 0,
+            },
+        )
+    }
+
+    pub fn decoded(&self) -> u64 {
+        self.n_decoded
+    }
+
+    pub fn corrected(&self) -> u64 {
+        self.n_corrected
+    }
+
+    pub fn crc_errors(&self) -> u64 {
+        self.n_crc_errors
+    }
+
+    fn position(&mut self, icao: u32, me: &[u8]) -> Value {
+        let v = me.iter().fold(0u64, |a, b| (a << 8) | *b as u64);
+        let odd = (v >> 34) & 1 == 1;
+        let lat = ((v >> 17) & 0x1ffff) as u32;
+        let lon = (v & 0x1ffff) as u32;
+
+        let now = Instant::now();
+        let a = self.aircraft.entry(icao).or_default();
+        if odd {
+            a.odd = Some((lat, lon, now));
+        } else {
+            a.even = Some((lat, lon, now));
+        }
+
+        let position = match (a.even, a.odd) {
+            (Some(e), Some(o))
+                if now.duration_since(e.2) < CPR_MAX_AGE
+                    && now.duration_since(o.2) < CPR_MAX_AGE =>
+            {
+                cpr_global((e.0, e.1), (o.0, o.1), odd)
+            }
+            _ => None,
+        };
+
+        json!({
+            "icao": format!("{:06x}", icao),
+            "type": "position",
+            "altitude": altitude(((v >> 36) & 0xfff) as u16),
+            "cpr_format": if odd { "odd" } else { "even" },
+            "latitude": position.map(|p| p.0),
+            "longitude": position.map(|p| p.1),
+        })
+    }
+
+    fn decode(&mut self, mut frame: Vec<u8>) -> Option<Value> {
+        if frame.len() != LONG_FRAME_BYTES {
+            return None;
+        }
+        let df = frame[0] >> 3;
+        if df != 17 && df != 18 {
+            return None;
+        }
+
+        let syndrome = crc24(&frame);
+        if syndrome != 0 {
+            match self.syndromes.get(&syndrome) {
+                Some(bit) => {
+                    frame[bit / 8] ^= 0x80 >> (bit % 8);
+                    self.n_corrected += 1;
+                }
+                None => {
+                    self.n_crc_errors += 1;
+                    return None;
+                }
+            }
+        }
+
+        let icao = u32::from_be_bytes([0, frame[1], frame[2], frame[3]]);
+        let me = &frame[4..11];
+        match me[0] >> 3 {
+            1..=4 => Some(json!({
+                "icao": format!("{:06x}", icao),
+                "type": "identification",
+                "callsign": callsign(me),
+            })),
+            9..=18 => Some(self.position(icao, me)),
+            _ => None,
+        }
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(frame) => {
+                    if let Some(v) = self.decode(frame) {
+                        self.n_decoded += 1;
+                        mio.post(0, Pmt::String(v.to_string())).await;
+                    }
+                }
+                p => warn!("adsb decoder: expected Pmt::Blob, got {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Decoder {
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!(
+            "adsb decoder: n_decoded {}, n_corrected {}, n_crc_errors {}",
+            self.n_decoded, self.n_corrected, self.n_crc_errors
+        );
+        Ok(())
+    }
+}
+
+pub struct DecoderBuilder {}
+
+impl DecoderBuilder {
+    pub fn new() -> DecoderBuilder {
+        DecoderBuilder {}
+    }
+
+    pub fn build(self) -> Block {
+        Decoder::new()
+    }
+}
+
+impl Default for DecoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,158 @@
+use anyhow::Result;
+use std::mem::size_of;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// at 2 Msps, one sample is half a bit (0.5 us)
+const PREAMBLE_LEN: usize = 16;
+const SHORT_FRAME_BITS: usize = 56;
+const LONG_FRAME_BITS: usize = 112;
+const WINDOW: usize = PREAMBLE_LEN + 2 * LONG_FRAME_BITS;
+
+// pulses at 0, 1, 3.5, and 4.5 us, quiet between 5 and 8 us
+fn preamble(m: &[f32]) -> bool {
+    if !(m[0] > m[1]
+        && m[1] < m[2]
+        && m[2] > m[3]
+        && m[3] < m[0]
+        && m[4] < m[0]
+        && m[5] < m[0]
+        && m[6] < m[0]
+        && m[7] > m[8]
+        && m[8] < m[9]
+        && m[9] > m[6])
+    {
+        return false;
+    }
+
+    let high = (m[0] + m[2] + m[7] + m[9]) / 6.0;
+    m[4] < high && m[5] < high && m[11..15].iter().all(|x| *x < high)
+}
+
+// pulse position modulation: a pulse in the first half of the bit period is a one
+fn slice_bits(m: &[f32], n_bits: usize) -> Vec<u8> {
+    let mut frame = vec![0u8; n_bits / 8];
+    for i in 0..n_bits {
+        if m[2 * i] > m[2 * i + 1] {
+            frame[i / 8] |= 0x80 >> (i % 8);
+        }
+    }
+    frame
+}
+
+fn downlink_format(frame: &[u8]) -> u8 {
+    let df = frame[0] >> 3;
+    if df >= 24 {
+        24
+    } else {
+        df
+    }
+}
+
+pub struct Demodulator {
+    n_frames: u64,
+}
+
+impl Demodulator {
+    pub fn new() -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Demodulator").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<f32>())
+                .build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Demodulator { n_frames: 0 },
+        )
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.n_frames
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Demodulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+
+        let mut frames = Vec::new();
+        let mut pos = 0;
+        while pos + WINDOW <= i.len() {
+            if preamble(&i[pos..]) {
+                let data = &i[pos + PREAMBLE_LEN..];
+                let n_bits = match downlink_format(&slice_bits(data, 8)) {
+                    0 | 4 | 5 | 11 => SHORT_FRAME_BITS,
+                    16 | 17 | 18 | 19 | 20 | 21 | 24 => LONG_FRAME_BITS,
+                    _ => 0,
+                };
+
+                if n_bits > 0 {
+                    frames.push(slice_bits(data, n_bits));
+                    pos += PREAMBLE_LEN + 2 * n_bits;
+                    continue;
+                }
+            }
+            pos += 1;
+        }
+
+        let finished = sio.input(0).finished();
+        if finished {
+            pos = i.len();
+        }
+        sio.input(0).consume(pos);
+
+        for f in frames.drain(..) {
+            self.n_frames += 1;
+            mio.post(0, Pmt::Blob(f)).await;
+        }
+
+        if finished {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("adsb demodulator: n_frames {}", self.n_frames);
+        Ok(())
+    }
+}
+
+pub struct DemodulatorBuilder {}
+
+impl DemodulatorBuilder {
+    pub fn new() -> DemodulatorBuilder {
+        DemodulatorBuilder {}
+    }
+
+    pub fn build(self) -> Block {
+        Demodulator::new()
+    }
+}
+
+impl Default for DemodulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
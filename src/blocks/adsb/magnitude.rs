@@ -0,0 +1,78 @@
+use anyhow::Result;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// magnitude of interleaved, offset-binary u8 IQ samples (rtl_sdr format)
+pub struct Magnitude {}
+
+impl Magnitude {
+    pub fn new() -> Block {
+        Block::new_sync(
+            BlockMetaBuilder::new("Magnitude").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<u8>())
+                .add_output("out", size_of::<f32>())
+                .build(),
+            MessageIoBuilder::<Magnitude>::new().build(),
+            Magnitude {},
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for Magnitude {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<f32>();
+
+        let n = cmp::min(i.len() / 2, o.len());
+        for (iq, m) in i.chunks_exact(2).zip(o.iter_mut()).take(n) {
+            let re = (iq[0] as f32 - 127.5) / 127.5;
+            let im = (iq[1] as f32 - 127.5) / 127.5;
+            *m = (re * re + im * im).sqrt();
+        }
+
+        sio.input(0).consume(2 * n);
+        sio.output(0).produce(n);
+
+        if sio.input(0).finished() && i.len() - 2 * n < 2 {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct MagnitudeBuilder {}
+
+impl MagnitudeBuilder {
+    pub fn new() -> MagnitudeBuilder {
+        MagnitudeBuilder {}
+    }
+
+    pub fn build(self) -> Block {
+        Magnitude::new()
+    }
+}
+
+impl Default for MagnitudeBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,6 @@
+mod decoder;
+pub use decoder::{crc24, Decoder, DecoderBuilder};
+mod demodulator;
+pub use demodulator::{Demodulator, DemodulatorBuilder};
+mod magnitude;
+pub use magnitude::{Magnitude, MagnitudeBuilder};
@@ -48,17 +48,23 @@ impl FileSource {
 impl AsyncKernel for FileSource {
     async fn work(
         &mut self,
-        _io: &mut WorkIo,
+        io: &mut WorkIo,
         sio: &mut StreamIo,
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let out = sio.output(0).slice::<f32>();
 
+        let mut n = 0;
         for (i, v) in self.src.by_ref().take(out.len()).enumerate() {
             out[i] = v;
+            n += 1;
+        }
+        sio.output(0).produce(n);
+
+        if n < out.len() {
+            io.finished = true;
         }
-        sio.output(0).produce(out.len());
 
         Ok(())
     }
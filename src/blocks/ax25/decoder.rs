@@ -0,0 +1,105 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::blocks::ax25::Frame;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// parses AX.25 frames and forwards them in TNC2 monitor format
+pub struct Decoder {
+    n_decoded: u64,
+    n_invalid: u64,
+}
+
+impl Decoder {
+    pub fn new() -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Decoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", Decoder::handler)
+                .build(),
+            Decoder {
+                n_decoded: 0,
+                n_invalid: 0,
+            },
+        )
+    }
+
+    pub fn decoded(&self) -> u64 {
+        self.n_decoded
+    }
+
+    pub fn invalid(&self) -> u64 {
+        self.n_invalid
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(data) => match Frame::parse(&data) {
+                    Ok(frame) => {
+                        self.n_decoded += 1;
+                        mio.post(0, Pmt::String(frame.to_string())).await;
+                    }
+                    Err(e) => {
+                        debug!("ax25 decoder: invalid frame, {}", e);
+                        self.n_invalid += 1;
+                    }
+                },
+                p => warn!("ax25 decoder: expected Pmt::Blob, got {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Decoder {
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!(
+            "ax25 decoder: n_decoded {}, n_invalid {}",
+            self.n_decoded, self.n_invalid
+        );
+        Ok(())
+    }
+}
+
+pub struct DecoderBuilder {}
+
+impl DecoderBuilder {
+    pub fn new() -> DecoderBuilder {
+        DecoderBuilder {}
+    }
+
+    pub fn build(self) -> Block {
+        Decoder::new()
+    }
+}
+
+impl Default for DecoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,164 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::mem::size_of;
+
+use crate::blocks::ax25::hdlc::Deframer;
+use crate::blocks::ax25::{BAUD_RATE, MARK_FREQUENCY, SPACE_FREQUENCY};
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// how far the bit clock is pulled towards a tone transition
+const CLOCK_GAIN: f32 = 0.3;
+
+pub struct Demodulator {
+    mark: Vec<Complex<f32>>,
+    space: Vec<Complex<f32>>,
+    history: VecDeque<f32>,
+    clock_step: f32,
+    clock: f32,
+    tone: bool,
+    last_tone: bool,
+    deframer: Deframer,
+    n_frames: u64,
+}
+
+impl Demodulator {
+    pub fn new(sample_rate: f32) -> Block {
+        // non-coherent tone detection, correlating over one bit period
+        let n = (sample_rate / BAUD_RATE).round() as usize;
+        let tone = |f: f32| -> Vec<Complex<f32>> {
+            (0..n)
+                .map(|k| Complex::from_polar(1.0, -2.0 * PI * f * k as f32 / sample_rate))
+                .collect()
+        };
+
+        Block::new_async(
+            BlockMetaBuilder::new("Demodulator").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<f32>())
+                .build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Demodulator {
+                mark: tone(MARK_FREQUENCY),
+                space: tone(SPACE_FREQUENCY),
+                history: VecDeque::with_capacity(n),
+                clock_step: BAUD_RATE / sample_rate,
+                clock: 0.0,
+                tone: true,
+                last_tone: true,
+                deframer: Deframer::new(),
+                n_frames: 0,
+            },
+        )
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.n_frames
+    }
+
+    fn process(&mut self, sample: f32, frames: &mut Vec<Vec<u8>>) {
+        self.history.push_back(sample);
+        if self.history.len() > self.mark.len() {
+            self.history.pop_front();
+        }
+
+        let correlate = |h: &VecDeque<f32>, t: &[Complex<f32>]| -> f32 {
+            h.iter()
+                .zip(t.iter())
+                .map(|(x, t)| t * x)
+                .sum::<Complex<f32>>()
+                .norm_sqr()
+        };
+        let tone = correlate(&self.history, &self.mark) > correlate(&self.history, &self.space);
+
+        // tone changes happen at bit boundaries
+        if tone != self.tone {
+            if self.clock < 0.5 {
+                self.clock *= 1.0 - CLOCK_GAIN;
+            } else {
+                self.clock += (1.0 - self.clock) * CLOCK_GAIN;
+            }
+            self.tone = tone;
+        }
+
+        let last = self.clock;
+        self.clock += self.clock_step;
+
+        // sample in the middle of the bit and undo NRZI
+        if last < 0.5 && self.clock >= 0.5 {
+            let bit = tone == self.last_tone;
+            self.last_tone = tone;
+            if let Some(f) = self.deframer.push(bit) {
+                frames.push(f);
+            }
+        }
+
+        if self.clock >= 1.0 {
+            self.clock -= 1.0;
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Demodulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+
+        let mut frames = Vec::new();
+        for s in i.iter() {
+            self.process(*s, &mut frames);
+        }
+        sio.input(0).consume(i.len());
+
+        for f in frames.drain(..) {
+            self.n_frames += 1;
+            mio.post(0, Pmt::Blob(f)).await;
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("ax25 demodulator: n_frames {}", self.n_frames);
+        Ok(())
+    }
+}
+
+pub struct DemodulatorBuilder {
+    sample_rate: f32,
+}
+
+impl DemodulatorBuilder {
+    pub fn new(sample_rate: f32) -> DemodulatorBuilder {
+        DemodulatorBuilder { sample_rate }
+    }
+
+    pub fn build(self) -> Block {
+        Demodulator::new(self.sample_rate)
+    }
+}
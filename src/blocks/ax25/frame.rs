@@ -0,0 +1,207 @@
+use anyhow::{bail, ensure, Context, Result};
+use std::fmt;
+use std::str::FromStr;
+
+const ADDRESS_LEN: usize = 7;
+const MAX_DIGIPEATERS: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Address {
+    pub callsign: String,
+    pub ssid: u8,
+    // has-been-repeated bit, only meaningful for digipeaters
+    pub repeated: bool,
+}
+
+impl Address {
+    pub fn new(callsign: &str, ssid: u8) -> Result<Address> {
+        ensure!(
+            !callsign.is_empty()
+                && callsign.len() <= 6
+                && callsign
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit()),
+            "invalid callsign {:?}",
+            callsign
+        );
+        ensure!(ssid < 16, "invalid ssid {}", ssid);
+
+        Ok(Address {
+            callsign: callsign.to_string(),
+            ssid,
+            repeated: false,
+        })
+    }
+
+    fn parse(data: &[u8]) -> Result<Address> {
+        let callsign: String = data[0..6]
+            .iter()
+            .map(|b| (b >> 1) as char)
+            .collect::<String>()
+            .trim_end()
+            .to_string();
+        let mut a = Address::new(&callsign, (data[6] >> 1) & 0x0f)?;
+        a.repeated = data[6] & 0x80 != 0;
+        Ok(a)
+    }
+
+    fn encode(&self, out: &mut Vec<u8>, high_bit: bool, last: bool) {
+        let mut callsign = self.callsign.as_bytes().to_vec();
+        callsign.resize(6, b' ');
+        out.extend(callsign.iter().map(|b| b << 1));
+        out.push(0x60 | (self.ssid << 1) | ((high_bit as u8) << 7) | last as u8);
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.callsign)?;
+        if self.ssid != 0 {
+            write!(f, "-{}", self.ssid)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Address {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Address> {
+        let (s, repeated) = match s.strip_suffix('*') {
+            Some(s) => (s, true),
+            None => (s, false),
+        };
+        let mut a = match s.split_once('-') {
+            Some((call, ssid)) => Address::new(
+                call,
+                ssid.parse()
+                    .with_context(|| format!("invalid ssid {:?}", ssid))?,
+            )?,
+            None => Address::new(s, 0)?,
+        };
+        a.repeated = repeated;
+        Ok(a)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub destination: Address,
+    pub source: Address,
+    pub digipeaters: Vec<Address>,
+    pub control: u8,
+    pub pid: Option<u8>,
+    pub info: Vec<u8>,
+}
+
+impl Frame {
+    // UI frame without layer 3 protocol, as used by APRS
+    pub fn ui(
+        source: Address,
+        destination: Address,
+        digipeaters: Vec<Address>,
+        info: &[u8],
+    ) -> Frame {
+        Frame {
+            destination,
+            source,
+            digipeaters,
+            control: 0x03,
+            pid: Some(0xf0),
+            info: info.to_vec(),
+        }
+    }
+
+    // frame without flags and FCS
+    pub fn parse(data: &[u8]) -> Result<Frame> {
+        let mut addresses = Vec::new();
+        let mut pos = 0;
+        loop {
+            ensure!(data.len() >= pos + ADDRESS_LEN, "truncated address field");
+            addresses.push(Address::parse(&data[pos..pos + ADDRESS_LEN])?);
+            pos += ADDRESS_LEN;
+            if data[pos - 1] & 0x01 != 0 {
+                break;
+            }
+        }
+        ensure!(
+            (2..=2 + MAX_DIGIPEATERS).contains(&addresses.len()),
+            "invalid number of addresses ({})",
+            addresses.len()
+        );
+        ensure!(data.len() > pos, "missing control field");
+
+        let mut addresses = addresses.into_iter();
+        let mut destination = addresses.next().unwrap();
+        let mut source = addresses.next().unwrap();
+        destination.repeated = false;
+        source.repeated = false;
+
+        let control = data[pos];
+        pos += 1;
+
+        // I and UI frames carry a protocol identifier
+        let pid = if control & 0x01 == 0 || control & 0xef == 0x03 {
+            ensure!(data.len() > pos, "missing pid field");
+            pos += 1;
+            Some(data[pos - 1])
+        } else {
+            None
+        };
+
+        Ok(Frame {
+            destination,
+            source,
+            digipeaters: addresses.collect(),
+            control,
+            pid,
+            info: data[pos..].to_vec(),
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        self.destination.encode(&mut out, true, false);
+        self.source
+            .encode(&mut out, false, self.digipeaters.is_empty());
+        for (i, d) in self.digipeaters.iter().enumerate() {
+            d.encode(&mut out, d.repeated, i == self.digipeaters.len() - 1);
+        }
+        out.push(self.control);
+        if let Some(pid) = self.pid {
+            out.push(pid);
+        }
+        out.extend_from_slice(&self.info);
+        out
+    }
+}
+
+// TNC2 monitor format, e.g., N0CALL-7>APRS,WIDE1-1*:hello
+impl fmt::Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}>{}", self.source, self.destination)?;
+        for d in self.digipeaters.iter() {
+            write!(f, ",{}{}", d, if d.repeated { "*" } else { "" })?;
+        }
+        write!(f, ":{}", String::from_utf8_lossy(&self.info))
+    }
+}
+
+impl FromStr for Frame {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Frame> {
+        let (header, info) = s.split_once(':').context("missing info separator")?;
+        let (source, path) = header.split_once('>').context("missing destination")?;
+        let mut path = path.split(',');
+
+        let source = source.parse()?;
+        let destination = path.next().unwrap().parse()?;
+        let digipeaters = path.map(|d| d.parse()).collect::<Result<Vec<Address>>>()?;
+        if digipeaters.len() > MAX_DIGIPEATERS {
+            bail!("too many digipeaters ({})", digipeaters.len());
+        }
+
+        Ok(Frame::ui(source, destination, digipeaters, info.as_bytes()))
+    }
+}
@@ -0,0 +1,148 @@
+const FLAG: u8 = 0x7e;
+// address, control, and FCS
+const MIN_FRAME_LEN: usize = 2 * 7 + 1 + 2;
+const MAX_FRAME_LEN: usize = 512;
+
+// CRC-16/X.25 frame check sequence (reflected, init 0xffff, final xor)
+pub fn fcs(data: &[u8]) -> u16 {
+    let mut crc = 0xffffu16;
+    for b in data {
+        crc ^= *b as u16;
+        for _ in 0..8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ 0x8408;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    !crc
+}
+
+// flags, bit-stuffed frame with FCS (LSB first), and closing flags
+pub fn encode(frame: &[u8], n_flags: usize) -> Vec<bool> {
+    let mut bits = Vec::new();
+    let flag = |bits: &mut Vec<bool>| bits.extend((0..8).map(|i| (FLAG >> i) & 1 == 1));
+
+    for _ in 0..n_flags.max(1) {
+        flag(&mut bits);
+    }
+
+    let mut ones = 0;
+    for b in frame.iter().chain(fcs(frame).to_le_bytes().iter()) {
+        for i in 0..8 {
+            let bit = (b >> i) & 1 == 1;
+            bits.push(bit);
+            if bit {
+                ones += 1;
+                if ones == 5 {
+                    bits.push(false);
+                    ones = 0;
+                }
+            } else {
+                ones = 0;
+            }
+        }
+    }
+
+    flag(&mut bits);
+    flag(&mut bits);
+    bits
+}
+
+// a zero is sent as a change of the tone, a one keeps the tone
+pub fn nrzi_encode(bits: &[bool]) -> Vec<bool> {
+    let mut level = true;
+    bits.iter()
+        .map(|b| {
+            if !b {
+                level = !level;
+            }
+            level
+        })
+        .collect()
+}
+
+// finds flag-delimited frames in the (NRZI-decoded) bit stream and checks the FCS
+pub struct Deframer {
+    reg: u8,
+    ones: usize,
+    in_frame: bool,
+    bits: Vec<bool>,
+}
+
+impl Deframer {
+    pub fn new() -> Deframer {
+        Deframer {
+            reg: 0,
+            ones: 0,
+            in_frame: false,
+            bits: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, bit: bool) -> Option<Vec<u8>> {
+        self.reg = (self.reg >> 1) | ((bit as u8) << 7);
+
+        if self.reg == FLAG {
+            // the first seven bits of the flag were already queued
+            let n = self.bits.len().saturating_sub(7);
+            let frame = if self.in_frame { self.frame(n) } else { None };
+            self.bits.clear();
+            self.ones = 0;
+            self.in_frame = true;
+            return frame;
+        }
+
+        if !self.in_frame {
+            return None;
+        }
+
+        if bit {
+            self.ones += 1;
+            if self.ones > 6 {
+                self.in_frame = false;
+                self.bits.clear();
+                return None;
+            }
+        } else {
+            let stuffed = self.ones == 5;
+            self.ones = 0;
+            if stuffed {
+                return None;
+            }
+        }
+
+        self.bits.push(bit);
+        if self.bits.len() > (MAX_FRAME_LEN + 1) * 8 {
+            self.in_frame = false;
+            self.bits.clear();
+        }
+        None
+    }
+
+    fn frame(&self, n_bits: usize) -> Option<Vec<u8>> {
+        if n_bits & 0x7 != 0 || n_bits < MIN_FRAME_LEN * 8 {
+            return None;
+        }
+
+        let bytes: Vec<u8> = self.bits[..n_bits]
+            .chunks(8)
+            .map(|c| c.iter().rev().fold(0u8, |a, b| (a << 1) | *b as u8))
+            .collect();
+
+        let (frame, crc) = bytes.split_at(bytes.len() - 2);
+        if fcs(frame) == u16::from_le_bytes([crc[0], crc[1]]) {
+            Some(frame.to_vec())
+        } else {
+            debug!("ax25 deframer: fcs error");
+            None
+        }
+    }
+}
+
+impl Default for Deframer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,13 @@
+pub const BAUD_RATE: f32 = 1200.0;
+pub const MARK_FREQUENCY: f32 = 1200.0;
+pub const SPACE_FREQUENCY: f32 = 2200.0;
+
+mod decoder;
+pub use decoder::{Decoder, DecoderBuilder};
+mod demodulator;
+pub use demodulator::{Demodulator, DemodulatorBuilder};
+mod frame;
+pub use frame::{Address, Frame};
+pub mod hdlc;
+mod modulator;
+pub use modulator::{modulate, Modulator, ModulatorBuilder};
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use std::cmp;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::mem::size_of;
+
+use crate::blocks::ax25::hdlc;
+use crate::blocks::ax25::Frame;
+use crate::blocks::ax25::{BAUD_RATE, MARK_FREQUENCY, SPACE_FREQUENCY};
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Bell 202 AFSK, phase continuous
+pub fn modulate(frame: &[u8], sample_rate: f32, n_flags: usize) -> Vec<f32> {
+    let levels = hdlc::nrzi_encode(&hdlc::encode(frame, n_flags));
+    let n_samples = (levels.len() as f32 * sample_rate / BAUD_RATE).ceil() as usize;
+
+    let mut phase = 0.0f32;
+    (0..n_samples)
+        .map(|n| {
+            let bit = cmp::min(
+                (n as f32 * BAUD_RATE / sample_rate) as usize,
+                levels.len() - 1,
+            );
+            let f = if levels[bit] {
+                MARK_FREQUENCY
+            } else {
+                SPACE_FREQUENCY
+            };
+            phase = (phase + 2.0 * PI * f / sample_rate) % (2.0 * PI);
+            0.5 * phase.sin()
+        })
+        .collect()
+}
+
+pub struct Modulator {
+    sample_rate: f32,
+    n_flags: usize,
+    samples: VecDeque<f32>,
+    terminated: bool,
+    n_frames: u64,
+}
+
+impl Modulator {
+    pub fn new(sample_rate: f32, n_flags: usize) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Modulator")
+                .drain_on_terminate()
+                .build(),
+            StreamIoBuilder::new()
+                .add_output("out", size_of::<f32>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_sync_input("in", Self::handler)
+                .build(),
+            Modulator {
+                sample_rate,
+                n_flags,
+                samples: VecDeque::new(),
+                terminated: false,
+                n_frames: 0,
+            },
+        )
+    }
+
+    fn handler(
+        &mut self,
+        _mio: &mut MessageIo<Modulator>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        // raw frames (without FCS) or UI frames in TNC2 format
+        let frame = match p {
+            Pmt::Blob(frame) => Ok(frame),
+            Pmt::String(s) => s
+                .parse::<Frame>()
+                .map(|f| f.to_bytes())
+                .with_context(|| format!("cannot parse {:?}", s)),
+            p => Err(anyhow::anyhow!(
+                "expected Pmt::Blob or Pmt::String, got {:?}",
+                p
+            )),
+        };
+
+        match frame {
+            Ok(frame) => {
+                self.samples
+                    .extend(modulate(&frame, self.sample_rate, self.n_flags));
+                self.n_frames += 1;
+            }
+            Err(e) => warn!("ax25 modulator: dropping frame, {:#}", e),
+        }
+        Ok(Pmt::Null)
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.n_frames
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Modulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // upstream terminated, send out what is queued before finishing
+        if io.finished {
+            self.terminated = true;
+            io.finished = false;
+        }
+
+        let o = sio.output(0).slice::<f32>();
+        let n = cmp::min(o.len(), self.samples.len());
+
+        for (v, s) in o.iter_mut().zip(self.samples.drain(0..n)) {
+            *v = s;
+        }
+        sio.output(0).produce(n);
+
+        if self.terminated && self.samples.is_empty() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("ax25 modulator: n_frames {}", self.n_frames);
+        Ok(())
+    }
+}
+
+pub struct ModulatorBuilder {
+    sample_rate: f32,
+    n_flags: usize,
+}
+
+impl ModulatorBuilder {
+    pub fn new(sample_rate: f32) -> ModulatorBuilder {
+        ModulatorBuilder {
+            sample_rate,
+            n_flags: 32,
+        }
+    }
+
+    // flags sent before the frame (TXDELAY)
+    pub fn preamble(mut self, n_flags: usize) -> ModulatorBuilder {
+        self.n_flags = n_flags;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Modulator::new(self.sample_rate, self.n_flags)
+    }
+}
@@ -0,0 +1,74 @@
+use anyhow::{ensure, Result};
+
+// writes row by row and reads column by column
+#[derive(Clone, Debug)]
+pub struct BlockInterleaver {
+    rows: usize,
+    cols: usize,
+}
+
+impl BlockInterleaver {
+    pub fn new(rows: usize, cols: usize) -> Result<BlockInterleaver> {
+        ensure!(
+            rows > 0 && cols > 0,
+            "empty interleaver ({}x{})",
+            rows,
+            cols
+        );
+        Ok(BlockInterleaver { rows, cols })
+    }
+
+    pub fn block_len(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    fn check<T>(&self, data: &[T]) -> Result<()> {
+        ensure!(
+            data.chunks_exact(self.block_len()).remainder().is_empty(),
+            "length {} is not a multiple of the block length {}",
+            data.len(),
+            self.block_len()
+        );
+        Ok(())
+    }
+
+    pub fn interleave<T: Copy>(&self, data: &[T]) -> Result<Vec<T>> {
+        self.check(data)?;
+        let mut out = data.to_vec();
+        for (i, o) in data
+            .chunks(self.block_len())
+            .zip(out.chunks_mut(self.block_len()))
+        {
+            self.interleave_block(i, o);
+        }
+        Ok(out)
+    }
+
+    pub fn deinterleave<T: Copy>(&self, data: &[T]) -> Result<Vec<T>> {
+        self.check(data)?;
+        let mut out = data.to_vec();
+        for (i, o) in data
+            .chunks(self.block_len())
+            .zip(out.chunks_mut(self.block_len()))
+        {
+            self.deinterleave_block(i, o);
+        }
+        Ok(out)
+    }
+
+    pub(super) fn interleave_block<T: Copy>(&self, input: &[T], output: &mut [T]) {
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                output[c * self.rows + r] = input[r * self.cols + c];
+            }
+        }
+    }
+
+    pub(super) fn deinterleave_block<T: Copy>(&self, input: &[T], output: &mut [T]) {
+        for r in 0..self.rows {
+            for c in 0..self.cols {
+                output[r * self.cols + c] = input[c * self.rows + r];
+            }
+        }
+    }
+}
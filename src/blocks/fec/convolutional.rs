@@ -0,0 +1,116 @@
+use anyhow::{ensure, Result};
+
+// puncturing patterns over the output of a rate 1/2 code (802.11 order)
+pub const PUNCTURE_2_3: [bool; 4] = [true, true, true, false];
+pub const PUNCTURE_3_4: [bool; 6] = [true, true, true, false, false, true];
+
+// the current input is the MSB of the polynomials, encoding is terminated
+// with k - 1 zero tail bits; bits are unpacked, one per byte
+#[derive(Clone, Debug)]
+pub struct ConvolutionalCode {
+    k: usize,
+    polys: Vec<u32>,
+    puncturing: Vec<bool>,
+}
+
+impl ConvolutionalCode {
+    pub fn new(k: usize, polys: Vec<u32>) -> Result<ConvolutionalCode> {
+        ensure!((2..=16).contains(&k), "invalid constraint length {}", k);
+        ensure!(!polys.is_empty(), "no generator polynomials");
+        for p in polys.iter() {
+            ensure!(
+                *p != 0 && *p < (1 << k),
+                "invalid polynomial {:o} for constraint length {}",
+                p,
+                k
+            );
+        }
+
+        let n = polys.len();
+        Ok(ConvolutionalCode {
+            k,
+            polys,
+            puncturing: vec![true; n],
+        })
+    }
+
+    // K=7, rate 1/2 code used by 802.11, DVB, and CCSDS
+    pub fn k7() -> ConvolutionalCode {
+        ConvolutionalCode::new(7, vec![0o133, 0o171]).unwrap()
+    }
+
+    pub fn puncturing(mut self, pattern: &[bool]) -> Result<ConvolutionalCode> {
+        ensure!(
+            !pattern.is_empty()
+                && pattern
+                    .chunks_exact(self.polys.len())
+                    .remainder()
+                    .is_empty(),
+            "puncturing pattern has to cover full output symbols"
+        );
+        ensure!(
+            pattern
+                .chunks(self.polys.len())
+                .all(|c| c.iter().any(|x| *x)),
+            "puncturing removes a complete output symbol"
+        );
+        self.puncturing = pattern.to_vec();
+        Ok(self)
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn polys(&self) -> &[u32] {
+        &self.polys
+    }
+
+    pub fn puncturing_pattern(&self) -> &[bool] {
+        &self.puncturing
+    }
+
+    // output bits for a shift register value (input bit as MSB)
+    pub(super) fn output(&self, register: u32) -> impl Iterator<Item = u8> + '_ {
+        self.polys
+            .iter()
+            .map(move |p| ((register & p).count_ones() & 1) as u8)
+    }
+
+    // encoded length of n_bits information bits, including tail bits
+    pub fn encoded_len(&self, n_bits: usize) -> usize {
+        let n = (n_bits + self.k - 1) * self.polys.len();
+        let kept = self.puncturing.iter().filter(|x| **x).count();
+        let full = n / self.puncturing.len() * kept;
+        full + self.puncturing[..n % self.puncturing.len()]
+            .iter()
+            .filter(|x| **x)
+            .count()
+    }
+
+    pub fn encode(&self, bits: &[u8]) -> Vec<u8> {
+        let mut out = Vec::with_capacity(self.encoded_len(bits.len()));
+        let mut state = 0u32;
+        let mut i = 0;
+
+        let tail = vec![0u8; self.k - 1];
+        for b in bits.iter().copied().chain(tail) {
+            let register = (((b & 1) as u32) << (self.k - 1)) | state;
+            for o in self.output(register) {
+                if self.puncturing[i % self.puncturing.len()] {
+                    out.push(o);
+                }
+                i += 1;
+            }
+            state = register >> 1;
+        }
+
+        out
+    }
+}
+
+impl Default for ConvolutionalCode {
+    fn default() -> Self {
+        Self::k7()
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::blocks::fec::ConvolutionalCode;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// encodes blocks of block_len unpacked bits, each terminated individually
+pub struct ConvolutionalEncoder {
+    code: ConvolutionalCode,
+    block_len: usize,
+}
+
+impl ConvolutionalEncoder {
+    pub fn new(code: ConvolutionalCode, block_len: usize) -> Block {
+        Block::new_sync(
+            BlockMetaBuilder::new("ConvolutionalEncoder").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<u8>())
+                .add_output("out", size_of::<u8>())
+                .build(),
+            MessageIoBuilder::<ConvolutionalEncoder>::new().build(),
+            ConvolutionalEncoder { code, block_len },
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for ConvolutionalEncoder {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<u8>();
+        let encoded_len = self.code.encoded_len(self.block_len);
+
+        let n = cmp::min(i.len() / self.block_len, o.len() / encoded_len);
+        for k in 0..n {
+            let encoded = self
+                .code
+                .encode(&i[k * self.block_len..(k + 1) * self.block_len]);
+            o[k * encoded_len..(k + 1) * encoded_len].copy_from_slice(&encoded);
+        }
+
+        sio.input(0).consume(n * self.block_len);
+        sio.output(0).produce(n * encoded_len);
+
+        if sio.input(0).finished() && i.len() - n * self.block_len < self.block_len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct ConvolutionalEncoderBuilder {
+    code: ConvolutionalCode,
+    block_len: usize,
+}
+
+impl ConvolutionalEncoderBuilder {
+    pub fn new(block_len: usize) -> ConvolutionalEncoderBuilder {
+        ConvolutionalEncoderBuilder {
+            code: ConvolutionalCode::k7(),
+            block_len,
+        }
+    }
+
+    pub fn code(mut self, code: ConvolutionalCode) -> ConvolutionalEncoderBuilder {
+        self.code = code;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ConvolutionalEncoder::new(self.code, self.block_len)
+    }
+}
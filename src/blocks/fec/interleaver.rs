@@ -0,0 +1,108 @@
+use anyhow::Result;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::blocks::fec::BlockInterleaver;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+pub struct Interleaver<T: Copy + Send + 'static> {
+    interleaver: BlockInterleaver,
+    deinterleave: bool,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> Interleaver<T> {
+    pub fn new(interleaver: BlockInterleaver, deinterleave: bool) -> Block {
+        Block::new_sync(
+            BlockMetaBuilder::new(if deinterleave {
+                "Deinterleaver"
+            } else {
+                "Interleaver"
+            })
+            .build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<T>())
+                .add_output("out", size_of::<T>())
+                .build(),
+            MessageIoBuilder::<Interleaver<T>>::new().build(),
+            Interleaver::<T> {
+                interleaver,
+                deinterleave,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl<T: Copy + Send + 'static> SyncKernel for Interleaver<T> {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+        let len = self.interleaver.block_len();
+
+        let n = cmp::min(i.len(), o.len()) / len;
+        for k in 0..n {
+            let input = &i[k * len..(k + 1) * len];
+            let output = &mut o[k * len..(k + 1) * len];
+            if self.deinterleave {
+                self.interleaver.deinterleave_block(input, output);
+            } else {
+                self.interleaver.interleave_block(input, output);
+            }
+        }
+
+        sio.input(0).consume(n * len);
+        sio.output(0).produce(n * len);
+
+        if sio.input(0).finished() && i.len() - n * len < len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct InterleaverBuilder<T: Copy + Send + 'static> {
+    rows: usize,
+    cols: usize,
+    deinterleave: bool,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Copy + Send + 'static> InterleaverBuilder<T> {
+    pub fn new(rows: usize, cols: usize) -> InterleaverBuilder<T> {
+        InterleaverBuilder {
+            rows,
+            cols,
+            deinterleave: false,
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    pub fn deinterleave(mut self, deinterleave: bool) -> InterleaverBuilder<T> {
+        self.deinterleave = deinterleave;
+        self
+    }
+
+    pub fn build(self) -> Result<Block> {
+        Ok(Interleaver::<T>::new(
+            BlockInterleaver::new(self.rows, self.cols)?,
+            self.deinterleave,
+        ))
+    }
+}
@@ -0,0 +1,18 @@
+mod block_interleaver;
+pub use block_interleaver::BlockInterleaver;
+mod convolutional;
+pub use convolutional::{ConvolutionalCode, PUNCTURE_2_3, PUNCTURE_3_4};
+mod convolutional_encoder;
+pub use convolutional_encoder::{ConvolutionalEncoder, ConvolutionalEncoderBuilder};
+mod interleaver;
+pub use interleaver::{Interleaver, InterleaverBuilder};
+mod reed_solomon;
+pub use reed_solomon::ReedSolomon;
+mod reed_solomon_decoder;
+pub use reed_solomon_decoder::{ReedSolomonDecoder, ReedSolomonDecoderBuilder};
+mod reed_solomon_encoder;
+pub use reed_solomon_encoder::{ReedSolomonEncoder, ReedSolomonEncoderBuilder};
+mod viterbi;
+pub use viterbi::Viterbi;
+mod viterbi_decoder;
+pub use viterbi_decoder::{ViterbiDecoder, ViterbiDecoderBuilder};
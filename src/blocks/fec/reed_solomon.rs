@@ -0,0 +1,275 @@
+use anyhow::{bail, ensure, Result};
+
+const N: usize = 255;
+const PRIMITIVE: u16 = 0x11d;
+
+// Reed-Solomon over GF(2^8), first consecutive root 1 (alpha^0); shortened
+// codes are supported by passing less data
+#[derive(Clone, Debug)]
+pub struct ReedSolomon {
+    n_parity: usize,
+    exp: [u8; 512],
+    log: [u8; 256],
+    generator: Vec<u8>,
+}
+
+impl ReedSolomon {
+    // RS(255, 223)
+    pub fn new() -> ReedSolomon {
+        ReedSolomon::with_parity(32).unwrap()
+    }
+
+    pub fn with_parity(n_parity: usize) -> Result<ReedSolomon> {
+        ensure!(
+            n_parity > 0 && n_parity < N,
+            "invalid number of parity bytes {}",
+            n_parity
+        );
+
+        let mut exp = [0u8; 512];
+        let mut log = [0u8; 256];
+        let mut x = 1u16;
+        for (i, e) in exp.iter_mut().take(N).enumerate() {
+            *e = x as u8;
+            log[x as usize] = i as u8;
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= PRIMITIVE;
+            }
+        }
+        for i in N..512 {
+            exp[i] = exp[i - N];
+        }
+
+        let mut rs = ReedSolomon {
+            n_parity,
+            exp,
+            log,
+            generator: vec![1],
+        };
+        for i in 0..n_parity {
+            rs.generator = rs.poly_mul(&rs.generator, &[1, rs.exp[i]]);
+        }
+        Ok(rs)
+    }
+
+    pub fn n_parity(&self) -> usize {
+        self.n_parity
+    }
+
+    // maximum number of data bytes per codeword
+    pub fn max_data_len(&self) -> usize {
+        N - self.n_parity
+    }
+
+    fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    fn div(&self, a: u8, b: u8) -> u8 {
+        debug_assert!(b != 0);
+        if a == 0 {
+            0
+        } else {
+            self.exp[(self.log[a as usize] as usize + N - self.log[b as usize] as usize) % N]
+        }
+    }
+
+    fn pow(&self, a: u8, p: isize) -> u8 {
+        let l = (self.log[a as usize] as isize * p).rem_euclid(N as isize);
+        self.exp[l as usize]
+    }
+
+    fn inverse(&self, a: u8) -> u8 {
+        self.exp[N - self.log[a as usize] as usize]
+    }
+
+    // polynomials have the highest degree first
+    fn poly_mul(&self, p: &[u8], q: &[u8]) -> Vec<u8> {
+        let mut r = vec![0u8; p.len() + q.len() - 1];
+        for (i, a) in q.iter().enumerate() {
+            for (j, b) in p.iter().enumerate() {
+                r[i + j] ^= self.mul(*a, *b);
+            }
+        }
+        r
+    }
+
+    fn poly_scale(&self, p: &[u8], x: u8) -> Vec<u8> {
+        p.iter().map(|a| self.mul(*a, x)).collect()
+    }
+
+    fn poly_add(&self, p: &[u8], q: &[u8]) -> Vec<u8> {
+        let len = p.len().max(q.len());
+        let mut r = vec![0u8; len];
+        for (i, a) in p.iter().enumerate() {
+            r[i + len - p.len()] = *a;
+        }
+        for (i, a) in q.iter().enumerate() {
+            r[i + len - q.len()] ^= *a;
+        }
+        r
+    }
+
+    fn poly_eval(&self, p: &[u8], x: u8) -> u8 {
+        p.iter().skip(1).fold(p[0], |y, a| self.mul(y, x) ^ a)
+    }
+
+    // remainder of the division by a monic polynomial
+    fn poly_rem(&self, dividend: &[u8], divisor: &[u8]) -> Vec<u8> {
+        let mut out = dividend.to_vec();
+        for i in 0..dividend.len() - (divisor.len() - 1) {
+            let coef = out[i];
+            if coef != 0 {
+                for (j, d) in divisor.iter().enumerate().skip(1) {
+                    out[i + j] ^= self.mul(*d, coef);
+                }
+            }
+        }
+        out[dividend.len() - (divisor.len() - 1)..].to_vec()
+    }
+
+    // systematic codeword, data followed by parity
+    pub fn encode(&self, data: &[u8]) -> Result<Vec<u8>> {
+        ensure!(
+            data.len() <= self.max_data_len(),
+            "data too long ({} > {} bytes)",
+            data.len(),
+            self.max_data_len()
+        );
+
+        let mut out = data.to_vec();
+        out.resize(data.len() + self.n_parity, 0);
+        for i in 0..data.len() {
+            let coef = out[i];
+            if coef != 0 {
+                for (j, g) in self.generator.iter().enumerate().skip(1) {
+                    out[i + j] ^= self.mul(*g, coef);
+                }
+            }
+        }
+        out[..data.len()].copy_from_slice(data);
+        Ok(out)
+    }
+
+    // syndromes, with a leading zero to simplify the evaluator
+    fn syndromes(&self, codeword: &[u8]) -> Vec<u8> {
+        let mut s = vec![0u8];
+        s.extend((0..self.n_parity).map(|i| self.poly_eval(codeword, self.exp[i])));
+        s
+    }
+
+    // Berlekamp-Massey
+    fn error_locator(&self, synd: &[u8]) -> Result<Vec<u8>> {
+        let mut err_loc = vec![1u8];
+        let mut old_loc = vec![1u8];
+
+        for i in 0..self.n_parity {
+            let k = i + 1;
+            let mut delta = synd[k];
+            for j in 1..err_loc.len() {
+                delta ^= self.mul(err_loc[err_loc.len() - 1 - j], synd[k - j]);
+            }
+            old_loc.push(0);
+            if delta != 0 {
+                if old_loc.len() > err_loc.len() {
+                    let new_loc = self.poly_scale(&old_loc, delta);
+                    old_loc = self.poly_scale(&err_loc, self.inverse(delta));
+                    err_loc = new_loc;
+                }
+                err_loc = self.poly_add(&err_loc, &self.poly_scale(&old_loc, delta));
+            }
+        }
+
+        let first = err_loc.iter().position(|x| *x != 0).unwrap_or(0);
+        let err_loc = err_loc[first..].to_vec();
+        if (err_loc.len() - 1) * 2 > self.n_parity {
+            bail!("too many errors");
+        }
+        Ok(err_loc)
+    }
+
+    // Chien search
+    fn error_positions(&self, err_loc: &[u8], len: usize) -> Result<Vec<usize>> {
+        let rev: Vec<u8> = err_loc.iter().rev().copied().collect();
+        let pos: Vec<usize> = (0..len)
+            .filter(|i| self.poly_eval(&rev, self.pow(2, *i as isize)) == 0)
+            .map(|i| len - 1 - i)
+            .collect();
+        ensure!(pos.len() == err_loc.len() - 1, "cannot locate errors");
+        Ok(pos)
+    }
+
+    // Forney algorithm
+    fn correct(&self, codeword: &mut [u8], synd: &[u8], pos: &[usize]) {
+        let len = codeword.len();
+        let coef_pos: Vec<usize> = pos.iter().map(|p| len - 1 - p).collect();
+
+        let mut err_loc = vec![1u8];
+        for i in coef_pos.iter() {
+            err_loc = self.poly_mul(
+                &err_loc,
+                &self.poly_add(&[1], &[self.pow(2, *i as isize), 0]),
+            );
+        }
+
+        let synd_rev: Vec<u8> = synd.iter().rev().copied().collect();
+        let mut divisor = vec![0u8; err_loc.len() + 1];
+        divisor[0] = 1;
+        let err_eval = self.poly_rem(&self.poly_mul(&synd_rev, &err_loc), &divisor);
+
+        let x: Vec<u8> = coef_pos
+            .iter()
+            .map(|c| self.pow(2, -((N - c) as isize)))
+            .collect();
+
+        for (i, xi) in x.iter().enumerate() {
+            let xi_inv = self.inverse(*xi);
+            let err_loc_prime = x
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i)
+                .fold(1u8, |a, (_, xj)| self.mul(a, 1 ^ self.mul(xi_inv, *xj)));
+
+            let y = self.mul(*xi, self.poly_eval(&err_eval, xi_inv));
+            codeword[pos[i]] ^= self.div(y, err_loc_prime);
+        }
+    }
+
+    // corrects the codeword in place, returning the number of corrected bytes
+    pub fn decode(&self, codeword: &mut [u8]) -> Result<usize> {
+        ensure!(
+            codeword.len() > self.n_parity && codeword.len() <= N,
+            "invalid codeword length {}",
+            codeword.len()
+        );
+
+        let synd = self.syndromes(codeword);
+        if synd.iter().all(|s| *s == 0) {
+            return Ok(0);
+        }
+
+        let err_loc = self.error_locator(&synd)?;
+        let pos = self.error_positions(&err_loc, codeword.len())?;
+
+        let mut corrected = codeword.to_vec();
+        self.correct(&mut corrected, &synd, &pos);
+        ensure!(
+            self.syndromes(&corrected).iter().all(|s| *s == 0),
+            "cannot correct errors"
+        );
+
+        codeword.copy_from_slice(&corrected);
+        Ok(pos.len())
+    }
+}
+
+impl Default for ReedSolomon {
+    fn default() -> Self {
+        Self::new()
+    }
+}
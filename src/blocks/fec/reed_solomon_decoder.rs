@@ -0,0 +1,133 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::blocks::fec::ReedSolomon;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// decodes PDUs of the ReedSolomonEncoder, dropping uncorrectable ones
+pub struct ReedSolomonDecoder {
+    rs: ReedSolomon,
+    n_decoded: u64,
+    n_corrected: u64,
+    n_failed: u64,
+}
+
+impl ReedSolomonDecoder {
+    pub fn new(rs: ReedSolomon) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("ReedSolomonDecoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", ReedSolomonDecoder::handler)
+                .build(),
+            ReedSolomonDecoder {
+                rs,
+                n_decoded: 0,
+                n_corrected: 0,
+                n_failed: 0,
+            },
+        )
+    }
+
+    pub fn decoded(&self) -> u64 {
+        self.n_decoded
+    }
+
+    // corrected bytes
+    pub fn corrected(&self) -> u64 {
+        self.n_corrected
+    }
+
+    pub fn failed(&self) -> u64 {
+        self.n_failed
+    }
+
+    fn decode(&mut self, mut data: Vec<u8>) -> Result<Vec<u8>> {
+        let n_parity = self.rs.n_parity();
+        let mut out = Vec::new();
+        for chunk in data.chunks_mut(self.rs.max_data_len() + n_parity) {
+            self.n_corrected += self.rs.decode(chunk)? as u64;
+            out.extend_from_slice(&chunk[..chunk.len() - n_parity]);
+        }
+        Ok(out)
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(data) => match self.decode(data) {
+                    Ok(data) => {
+                        self.n_decoded += 1;
+                        mio.post(0, Pmt::Blob(data)).await;
+                    }
+                    Err(e) => {
+                        debug!("reed solomon decoder: {}", e);
+                        self.n_failed += 1;
+                    }
+                },
+                p => warn!("reed solomon decoder: expected Pmt::Blob, got {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for ReedSolomonDecoder {
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!(
+            "reed solomon decoder: n_decoded {}, n_corrected {}, n_failed {}",
+            self.n_decoded, self.n_corrected, self.n_failed
+        );
+        Ok(())
+    }
+}
+
+pub struct ReedSolomonDecoderBuilder {
+    rs: ReedSolomon,
+}
+
+impl ReedSolomonDecoderBuilder {
+    pub fn new() -> ReedSolomonDecoderBuilder {
+        ReedSolomonDecoderBuilder {
+            rs: ReedSolomon::new(),
+        }
+    }
+
+    pub fn code(mut self, rs: ReedSolomon) -> ReedSolomonDecoderBuilder {
+        self.rs = rs;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ReedSolomonDecoder::new(self.rs)
+    }
+}
+
+impl Default for ReedSolomonDecoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
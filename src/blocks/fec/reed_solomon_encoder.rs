@@ -0,0 +1,86 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::blocks::fec::ReedSolomon;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+// PDUs longer than the maximum data length are split into several codewords,
+// only the last one is shortened
+pub struct ReedSolomonEncoder {
+    rs: ReedSolomon,
+}
+
+impl ReedSolomonEncoder {
+    pub fn new(rs: ReedSolomon) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("ReedSolomonEncoder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", ReedSolomonEncoder::handler)
+                .build(),
+            ReedSolomonEncoder { rs },
+        )
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(data) if !data.is_empty() => {
+                    let mut out = Vec::new();
+                    for chunk in data.chunks(self.rs.max_data_len()) {
+                        out.extend(self.rs.encode(chunk)?);
+                    }
+                    mio.post(0, Pmt::Blob(out)).await;
+                }
+                p => warn!("reed solomon encoder: expected Pmt::Blob, got {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for ReedSolomonEncoder {}
+
+pub struct ReedSolomonEncoderBuilder {
+    rs: ReedSolomon,
+}
+
+impl ReedSolomonEncoderBuilder {
+    pub fn new() -> ReedSolomonEncoderBuilder {
+        ReedSolomonEncoderBuilder {
+            rs: ReedSolomon::new(),
+        }
+    }
+
+    pub fn code(mut self, rs: ReedSolomon) -> ReedSolomonEncoderBuilder {
+        self.rs = rs;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ReedSolomonEncoder::new(self.rs)
+    }
+}
+
+impl Default for ReedSolomonEncoderBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
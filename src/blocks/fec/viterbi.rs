@@ -0,0 +1,96 @@
+use crate::blocks::fec::ConvolutionalCode;
+
+// soft-decision Viterbi decoder for terminated convolutional codes; soft
+// bits are in [-1, 1], positive values meaning one, zero is an erasure
+#[derive(Clone, Debug)]
+pub struct Viterbi {
+    code: ConvolutionalCode,
+    // expected output (+1/-1) for every shift register value
+    outputs: Vec<Vec<f32>>,
+}
+
+impl Viterbi {
+    pub fn new(code: ConvolutionalCode) -> Viterbi {
+        let outputs = (0..1u32 << code.k())
+            .map(|r| {
+                code.output(r)
+                    .map(|b| if b == 1 { 1.0 } else { -1.0 })
+                    .collect()
+            })
+            .collect();
+        Viterbi { code, outputs }
+    }
+
+    pub fn code(&self) -> &ConvolutionalCode {
+        &self.code
+    }
+
+    // reinsert erasures for punctured bits
+    fn depuncture(&self, soft: &[f32]) -> Vec<f32> {
+        let pattern = self.code.puncturing_pattern();
+        let n_out = self.code.polys().len();
+
+        let mut out = Vec::new();
+        let mut soft = soft.iter();
+        let mut i = 0;
+        loop {
+            if i % n_out == 0 && soft.len() == 0 {
+                break;
+            }
+            if pattern[i % pattern.len()] {
+                match soft.next() {
+                    Some(s) => out.push(*s),
+                    None => out.push(0.0),
+                }
+            } else {
+                out.push(0.0);
+            }
+            i += 1;
+        }
+        out
+    }
+
+    pub fn decode(&self, soft: &[f32]) -> Vec<u8> {
+        let k = self.code.k();
+        let n_out = self.code.polys().len();
+        let n_states = 1usize << (k - 1);
+        let soft = self.depuncture(soft);
+        let n_steps = soft.len() / n_out;
+
+        let mut metrics = vec![f32::NEG_INFINITY; n_states];
+        metrics[0] = 0.0;
+        let mut next = vec![f32::NEG_INFINITY; n_states];
+        let mut decisions = vec![0u8; n_steps * n_states];
+
+        for t in 0..n_steps {
+            let s = &soft[t * n_out..(t + 1) * n_out];
+            next.iter_mut().for_each(|m| *m = f32::NEG_INFINITY);
+
+            for (r, expected) in self.outputs.iter().enumerate() {
+                let m = metrics[r & (n_states - 1)];
+                if m == f32::NEG_INFINITY {
+                    continue;
+                }
+                let branch: f32 = s.iter().zip(expected.iter()).map(|(a, b)| a * b).sum();
+                let ns = r >> 1;
+                if m + branch > next[ns] {
+                    next[ns] = m + branch;
+                    decisions[t * n_states + ns] = (r & 1) as u8;
+                }
+            }
+            std::mem::swap(&mut metrics, &mut next);
+        }
+
+        // terminated code, trace back from the zero state
+        let mut bits = Vec::with_capacity(n_steps);
+        let mut state = 0usize;
+        for t in (0..n_steps).rev() {
+            bits.push((state >> (k - 2)) as u8 & 1);
+            let d = decisions[t * n_states + state] as usize;
+            state = ((state << 1) | d) & (n_states - 1);
+        }
+        bits.reverse();
+        bits.truncate(n_steps.saturating_sub(k - 1));
+        bits
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::Result;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::blocks::fec::ConvolutionalCode;
+use crate::blocks::fec::Viterbi;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// decodes soft bits of blocks encoded by the ConvolutionalEncoder
+pub struct ViterbiDecoder {
+    viterbi: Viterbi,
+    block_len: usize,
+}
+
+impl ViterbiDecoder {
+    pub fn new(code: ConvolutionalCode, block_len: usize) -> Block {
+        Block::new_sync(
+            BlockMetaBuilder::new("ViterbiDecoder").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<f32>())
+                .add_output("out", size_of::<u8>())
+                .build(),
+            MessageIoBuilder::<ViterbiDecoder>::new().build(),
+            ViterbiDecoder {
+                viterbi: Viterbi::new(code),
+                block_len,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for ViterbiDecoder {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<u8>();
+        let encoded_len = self.viterbi.code().encoded_len(self.block_len);
+
+        let n = cmp::min(i.len() / encoded_len, o.len() / self.block_len);
+        for k in 0..n {
+            let decoded = self
+                .viterbi
+                .decode(&i[k * encoded_len..(k + 1) * encoded_len]);
+            o[k * self.block_len..(k + 1) * self.block_len].copy_from_slice(&decoded);
+        }
+
+        sio.input(0).consume(n * encoded_len);
+        sio.output(0).produce(n * self.block_len);
+
+        if sio.input(0).finished() && i.len() - n * encoded_len < encoded_len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct ViterbiDecoderBuilder {
+    code: ConvolutionalCode,
+    block_len: usize,
+}
+
+impl ViterbiDecoderBuilder {
+    pub fn new(block_len: usize) -> ViterbiDecoderBuilder {
+        ViterbiDecoderBuilder {
+            code: ConvolutionalCode::k7(),
+            block_len,
+        }
+    }
+
+    pub fn code(mut self, code: ConvolutionalCode) -> ViterbiDecoderBuilder {
+        self.code = code;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        ViterbiDecoder::new(self.code, self.block_len)
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{bail, Context, Result};
+use async_fs::{File, OpenOptions};
+use futures::AsyncWriteExt;
+use std::path::PathBuf;
+
+use crate::blocks::fifo_source::mkfifo;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Writes items to a named pipe (FIFO). With `create`, the FIFO is created if the
+// path does not exist. Opening the FIFO waits for a reader. Only complete items
+// are written.
+//
+// Without `reopen`, the block fails once the reader closes the FIFO. With
+// `reopen`, it waits for the next reader; items in the meantime are lost.
+pub struct FifoSink {
+    path: PathBuf,
+    create: bool,
+    reopen: bool,
+    item_size: usize,
+    file: Option<File>,
+    n_opened: usize,
+}
+
+impl FifoSink {
+    pub fn new(path: &str) -> Block {
+        FifoSinkBuilder::new(path).build()
+    }
+
+    // number of readers seen so far
+    pub fn n_opened(&self) -> usize {
+        self.n_opened
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for FifoSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.file.is_none() {
+            self.file = Some(
+                OpenOptions::new()
+                    .write(true)
+                    .open(&self.path)
+                    .await
+                    .with_context(|| format!("fifo sink: cannot open {:?}", self.path))?,
+            );
+            debug!("fifo sink: opened {:?}", self.path);
+            self.n_opened += 1;
+        }
+
+        let i = sio.input(0).slice::<u8>();
+        let items = i.len() / self.item_size;
+        let bytes = &i[..items * self.item_size];
+
+        if !bytes.is_empty() {
+            let file = self.file.as_mut().context("no file")?;
+            let r = match file.write_all(bytes).await {
+                Ok(()) => file.flush().await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = r {
+                debug!("fifo sink: reader closed {:?} ({})", self.path, e);
+                self.file = None;
+                if !self.reopen {
+                    bail!("fifo sink: reader closed {:?}", self.path);
+                }
+            }
+        }
+
+        sio.input(0).consume(items);
+
+        if sio.input(0).finished() && items * self.item_size == i.len() {
+            io.finished = true;
+        } else if self.file.is_none() {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.create {
+            mkfifo(&self.path).context("fifo sink")?;
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // closing the FIFO signals the end of the stream to the reader
+        if let Some(mut f) = self.file.take() {
+            let _ = f.close().await;
+        }
+        Ok(())
+    }
+}
+
+pub struct FifoSinkBuilder {
+    path: PathBuf,
+    create: bool,
+    reopen: bool,
+    item_size: usize,
+}
+
+impl FifoSinkBuilder {
+    pub fn new(path: &str) -> FifoSinkBuilder {
+        FifoSinkBuilder {
+            path: PathBuf::from(path),
+            create: true,
+            reopen: false,
+            item_size: 1,
+        }
+    }
+
+    pub fn create(mut self, create: bool) -> FifoSinkBuilder {
+        self.create = create;
+        self
+    }
+
+    pub fn reopen(mut self, reopen: bool) -> FifoSinkBuilder {
+        self.reopen = reopen;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> FifoSinkBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(self.item_size > 0, "fifo sink: item size must be positive");
+        Block::new_async(
+            BlockMetaBuilder::new("FifoSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            FifoSink {
+                path: self.path,
+                create: self.create,
+                reopen: self.reopen,
+                item_size: self.item_size,
+                file: None,
+                n_opened: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::{bail, Context, Result};
+use async_fs::File;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use crate::blocks::ItemReader;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Reads items from a named pipe (FIFO). With `create`, the FIFO is created if
+// the path does not exist. Opening the FIFO waits for a writer. Without
+// `reopen`, the block finishes when the writer closes the FIFO. With `reopen`,
+// it waits for the next writer. Partial items at the end of a writer are
+// dropped.
+pub struct FifoSource {
+    path: PathBuf,
+    create: bool,
+    reopen: bool,
+    item_size: usize,
+    file: Option<File>,
+    reader: ItemReader,
+    n_opened: usize,
+}
+
+impl FifoSource {
+    pub fn new(path: &str) -> Block {
+        FifoSourceBuilder::new(path).build()
+    }
+
+    // number of writers seen so far
+    pub fn n_opened(&self) -> usize {
+        self.n_opened
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for FifoSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.file.is_none() {
+            self.file = Some(
+                File::open(&self.path)
+                    .await
+                    .with_context(|| format!("fifo source: cannot open {:?}", self.path))?,
+            );
+            debug!("fifo source: opened {:?}", self.path);
+            self.n_opened += 1;
+        }
+
+        let out = sio.output(0).slice::<u8>();
+        if out.len() < self.item_size {
+            return Ok(());
+        }
+
+        let file = self.file.as_mut().context("no file")?;
+        let items = match self
+            .reader
+            .read(file, out)
+            .await
+            .with_context(|| format!("fifo source: cannot read {:?}", self.path))?
+        {
+            Some(items) => items,
+            None => {
+                debug!("fifo source: writer closed {:?}", self.path);
+                let p = self.reader.reset();
+                if p > 0 {
+                    warn!("fifo source: dropping {} bytes of a partial item", p);
+                }
+                self.file = None;
+                if self.reopen {
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
+                return Ok(());
+            }
+        };
+        sio.output(0).produce(items);
+
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.create {
+            mkfifo(&self.path).context("fifo source")?;
+        }
+        Ok(())
+    }
+}
+
+// create a FIFO, unless the path exists
+pub(crate) fn mkfifo(path: &Path) -> Result<()> {
+    if path.exists() {
+        return Ok(());
+    }
+    let p = CString::new(path.as_os_str().as_bytes())?;
+    if unsafe { libc::mkfifo(p.as_ptr(), 0o644) } != 0 {
+        let e = std::io::Error::last_os_error();
+        // the other end might have created it in the meantime
+        if e.kind() != std::io::ErrorKind::AlreadyExists {
+            bail!("cannot create FIFO {:?}: {}", path, e);
+        }
+    }
+    Ok(())
+}
+
+pub struct FifoSourceBuilder {
+    path: PathBuf,
+    create: bool,
+    reopen: bool,
+    item_size: usize,
+}
+
+impl FifoSourceBuilder {
+    pub fn new(path: &str) -> FifoSourceBuilder {
+        FifoSourceBuilder {
+            path: PathBuf::from(path),
+            create: true,
+            reopen: false,
+            item_size: 1,
+        }
+    }
+
+    pub fn create(mut self, create: bool) -> FifoSourceBuilder {
+        self.create = create;
+        self
+    }
+
+    pub fn reopen(mut self, reopen: bool) -> FifoSourceBuilder {
+        self.reopen = reopen;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> FifoSourceBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.item_size > 0,
+            "fifo source: item size must be positive"
+        );
+        Block::new_async(
+            BlockMetaBuilder::new("FifoSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            FifoSource {
+                path: self.path,
+                create: self.create,
+                reopen: self.reopen,
+                item_size: self.item_size,
+                file: None,
+                reader: ItemReader::new(self.item_size),
+                n_opened: 0,
+            },
+        )
+    }
+}
@@ -1,7 +1,8 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use async_fs::File;
+use async_fs::OpenOptions;
 use futures::io::AsyncWriteExt;
-use std::fs::OpenOptions;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
@@ -9,30 +10,114 @@ use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+// The file name is a pattern, where `{seq}` is replaced by a sequence number and
+// `{time}` by the UTC time the file is opened (e.g., 20220131T235959Z). If the
+// pattern has no `{seq}`, files after the first one get a `.<seq>` suffix.
+//
+// The "record" message handler starts (String "start" or a non-zero U32/U64) and
+// stops (String "stop" or zero) recording. It returns the state as U32. While
+// stopped, samples are dropped; each start opens a new file.
 pub struct FileSink {
-    // item_size: usize,
+    item_size: usize,
     file_name: String,
     file: Option<File>,
+    current: Option<String>,
+    files: Vec<String>,
+    rotate_items: Option<usize>,
+    rotate_duration: Option<Duration>,
+    max_items: Option<usize>,
+    recording: bool,
+    opened: Instant,
+    n_file: usize,
     n_written: usize,
 }
 
 impl FileSink {
     pub fn new(item_size: usize, file_name: &str) -> Block {
-        debug_assert_eq!(item_size, 1);
-        Block::new_async(
-            BlockMetaBuilder::new("FileSink").build(),
-            StreamIoBuilder::new().add_input("in", item_size).build(),
-            MessageIoBuilder::new().build(),
-            FileSink {
-                file_name: file_name.into(),
-                file: None,
-                n_written: 0,
-            },
-        )
+        FileSinkBuilder::new(item_size, file_name).build()
+    }
+
+    pub fn n_written(&self) -> usize {
+        self.n_written
+    }
+
+    // names of all files written so far
+    pub fn files(&self) -> &Vec<String> {
+        &self.files
+    }
+
+    fn record(
+        &mut self,
+        _mio: &mut MessageIo<FileSink>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match &p {
+            Pmt::String(s) if s == "start" => self.recording = true,
+            Pmt::String(s) if s == "stop" => self.recording = false,
+            Pmt::U32(v) => self.recording = *v != 0,
+            Pmt::U64(v) => self.recording = *v != 0,
+            Pmt::Null => {}
+            _ => warn!("file sink: invalid record command {:?}", p),
+        }
+        Ok(Pmt::U32(self.recording as u32))
+    }
+
+    fn next_file_name(&self) -> String {
+        let seq = self.files.len();
+        let mut name = self
+            .file_name
+            .replace("{seq}", &seq.to_string())
+            .replace("{time}", &timestamp(SystemTime::now()));
+        if seq > 0 && !self.file_name.contains("{seq}") {
+            name = format!("{}.{}", name, seq);
+        }
+        name
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        if let Some(mut f) = self.file.take() {
+            f.flush()
+                .await
+                .with_context(|| format!("file sink: cannot flush {:?}", self.current))?;
+        }
+        Ok(())
+    }
+
+    async fn open(&mut self) -> Result<()> {
+        self.close().await?;
+
+        let name = self.next_file_name();
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&name)
+            .await
+            .with_context(|| format!("file sink: cannot open {:?}", name))?;
+        debug!("file sink: writing to {:?}", name);
+
+        self.file = Some(file);
+        self.current = Some(name.clone());
+        self.files.push(name);
+        self.opened = Instant::now();
+        self.n_file = 0;
+        Ok(())
+    }
+
+    fn rotate_due(&self) -> bool {
+        matches!(self.rotate_items, Some(n) if self.n_file >= n)
+            || matches!(self.rotate_duration, Some(d) if self.opened.elapsed() >= d)
+    }
+
+    fn done(&self) -> bool {
+        matches!(self.max_items, Some(n) if self.n_written >= n)
     }
 }
 
@@ -46,18 +131,45 @@ impl AsyncKernel for FileSink {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         let i = sio.input(0).slice::<u8>();
+        let n_input = i.len() / self.item_size;
+
+        let mut n_consumed = 0;
+        if !self.recording {
+            self.close().await?;
+            n_consumed = n_input;
+        } else {
+            while n_consumed < n_input && !self.done() {
+                if self.file.is_none() || self.rotate_due() {
+                    self.open().await?;
+                }
+
+                let mut n = n_input - n_consumed;
+                if let Some(max) = self.max_items {
+                    n = std::cmp::min(n, max - self.n_written);
+                }
+                if let Some(items) = self.rotate_items {
+                    n = std::cmp::min(n, items - self.n_file);
+                }
 
-        match self.file.as_mut().unwrap().write_all(i).await {
-            Ok(()) => {}
-            Err(e) => panic!("file sink: name {:?} file error {:?}", self.file_name, e),
+                let bytes = &i[n_consumed * self.item_size..(n_consumed + n) * self.item_size];
+                self.file
+                    .as_mut()
+                    .unwrap()
+                    .write_all(bytes)
+                    .await
+                    .with_context(|| format!("file sink: cannot write {:?}", self.current))?;
+
+                n_consumed += n;
+                self.n_file += n;
+                self.n_written += n;
+            }
         }
 
-        if sio.input(0).finished() {
+        sio.input(0).consume(n_consumed);
+
+        if self.done() || (sio.input(0).finished() && n_consumed == n_input) {
             io.finished = true;
         }
-
-        self.n_written += i.len();
-        sio.input(0).consume(i.len());
         Ok(())
     }
 
@@ -67,14 +179,9 @@ impl AsyncKernel for FileSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let file = OpenOptions::new()
-            .write(true)
-            .create(true)
-            .truncate(true)
-            .open(self.file_name.clone())
-            .unwrap();
-
-        self.file = Some(file.into());
+        if self.recording {
+            self.open().await?;
+        }
         Ok(())
     }
 
@@ -84,15 +191,49 @@ impl AsyncKernel for FileSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        debug!("n_written: {}", self.n_written);
-        self.file.as_mut().unwrap().flush().await.unwrap();
-        Ok(())
+        debug!(
+            "file sink: n_written {}, n_files {}",
+            self.n_written,
+            self.files.len()
+        );
+        self.close().await
     }
 }
 
+// UTC time in ISO 8601 basic format
+fn timestamp(t: SystemTime) -> String {
+    let secs = t.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rem) = (secs / 86400, secs % 86400);
+
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days as i64 + 719468;
+    let era = z / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem / 60 % 60,
+        rem % 60
+    )
+}
+
 pub struct FileSinkBuilder {
     item_size: usize,
     file: String,
+    rotate_size: Option<usize>,
+    rotate_duration: Option<Duration>,
+    max_items: Option<usize>,
+    recording: bool,
 }
 
 impl FileSinkBuilder {
@@ -100,10 +241,61 @@ impl FileSinkBuilder {
         FileSinkBuilder {
             item_size,
             file: file.into(),
+            rotate_size: None,
+            rotate_duration: None,
+            max_items: None,
+            recording: true,
         }
     }
 
+    // start a new file once the current one has `bytes` bytes
+    // (rounded down to complete items, at least one item)
+    pub fn rotate_size(mut self, bytes: usize) -> FileSinkBuilder {
+        self.rotate_size = Some(bytes);
+        self
+    }
+
+    pub fn rotate_duration(mut self, duration: Duration) -> FileSinkBuilder {
+        self.rotate_duration = Some(duration);
+        self
+    }
+
+    // finish after writing `n` items
+    pub fn max_items(mut self, n: usize) -> FileSinkBuilder {
+        self.max_items = Some(n);
+        self
+    }
+
+    // start recording right away or wait for a "record" message
+    pub fn recording(mut self, recording: bool) -> FileSinkBuilder {
+        self.recording = recording;
+        self
+    }
+
     pub fn build(self) -> Block {
-        FileSink::new(self.item_size, &self.file)
+        assert!(self.item_size > 0, "file sink: item size must be positive");
+        let item_size = self.item_size;
+        Block::new_async(
+            BlockMetaBuilder::new("FileSink").build(),
+            StreamIoBuilder::new().add_input("in", item_size).build(),
+            MessageIoBuilder::new()
+                .add_sync_input("record", FileSink::record)
+                .input_kinds("record", &[PmtKind::String, PmtKind::U32, PmtKind::U64])
+                .build(),
+            FileSink {
+                item_size,
+                file_name: self.file,
+                file: None,
+                current: None,
+                files: Vec::new(),
+                rotate_items: self.rotate_size.map(|b| std::cmp::max(b / item_size, 1)),
+                rotate_duration: self.rotate_duration,
+                max_items: self.max_items,
+                recording: self.recording,
+                opened: Instant::now(),
+                n_file: 0,
+                n_written: 0,
+            },
+        )
     }
 }
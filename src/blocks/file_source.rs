@@ -1,5 +1,7 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
+use std::io::SeekFrom;
 
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
@@ -7,34 +9,110 @@ use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+// Reads items from a file, starting at `offset` items into the file and stopping
+// after `length` items, if set. With `repeat`, it loops over this region.
+// Trailing bytes that do not form a complete item are ignored.
+//
+// The "seek" message handler takes an item index (U32/U64), relative to the
+// offset, and returns the current position. Seeking beyond the end of the
+// region is an error. Any other Pmt just queries the position.
 pub struct FileSource {
-    // item_size: usize,
+    item_size: usize,
     file_name: String,
     file: Option<async_fs::File>,
-    file_size: usize,
-    n_produced: usize,
+    offset: u64,
+    length: Option<u64>,
+    repeat: bool,
+    n_items: u64,
+    position: u64,
+    seek: Option<u64>,
+    n_produced: u64,
 }
 
 impl FileSource {
     pub fn new(item_size: usize, file_name: String) -> Block {
-        // todo
-        debug_assert_eq!(item_size, 1);
+        FileSourceBuilder::new(item_size, file_name).build()
+    }
+
+    fn with_config(
+        item_size: usize,
+        file_name: String,
+        offset: u64,
+        length: Option<u64>,
+        repeat: bool,
+    ) -> Block {
+        assert!(item_size > 0, "file source: item size must be positive");
         Block::new_async(
             BlockMetaBuilder::new("FileSource").build(),
             StreamIoBuilder::new().add_output("out", item_size).build(),
-            MessageIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input("seek", Self::seek)
+                .input_kinds("seek", &[PmtKind::U32, PmtKind::U64])
+                .build(),
             FileSource {
+                item_size,
                 file_name,
-                file_size: 0,
                 file: None,
+                offset,
+                length,
+                repeat,
+                n_items: 0,
+                position: 0,
+                seek: None,
                 n_produced: 0,
             },
         )
     }
+
+    pub fn n_produced(&self) -> u64 {
+        self.n_produced
+    }
+
+    fn seek(
+        &mut self,
+        _mio: &mut MessageIo<FileSource>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let target = match p {
+            Pmt::U32(v) => Some(v as u64),
+            Pmt::U64(v) => Some(v),
+            _ => None,
+        };
+
+        // handlers are only called after init, so the number of items is known
+        if let Some(target) = target {
+            if target > self.n_items {
+                bail!(
+                    "file source: cannot seek to item {} of {} in {:?}",
+                    target,
+                    self.n_items,
+                    self.file_name
+                );
+            }
+            self.seek = Some(target);
+        }
+
+        Ok(Pmt::U64(self.seek.unwrap_or(self.position)))
+    }
+
+    async fn seek_file(&mut self, position: u64) -> Result<()> {
+        let pos = (self.offset + position) * self.item_size as u64;
+        self.file
+            .as_mut()
+            .unwrap()
+            .seek(SeekFrom::Start(pos))
+            .await
+            .with_context(|| format!("cannot seek in {:?}", self.file_name))?;
+        self.position = position;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -46,27 +124,44 @@ impl AsyncKernel for FileSource {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let out = sio.output(0).slice::<u8>();
+        if let Some(position) = self.seek.take() {
+            self.seek_file(position).await?;
+        }
 
-        let n_read = std::cmp::min(out.len(), self.file_size - self.n_produced);
+        if self.position == self.n_items {
+            if self.repeat && self.n_items > 0 {
+                self.seek_file(0).await?;
+            } else {
+                io.finished = true;
+                return Ok(());
+            }
+        }
+
+        let out = sio.output(0).slice::<u8>();
+        let n = std::cmp::min(
+            (out.len() / self.item_size) as u64,
+            self.n_items - self.position,
+        ) as usize;
 
-        match self
-            .file
+        self.file
             .as_mut()
             .unwrap()
-            .read_exact(&mut out[..n_read])
+            .read_exact(&mut out[..n * self.item_size])
             .await
-        {
-            Ok(_) => {
-                self.n_produced += n_read;
-                sio.output(0).produce(n_read);
+            .with_context(|| format!("error reading {:?}", self.file_name))?;
+
+        self.position += n as u64;
+        self.n_produced += n as u64;
+        sio.output(0).produce(n);
+
+        if self.position == self.n_items {
+            if self.repeat {
+                io.call_again = true;
+            } else {
+                io.finished = true;
             }
-            Err(_) => panic!("Error while reading file"),
         }
 
-        if self.file_size == self.n_produced {
-            io.finished = true;
-        }
         Ok(())
     }
 
@@ -76,11 +171,36 @@ impl AsyncKernel for FileSource {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let metadata = std::fs::metadata(self.file_name.clone()).unwrap();
-        self.file_size = metadata.len() as usize;
+        let file = async_fs::File::open(&self.file_name)
+            .await
+            .with_context(|| format!("cannot open {:?}", self.file_name))?;
+        let file_size = file.metadata().await?.len();
+        let item_size = self.item_size as u64;
 
-        self.file = Some(async_fs::File::open(self.file_name.clone()).await.unwrap());
-        Ok(())
+        if file_size % item_size != 0 {
+            warn!(
+                "file source: size of {:?} is not a multiple of the item size, ignoring {} trailing bytes",
+                self.file_name,
+                file_size % item_size
+            );
+        }
+
+        let file_items = file_size / item_size;
+        if self.offset > file_items {
+            bail!(
+                "file source: offset {} beyond the end of {:?} ({} items)",
+                self.offset,
+                self.file_name,
+                file_items
+            );
+        }
+
+        self.n_items = file_items - self.offset;
+        if let Some(length) = self.length {
+            self.n_items = std::cmp::min(self.n_items, length);
+        }
+        self.file = Some(file);
+        self.seek_file(0).await
     }
 
     async fn deinit(
@@ -97,6 +217,9 @@ impl AsyncKernel for FileSource {
 pub struct FileSourceBuilder {
     item_size: usize,
     file_name: String,
+    offset: u64,
+    length: Option<u64>,
+    repeat: bool,
 }
 
 impl FileSourceBuilder {
@@ -104,10 +227,36 @@ impl FileSourceBuilder {
         FileSourceBuilder {
             item_size,
             file_name,
+            offset: 0,
+            length: None,
+            repeat: false,
         }
     }
 
+    // in items
+    pub fn offset(mut self, offset: u64) -> FileSourceBuilder {
+        self.offset = offset;
+        self
+    }
+
+    // in items
+    pub fn length(mut self, length: u64) -> FileSourceBuilder {
+        self.length = Some(length);
+        self
+    }
+
+    pub fn repeat(mut self, repeat: bool) -> FileSourceBuilder {
+        self.repeat = repeat;
+        self
+    }
+
     pub fn build(self) -> Block {
-        FileSource::new(self.item_size, self.file_name)
+        FileSource::with_config(
+            self.item_size,
+            self.file_name,
+            self.offset,
+            self.length,
+            self.repeat,
+        )
     }
 }
@@ -0,0 +1,124 @@
+use futures::AsyncRead;
+use futures::AsyncReadExt;
+use std::io;
+
+// Reads complete items from a byte stream (sockets, pipes, stdin). A read can
+// end in the middle of an item; the partial item is kept and completed by the
+// next read.
+pub(crate) struct ItemReader {
+    item_size: usize,
+    pending: Vec<u8>,
+}
+
+impl ItemReader {
+    pub(crate) fn new(item_size: usize) -> ItemReader {
+        assert!(item_size > 0, "item reader: item size must be positive");
+        ItemReader {
+            item_size,
+            pending: Vec::with_capacity(item_size),
+        }
+    }
+
+    // Reads into `out`, which has to hold at least one item, and returns the
+    // number of complete items at the start of `out`, or None at the end of the
+    // stream.
+    pub(crate) async fn read<R: AsyncRead + Unpin>(
+        &mut self,
+        reader: &mut R,
+        out: &mut [u8],
+    ) -> io::Result<Option<usize>> {
+        assert!(out.len() >= self.item_size);
+
+        let p = self.pending.len();
+        out[..p].copy_from_slice(&self.pending);
+        let n = reader.read(&mut out[p..]).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        self.pending.clear();
+
+        let bytes = p + n;
+        let items = bytes / self.item_size;
+        self.pending
+            .extend_from_slice(&out[items * self.item_size..bytes]);
+        Ok(Some(items))
+    }
+
+    // drops the partial item, e.g., at the end of a connection, and returns its size
+    pub(crate) fn reset(&mut self) -> usize {
+        let p = self.pending.len();
+        self.pending.clear();
+        p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_io::block_on;
+    use std::collections::VecDeque;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    // returns the chunks as separate reads
+    struct Chunks(VecDeque<Vec<u8>>);
+
+    impl AsyncRead for Chunks {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut [u8],
+        ) -> Poll<io::Result<usize>> {
+            let c = match self.0.front_mut() {
+                Some(c) => c,
+                None => return Poll::Ready(Ok(0)),
+            };
+            let n = std::cmp::min(c.len(), buf.len());
+            buf[..n].copy_from_slice(&c[..n]);
+            c.drain(..n);
+            if c.is_empty() {
+                self.0.pop_front();
+            }
+            Poll::Ready(Ok(n))
+        }
+    }
+
+    #[test]
+    fn partial_items() {
+        let data: Vec<u8> = (0..20).collect();
+        let mut chunks = Chunks(
+            vec![
+                data[..3].to_vec(),
+                data[3..5].to_vec(),
+                data[5..13].to_vec(),
+                data[13..].to_vec(),
+            ]
+            .into(),
+        );
+        let mut reader = ItemReader::new(4);
+        let mut received = Vec::new();
+        let mut out = [0u8; 8];
+
+        block_on(async {
+            while let Some(items) = reader.read(&mut chunks, &mut out).await.unwrap() {
+                received.extend_from_slice(&out[..items * 4]);
+            }
+        });
+        assert_eq!(received, data);
+        assert_eq!(reader.reset(), 0);
+    }
+
+    #[test]
+    fn partial_item_at_end() {
+        let mut chunks = Chunks(vec![vec![1, 2, 3, 4, 5, 6]].into());
+        let mut reader = ItemReader::new(4);
+        let mut out = [0u8; 16];
+
+        block_on(async {
+            assert_eq!(reader.read(&mut chunks, &mut out).await.unwrap(), Some(1));
+            assert_eq!(&out[..4], &[1, 2, 3, 4]);
+            assert_eq!(reader.read(&mut chunks, &mut out).await.unwrap(), None);
+        });
+        assert_eq!(reader.reset(), 2);
+    }
+}
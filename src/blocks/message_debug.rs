@@ -0,0 +1,125 @@
+use std::collections::VecDeque;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+// Prints the messages it receives and, optionally, keeps the last ones. While
+// the flowgraph is running, the stored messages can be retrieved through the
+// `messages` handler (e.g., with `FlowgraphHandle::callback_by_name`), which
+// returns them as `Pmt::VecPmt`; the `clear` handler drops them.
+pub struct MessageDebug {
+    print: bool,
+    max_stored: usize,
+    messages: VecDeque<Pmt>,
+    n_received: u64,
+}
+
+impl MessageDebug {
+    pub fn new() -> Block {
+        MessageDebugBuilder::new().build()
+    }
+
+    pub fn messages(&self) -> Vec<Pmt> {
+        self.messages.iter().cloned().collect()
+    }
+
+    pub fn received(&self) -> u64 {
+        self.n_received
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for MessageDebug {}
+
+pub struct MessageDebugBuilder {
+    print: bool,
+    max_stored: usize,
+}
+
+impl MessageDebugBuilder {
+    pub fn new() -> MessageDebugBuilder {
+        MessageDebugBuilder {
+            print: true,
+            max_stored: 0,
+        }
+    }
+
+    pub fn print(mut self, print: bool) -> MessageDebugBuilder {
+        self.print = print;
+        self
+    }
+
+    // keep the last n messages
+    pub fn store(mut self, n: usize) -> MessageDebugBuilder {
+        self.max_stored = n;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("MessageDebug").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input(
+                    "in",
+                    |block: &mut MessageDebug,
+                     _mio: &mut MessageIo<MessageDebug>,
+                     meta: &mut BlockMeta,
+                     p: Pmt| {
+                        block.n_received += 1;
+                        if block.print {
+                            info!(
+                                "{} message {}: {:#?}",
+                                meta.instance_name().unwrap_or("MessageDebug"),
+                                block.n_received,
+                                p
+                            );
+                        }
+                        if block.max_stored > 0 {
+                            if block.messages.len() == block.max_stored {
+                                block.messages.pop_front();
+                            }
+                            block.messages.push_back(p);
+                        }
+                        Ok(Pmt::Null)
+                    },
+                )
+                .add_sync_input(
+                    "messages",
+                    |block: &mut MessageDebug,
+                     _mio: &mut MessageIo<MessageDebug>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| { Ok(Pmt::VecPmt(block.messages())) },
+                )
+                .add_sync_input(
+                    "clear",
+                    |block: &mut MessageDebug,
+                     _mio: &mut MessageIo<MessageDebug>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        block.messages.clear();
+                        Ok(Pmt::Null)
+                    },
+                )
+                .build(),
+            MessageDebug {
+                print: self.print,
+                max_stored: self.max_stored,
+                messages: VecDeque::new(),
+                n_received: 0,
+            },
+        )
+    }
+}
+
+impl Default for MessageDebugBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,83 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+// Forwards the messages for which the predicate holds and drops the others.
+pub struct MessageFilter {
+    predicate: Box<dyn Fn(&Pmt) -> bool + Send + Sync>,
+    n_passed: u64,
+    n_dropped: u64,
+}
+
+impl MessageFilter {
+    pub fn new(predicate: impl Fn(&Pmt) -> bool + Send + Sync + 'static) -> Block {
+        MessageFilterBuilder::new(predicate).build()
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            if (self.predicate)(&p) {
+                self.n_passed += 1;
+                mio.post(0, p).await;
+            } else {
+                self.n_dropped += 1;
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+
+    pub fn passed(&self) -> u64 {
+        self.n_passed
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.n_dropped
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for MessageFilter {}
+
+pub struct MessageFilterBuilder {
+    predicate: Box<dyn Fn(&Pmt) -> bool + Send + Sync>,
+}
+
+impl MessageFilterBuilder {
+    pub fn new(predicate: impl Fn(&Pmt) -> bool + Send + Sync + 'static) -> MessageFilterBuilder {
+        MessageFilterBuilder {
+            predicate: Box::new(predicate),
+        }
+    }
+
+    pub fn build(self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("MessageFilter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", MessageFilter::handler)
+                .build(),
+            MessageFilter {
+                predicate: self.predicate,
+                n_passed: 0,
+                n_dropped: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::Result;
+use async_io::Timer;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+
+// Forwards at most `rate` messages per second with bursts of up to `burst`
+// messages (token bucket). Excess messages are delayed, which backs up the
+// input queue and, eventually, the sender, or dropped.
+pub struct MessageRateLimiter {
+    rate: f64,
+    burst: f64,
+    drop: bool,
+    tokens: f64,
+    t_last: Instant,
+    n_dropped: u64,
+}
+
+impl MessageRateLimiter {
+    pub fn new(rate: f64) -> Block {
+        MessageRateLimiterBuilder::new(rate).build()
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        self.tokens = (self.tokens + (now - self.t_last).as_secs_f64() * self.rate).min(self.burst);
+        self.t_last = now;
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            self.refill();
+            if self.tokens < 1.0 {
+                if self.drop {
+                    self.n_dropped += 1;
+                    return Ok(Pmt::Null);
+                }
+                Timer::after(Duration::from_secs_f64((1.0 - self.tokens) / self.rate)).await;
+                self.refill();
+            }
+            self.tokens = (self.tokens - 1.0).max(0.0);
+            mio.post(0, p).await;
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.n_dropped
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for MessageRateLimiter {}
+
+pub struct MessageRateLimiterBuilder {
+    rate: f64,
+    burst: usize,
+    drop: bool,
+}
+
+impl MessageRateLimiterBuilder {
+    pub fn new(rate: f64) -> MessageRateLimiterBuilder {
+        MessageRateLimiterBuilder {
+            rate,
+            burst: 1,
+            drop: false,
+        }
+    }
+
+    pub fn burst(mut self, burst: usize) -> MessageRateLimiterBuilder {
+        self.burst = burst;
+        self
+    }
+
+    // drop excess messages instead of delaying them
+    pub fn drop(mut self, drop: bool) -> MessageRateLimiterBuilder {
+        self.drop = drop;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.rate > 0.0,
+            "message rate limiter: rate must be positive"
+        );
+        assert!(
+            self.burst > 0,
+            "message rate limiter: burst must be positive"
+        );
+        Block::new_async(
+            BlockMetaBuilder::new("MessageRateLimiter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("out")
+                .add_async_input("in", MessageRateLimiter::handler)
+                .build(),
+            MessageRateLimiter {
+                rate: self.rate,
+                burst: self.burst as f64,
+                drop: self.drop,
+                tokens: self.burst as f64,
+                t_last: Instant::now(),
+                n_dropped: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,153 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
+use crate::runtime::StreamIoBuilder;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageRoute {
+    // messages of a Pmt variant
+    Kind(PmtKind),
+    // maps that contain the key
+    Key(String),
+}
+
+impl MessageRoute {
+    fn matches(&self, p: &Pmt) -> bool {
+        match self {
+            MessageRoute::Kind(k) => p.kind().as_ref() == Some(k),
+            MessageRoute::Key(k) => matches!(p, Pmt::MapStrPmt(m) if m.contains_key(k)),
+        }
+    }
+}
+
+// Dispatches messages to outputs. The routes are checked in the order they were
+// added and a message is posted to the output of the first route that matches.
+// Messages that match no route go to the "default" output, if enabled, or are
+// dropped.
+pub struct MessageRouter {
+    routes: Vec<(MessageRoute, usize)>,
+    default_output: Option<usize>,
+    n_dropped: u64,
+}
+
+impl MessageRouter {
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            let output = self
+                .routes
+                .iter()
+                .find(|(r, _)| r.matches(&p))
+                .map(|(_, o)| *o)
+                .or(self.default_output);
+            match output {
+                Some(o) => mio.post(o, p).await,
+                None => self.n_dropped += 1,
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.n_dropped
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for MessageRouter {}
+
+pub struct MessageRouterBuilder {
+    routes: Vec<(MessageRoute, String)>,
+    default_output: bool,
+}
+
+impl MessageRouterBuilder {
+    pub fn new() -> MessageRouterBuilder {
+        MessageRouterBuilder {
+            routes: Vec::new(),
+            default_output: false,
+        }
+    }
+
+    // several routes can share an output
+    pub fn route(mut self, route: MessageRoute, output: &str) -> MessageRouterBuilder {
+        self.routes.push((route, output.to_string()));
+        self
+    }
+
+    pub fn kind(self, kind: PmtKind, output: &str) -> MessageRouterBuilder {
+        self.route(MessageRoute::Kind(kind), output)
+    }
+
+    pub fn key(self, key: &str, output: &str) -> MessageRouterBuilder {
+        self.route(MessageRoute::Key(key.to_string()), output)
+    }
+
+    pub fn default_output(mut self, enable: bool) -> MessageRouterBuilder {
+        self.default_output = enable;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let mut outputs: Vec<String> = Vec::new();
+        let mut routes = Vec::new();
+        for (r, o) in self.routes.into_iter() {
+            let id = match outputs.iter().position(|x| *x == o) {
+                Some(id) => id,
+                None => {
+                    outputs.push(o);
+                    outputs.len() - 1
+                }
+            };
+            routes.push((r, id));
+        }
+
+        let default_output = if self.default_output {
+            assert!(
+                !outputs.iter().any(|o| o == "default"),
+                "message router: output name default is reserved"
+            );
+            outputs.push("default".to_string());
+            Some(outputs.len() - 1)
+        } else {
+            None
+        };
+
+        let mut mio = MessageIoBuilder::new().add_async_input("in", MessageRouter::handler);
+        for o in outputs.iter() {
+            mio = mio.add_output(o);
+        }
+
+        Block::new_async(
+            BlockMetaBuilder::new("MessageRouter").build(),
+            StreamIoBuilder::new().build(),
+            mio.build(),
+            MessageRouter {
+                routes,
+                default_output,
+                n_dropped: 0,
+            },
+        )
+    }
+}
+
+impl Default for MessageRouterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
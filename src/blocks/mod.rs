@@ -1,9 +1,14 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod adsb;
+
 mod apply;
 pub use apply::Apply;
 
 #[cfg(feature = "audio")]
 pub mod audio;
 
+pub mod ax25;
+
 mod combine;
 pub use combine::Combine;
 
@@ -11,6 +16,9 @@ mod copy;
 pub use copy::{Copy, CopyBuilder};
 mod copy_rand;
 pub use copy_rand::{CopyRand, CopyRandBuilder};
+
+pub mod fec;
+
 mod filter;
 pub use filter::Filter;
 
@@ -19,6 +27,16 @@ mod fft;
 #[cfg(not(target_arch = "wasm32"))]
 pub use fft::{Fft, FftBuilder};
 
+#[cfg(unix)]
+mod fifo_sink;
+#[cfg(unix)]
+pub use fifo_sink::{FifoSink, FifoSinkBuilder};
+
+#[cfg(unix)]
+mod fifo_source;
+#[cfg(unix)]
+pub use fifo_source::{FifoSource, FifoSourceBuilder};
+
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
 #[cfg(not(target_arch = "wasm32"))]
@@ -33,10 +51,24 @@ mod finite_source;
 pub use finite_source::FiniteSource;
 mod head;
 pub use head::{Head, HeadBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+mod item_reader;
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use item_reader::ItemReader;
 mod message_burst;
 pub use message_burst::{MessageBurst, MessageBurstBuilder};
 mod message_copy;
 pub use message_copy::{MessageCopy, MessageCopyBuilder};
+mod message_debug;
+pub use message_debug::{MessageDebug, MessageDebugBuilder};
+mod message_filter;
+pub use message_filter::{MessageFilter, MessageFilterBuilder};
+#[cfg(not(target_arch = "wasm32"))]
+mod message_rate_limiter;
+#[cfg(not(target_arch = "wasm32"))]
+pub use message_rate_limiter::{MessageRateLimiter, MessageRateLimiterBuilder};
+mod message_router;
+pub use message_router::{MessageRoute, MessageRouter, MessageRouterBuilder};
 mod message_sink;
 pub use message_sink::{MessageSink, MessageSinkBuilder};
 
@@ -50,6 +82,15 @@ pub use null_sink::{NullSink, NullSinkBuilder};
 mod null_source;
 pub use null_source::{NullSource, NullSourceBuilder};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ofdm;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod rtl_tcp;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sigmf;
+
 #[cfg(feature = "soapy")]
 mod soapy_src;
 #[cfg(feature = "soapy")]
@@ -60,10 +101,20 @@ pub use source::Source;
 mod split;
 pub use split::Split;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod stdin_source;
+#[cfg(not(target_arch = "wasm32"))]
+pub use stdin_source::{StdinSource, StdinSourceBuilder};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod stdout_sink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use stdout_sink::{StdoutSink, StdoutSinkBuilder};
+
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_sink;
 #[cfg(not(target_arch = "wasm32"))]
-pub use tcp_sink::{TcpSink, TcpSinkBuilder};
+pub use tcp_sink::{TcpClientPolicy, TcpSink, TcpSinkBuilder};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_source;
@@ -75,6 +126,26 @@ mod throttle;
 #[cfg(not(target_arch = "wasm32"))]
 pub use throttle::{Throttle, ThrottleBuilder};
 
+#[cfg(not(target_arch = "wasm32"))]
+mod udp_sink;
+#[cfg(not(target_arch = "wasm32"))]
+pub use udp_sink::{UdpSink, UdpSinkBuilder};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod udp_source;
+#[cfg(not(target_arch = "wasm32"))]
+pub use udp_source::{UdpSource, UdpSourceBuilder};
+
+#[cfg(unix)]
+mod unix_sink;
+#[cfg(unix)]
+pub use unix_sink::{UnixSink, UnixSinkBuilder};
+
+#[cfg(unix)]
+mod unix_source;
+#[cfg(unix)]
+pub use unix_source::{UnixSource, UnixSourceBuilder};
+
 mod vector_sink;
 pub use vector_sink::{VectorSink, VectorSinkBuilder};
 mod vector_source;
@@ -85,14 +156,25 @@ mod vulkan;
 #[cfg(feature = "vulkan")]
 pub use vulkan::{Vulkan, VulkanBuilder};
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod wav;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod websocket_sink;
 #[cfg(not(target_arch = "wasm32"))]
-pub use websocket_sink::{WebsocketSink, WebsocketSinkBuilder, WebsocketSinkMode};
+pub use websocket_sink::{
+    WebsocketClientPolicy, WebsocketSink, WebsocketSinkBuilder, WebsocketSinkMode,
+};
+#[cfg(not(target_arch = "wasm32"))]
+mod websocket_source;
+#[cfg(not(target_arch = "wasm32"))]
+pub use websocket_source::{WebsocketSource, WebsocketSourceBuilder};
 
 #[cfg(feature = "zeromq")]
 pub mod zeromq;
 
+pub mod zigbee;
+
 #[cfg(feature = "zynq")]
 mod zynq;
 #[cfg(feature = "zynq")]
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::mem::size_of;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// maps data symbols and pilots onto carriers and prepends the preamble,
+// producing frequency-domain vectors of fft_len items
+pub struct CarrierAllocator {
+    format: FrameFormat,
+    symbol: usize,
+}
+
+impl CarrierAllocator {
+    pub fn new(format: FrameFormat) -> Block {
+        let fft_len = format.fft_len();
+        Block::new_sync(
+            BlockMetaBuilder::new("CarrierAllocator").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<Complex<f32>>())
+                .add_output("out", fft_len * size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<CarrierAllocator>::new().build(),
+            CarrierAllocator { format, symbol: 0 },
+        )
+    }
+
+    fn allocate(&self, data: &[Complex<f32>], out: &mut [Complex<f32>]) {
+        out.iter_mut().for_each(|x| *x = Complex::new(0.0, 0.0));
+        for (k, v) in self.format.data_carriers().iter().zip(data.iter()) {
+            out[self.format.bin(*k)] = *v;
+        }
+        for (k, v) in self
+            .format
+            .pilot_carriers()
+            .iter()
+            .zip(self.format.pilot_symbols().iter())
+        {
+            out[self.format.bin(*k)] = *v;
+        }
+    }
+}
+
+#[async_trait]
+impl SyncKernel for CarrierAllocator {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let fft_len = self.format.fft_len();
+        let n_data = self.format.data_carriers().len();
+        let finished = sio.input(0).finished();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while (produced + 1) * fft_len <= o.len() {
+            let out = &mut o[produced * fft_len..(produced + 1) * fft_len];
+            let left = i.len() - consumed;
+
+            match self.symbol {
+                0 if left == 0 => break,
+                0 => out.copy_from_slice(self.format.sync_symbol()),
+                1 => out.copy_from_slice(self.format.training_symbol()),
+                _ if left >= n_data => {
+                    self.allocate(&i[consumed..consumed + n_data], out);
+                    consumed += n_data;
+                }
+                // complete the last frame with zero symbols
+                _ if finished => {
+                    let mut data = i[consumed..].to_vec();
+                    data.resize(n_data, Complex::new(0.0, 0.0));
+                    self.allocate(&data, out);
+                    consumed = i.len();
+                }
+                _ => break,
+            }
+
+            produced += 1;
+            self.symbol = (self.symbol + 1) % self.format.symbols_per_frame();
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if finished && consumed == i.len() && self.symbol == 0 {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CarrierAllocatorBuilder {
+    format: FrameFormat,
+}
+
+impl CarrierAllocatorBuilder {
+    pub fn new() -> CarrierAllocatorBuilder {
+        CarrierAllocatorBuilder {
+            format: FrameFormat::default(),
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> CarrierAllocatorBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        CarrierAllocator::new(self.format)
+    }
+}
+
+impl Default for CarrierAllocatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,110 @@
+use anyhow::Result;
+use rustfft::num_complex::Complex;
+use rustfft::{self, FftPlanner};
+use std::mem::size_of;
+use std::sync::Arc;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// drops the cyclic prefix of time-aligned symbols and transforms them into
+// frequency-domain vectors of fft_len items
+pub struct CyclicPrefixRemover {
+    format: FrameFormat,
+    plan: Arc<dyn rustfft::Fft<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl CyclicPrefixRemover {
+    pub fn new(format: FrameFormat) -> Block {
+        let fft_len = format.fft_len();
+        let mut planner = FftPlanner::<f32>::new();
+        let plan = planner.plan_fft_forward(fft_len);
+        let scratch = vec![Complex::new(0.0, 0.0); plan.get_outofplace_scratch_len()];
+
+        Block::new_sync(
+            BlockMetaBuilder::new("CyclicPrefixRemover").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<Complex<f32>>())
+                .add_output("out", fft_len * size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<CyclicPrefixRemover>::new().build(),
+            CyclicPrefixRemover {
+                format,
+                plan,
+                scratch,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for CyclicPrefixRemover {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let fft_len = self.format.fft_len();
+        let cp_len = self.format.cp_len();
+        let symbol_len = fft_len + cp_len;
+        let scale = 1.0 / (fft_len as f32).sqrt();
+
+        let n = std::cmp::min(i.len() / symbol_len, o.len() / fft_len);
+        for k in 0..n {
+            let input = &mut i[k * symbol_len + cp_len..(k + 1) * symbol_len];
+            let out = &mut o[k * fft_len..(k + 1) * fft_len];
+            self.plan
+                .process_outofplace_with_scratch(input, out, &mut self.scratch);
+            out.iter_mut().for_each(|x| *x *= scale);
+        }
+
+        sio.input(0).consume(n * symbol_len);
+        sio.output(0).produce(n);
+
+        if sio.input(0).finished() && i.len() - n * symbol_len < symbol_len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CyclicPrefixRemoverBuilder {
+    format: FrameFormat,
+}
+
+impl CyclicPrefixRemoverBuilder {
+    pub fn new() -> CyclicPrefixRemoverBuilder {
+        CyclicPrefixRemoverBuilder {
+            format: FrameFormat::default(),
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> CyclicPrefixRemoverBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        CyclicPrefixRemover::new(self.format)
+    }
+}
+
+impl Default for CyclicPrefixRemoverBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use rustfft::num_complex::Complex;
+use rustfft::{self, FftPlanner};
+use std::mem::size_of;
+use std::sync::Arc;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// IFFT of frequency-domain vectors, prepending the cyclic prefix
+pub struct CyclicPrefixer {
+    format: FrameFormat,
+    padding: usize,
+    plan: Arc<dyn rustfft::Fft<f32>>,
+    buf: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    symbol: usize,
+}
+
+impl CyclicPrefixer {
+    pub fn new(format: FrameFormat, padding: usize) -> Block {
+        let fft_len = format.fft_len();
+        let mut planner = FftPlanner::<f32>::new();
+        let plan = planner.plan_fft_inverse(fft_len);
+        let scratch = vec![Complex::new(0.0, 0.0); plan.get_outofplace_scratch_len()];
+
+        Block::new_sync(
+            BlockMetaBuilder::new("CyclicPrefixer").build(),
+            StreamIoBuilder::new()
+                .add_input("in", fft_len * size_of::<Complex<f32>>())
+                .add_output("out", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<CyclicPrefixer>::new().build(),
+            CyclicPrefixer {
+                format,
+                padding,
+                plan,
+                buf: vec![Complex::new(0.0, 0.0); fft_len],
+                scratch,
+                symbol: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for CyclicPrefixer {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let fft_len = self.format.fft_len();
+        let cp_len = self.format.cp_len();
+        let scale = 1.0 / (fft_len as f32).sqrt();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while (consumed + 1) * fft_len <= i.len() {
+            // zeros after the last symbol of a frame
+            let last = self.symbol + 1 == self.format.symbols_per_frame();
+            let n = fft_len + cp_len + if last { self.padding } else { 0 };
+            if produced + n > o.len() {
+                break;
+            }
+
+            let mut input = i[consumed * fft_len..(consumed + 1) * fft_len].to_vec();
+            self.plan
+                .process_outofplace_with_scratch(&mut input, &mut self.buf, &mut self.scratch);
+
+            let out = &mut o[produced..produced + n];
+            for (o, v) in out
+                .iter_mut()
+                .zip(self.buf[fft_len - cp_len..].iter().chain(self.buf.iter()))
+            {
+                *o = v * scale;
+            }
+            out[fft_len + cp_len..]
+                .iter_mut()
+                .for_each(|x| *x = Complex::new(0.0, 0.0));
+
+            consumed += 1;
+            produced += n;
+            self.symbol = (self.symbol + 1) % self.format.symbols_per_frame();
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && (consumed + 1) * fft_len > i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CyclicPrefixerBuilder {
+    format: FrameFormat,
+    padding: usize,
+}
+
+impl CyclicPrefixerBuilder {
+    pub fn new() -> CyclicPrefixerBuilder {
+        CyclicPrefixerBuilder {
+            format: FrameFormat::default(),
+            padding: 0,
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> CyclicPrefixerBuilder {
+        self.format = format;
+        self
+    }
+
+    // zero samples inserted between frames
+    pub fn padding(mut self, padding: usize) -> CyclicPrefixerBuilder {
+        self.padding = padding;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        CyclicPrefixer::new(self.format, self.padding)
+    }
+}
+
+impl Default for CyclicPrefixerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,149 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::mem::size_of;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// least-squares channel estimate from the training symbol, pilots track the
+// common phase error; outputs the equalized data symbols of each frame
+pub struct Equalizer {
+    format: FrameFormat,
+    channel: Vec<Complex<f32>>,
+    symbol: usize,
+}
+
+impl Equalizer {
+    pub fn new(format: FrameFormat) -> Block {
+        let fft_len = format.fft_len();
+        Block::new_sync(
+            BlockMetaBuilder::new("Equalizer").build(),
+            StreamIoBuilder::new()
+                .add_input("in", fft_len * size_of::<Complex<f32>>())
+                .add_output("out", fft_len * size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<Equalizer>::new().build(),
+            Equalizer {
+                format,
+                channel: vec![Complex::new(1.0, 0.0); fft_len],
+                symbol: 0,
+            },
+        )
+    }
+
+    fn estimate(&mut self, symbol: &[Complex<f32>]) {
+        for ((h, y), x) in self
+            .channel
+            .iter_mut()
+            .zip(symbol.iter())
+            .zip(self.format.training_symbol().iter())
+        {
+            if x.norm_sqr() > 0.0 {
+                *h = y / x;
+            }
+        }
+    }
+
+    fn equalize(&self, symbol: &[Complex<f32>], out: &mut [Complex<f32>]) {
+        for ((o, y), h) in out.iter_mut().zip(symbol.iter()).zip(self.channel.iter()) {
+            *o = if h.norm_sqr() > f32::EPSILON {
+                y / h
+            } else {
+                Complex::new(0.0, 0.0)
+            };
+        }
+
+        let phase: Complex<f32> = self
+            .format
+            .pilot_carriers()
+            .iter()
+            .zip(self.format.pilot_symbols().iter())
+            .map(|(k, p)| out[self.format.bin(*k)] * p.conj())
+            .sum();
+        if phase.norm() > 0.0 {
+            let correction = phase.conj() / phase.norm();
+            out.iter_mut().for_each(|x| *x *= correction);
+        }
+    }
+}
+
+#[async_trait]
+impl SyncKernel for Equalizer {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let fft_len = self.format.fft_len();
+
+        let n_in = i.len() / fft_len;
+        let n_out = o.len() / fft_len;
+
+        let mut consumed = 0;
+        let mut produced = 0;
+        while consumed < n_in {
+            let symbol = &i[consumed * fft_len..(consumed + 1) * fft_len];
+            match self.symbol {
+                0 => {}
+                1 => self.estimate(symbol),
+                _ => {
+                    if produced == n_out {
+                        break;
+                    }
+                    self.equalize(symbol, &mut o[produced * fft_len..(produced + 1) * fft_len]);
+                    produced += 1;
+                }
+            }
+            consumed += 1;
+            self.symbol = (self.symbol + 1) % self.format.symbols_per_frame();
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == n_in {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct EqualizerBuilder {
+    format: FrameFormat,
+}
+
+impl EqualizerBuilder {
+    pub fn new() -> EqualizerBuilder {
+        EqualizerBuilder {
+            format: FrameFormat::default(),
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> EqualizerBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Equalizer::new(self.format)
+    }
+}
+
+impl Default for EqualizerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
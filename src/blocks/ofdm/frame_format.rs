@@ -0,0 +1,160 @@
+use anyhow::{ensure, Result};
+use num_complex::Complex;
+use std::collections::HashSet;
+use std::f32::consts::SQRT_2;
+
+// frame layout shared by the transmit and receive chain: a Schmidl-Cox
+// sync symbol, a training symbol for channel estimation, and data symbols
+#[derive(Clone, Debug)]
+pub struct FrameFormat {
+    fft_len: usize,
+    cp_len: usize,
+    data_carriers: Vec<isize>,
+    pilot_carriers: Vec<isize>,
+    pilot_symbols: Vec<Complex<f32>>,
+    n_symbols: usize,
+    sync_symbol: Vec<Complex<f32>>,
+    training_symbol: Vec<Complex<f32>>,
+}
+
+// deterministic BPSK sequence for the preamble
+fn pn_sequence(n: usize) -> Vec<f32> {
+    let mut state = 0x5du8;
+    (0..n)
+        .map(|_| {
+            let bit = ((state >> 6) ^ (state >> 5)) & 1;
+            state = (state << 1) | bit;
+            if bit == 1 {
+                1.0
+            } else {
+                -1.0
+            }
+        })
+        .collect()
+}
+
+impl FrameFormat {
+    pub fn new(
+        fft_len: usize,
+        cp_len: usize,
+        data_carriers: Vec<isize>,
+        pilot_carriers: Vec<isize>,
+        pilot_symbols: Vec<Complex<f32>>,
+        n_symbols: usize,
+    ) -> Result<FrameFormat> {
+        ensure!(
+            fft_len >= 4 && fft_len & 1 == 0,
+            "fft length has to be even"
+        );
+        ensure!(cp_len < fft_len, "cyclic prefix longer than symbol");
+        ensure!(!data_carriers.is_empty(), "no data carriers");
+        ensure!(n_symbols > 0, "no data symbols per frame");
+        ensure!(
+            pilot_carriers.len() == pilot_symbols.len(),
+            "{} pilot carriers but {} pilot symbols",
+            pilot_carriers.len(),
+            pilot_symbols.len()
+        );
+
+        let half = (fft_len / 2) as isize;
+        let mut used = HashSet::new();
+        for k in data_carriers.iter().chain(pilot_carriers.iter()) {
+            ensure!(*k >= -half && *k < half, "carrier {} outside of fft", k);
+            ensure!(used.insert(*k), "carrier {} allocated twice", k);
+        }
+
+        let mut occupied: Vec<isize> = used.into_iter().collect();
+        occupied.sort_unstable();
+
+        let mut format = FrameFormat {
+            fft_len,
+            cp_len,
+            data_carriers,
+            pilot_carriers,
+            pilot_symbols,
+            n_symbols,
+            sync_symbol: vec![Complex::new(0.0, 0.0); fft_len],
+            training_symbol: vec![Complex::new(0.0, 0.0); fft_len],
+        };
+
+        // only even carriers, giving two identical halves in time domain
+        let pn = pn_sequence(occupied.len());
+        for (k, v) in occupied.iter().zip(pn.iter()) {
+            let bin = format.bin(*k);
+            if k % 2 == 0 {
+                format.sync_symbol[bin] = Complex::new(v * SQRT_2, 0.0);
+            }
+            format.training_symbol[bin] = Complex::new(*v, 0.0);
+        }
+        ensure!(
+            format.sync_symbol.iter().any(|x| x.norm_sqr() > 0.0),
+            "no even carriers for the sync symbol"
+        );
+
+        Ok(format)
+    }
+
+    // fft bin of a carrier index relative to DC
+    pub fn bin(&self, carrier: isize) -> usize {
+        (carrier + self.fft_len as isize) as usize % self.fft_len
+    }
+
+    pub fn fft_len(&self) -> usize {
+        self.fft_len
+    }
+
+    pub fn cp_len(&self) -> usize {
+        self.cp_len
+    }
+
+    pub fn data_carriers(&self) -> &[isize] {
+        &self.data_carriers
+    }
+
+    pub fn pilot_carriers(&self) -> &[isize] {
+        &self.pilot_carriers
+    }
+
+    pub fn pilot_symbols(&self) -> &[Complex<f32>] {
+        &self.pilot_symbols
+    }
+
+    // data symbols per frame
+    pub fn n_symbols(&self) -> usize {
+        self.n_symbols
+    }
+
+    // all OFDM symbols per frame, including the preamble
+    pub fn symbols_per_frame(&self) -> usize {
+        self.n_symbols + 2
+    }
+
+    // samples per frame, including cyclic prefixes
+    pub fn frame_len(&self) -> usize {
+        self.symbols_per_frame() * (self.fft_len + self.cp_len)
+    }
+
+    pub fn sync_symbol(&self) -> &[Complex<f32>] {
+        &self.sync_symbol
+    }
+
+    pub fn training_symbol(&self) -> &[Complex<f32>] {
+        &self.training_symbol
+    }
+}
+
+// 802.11a-like layout: 64 carriers, 48 data carriers, 4 pilots
+impl Default for FrameFormat {
+    fn default() -> Self {
+        let pilot_carriers = vec![-21, -7, 7, 21];
+        let data_carriers = (-26..=26)
+            .filter(|k| *k != 0 && !pilot_carriers.contains(k))
+            .collect();
+        let pilot_symbols = [1.0, 1.0, 1.0, -1.0]
+            .iter()
+            .map(|x| Complex::new(*x, 0.0))
+            .collect();
+
+        FrameFormat::new(64, 16, data_carriers, pilot_carriers, pilot_symbols, 10).unwrap()
+    }
+}
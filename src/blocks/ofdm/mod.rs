@@ -0,0 +1,14 @@
+mod carrier_allocator;
+pub use carrier_allocator::{CarrierAllocator, CarrierAllocatorBuilder};
+mod cyclic_prefix_remover;
+pub use cyclic_prefix_remover::{CyclicPrefixRemover, CyclicPrefixRemoverBuilder};
+mod cyclic_prefixer;
+pub use cyclic_prefixer::{CyclicPrefixer, CyclicPrefixerBuilder};
+mod equalizer;
+pub use equalizer::{Equalizer, EqualizerBuilder};
+mod frame_format;
+pub use frame_format::FrameFormat;
+mod schmidl_cox;
+pub use schmidl_cox::{SchmidlCox, SchmidlCoxBuilder};
+mod serializer;
+pub use serializer::{Serializer, SerializerBuilder};
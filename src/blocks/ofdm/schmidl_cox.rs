@@ -0,0 +1,210 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+enum State {
+    Search,
+    Frame {
+        remaining: usize,
+        omega: f32,
+        n: usize,
+    },
+}
+
+// Schmidl-Cox timing and (fractional) frequency synchronization, outputs
+// detected frames back-to-back with the frequency offset removed
+pub struct SchmidlCox {
+    format: FrameFormat,
+    threshold: f32,
+    buf: Vec<Complex<f32>>,
+    pos: usize,
+    state: State,
+    n_frames: u64,
+}
+
+impl SchmidlCox {
+    pub fn new(format: FrameFormat, threshold: f32) -> Block {
+        Block::new_sync(
+            BlockMetaBuilder::new("SchmidlCox").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<Complex<f32>>())
+                .add_output("out", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<SchmidlCox>::new().build(),
+            SchmidlCox {
+                format,
+                threshold,
+                buf: Vec::new(),
+                pos: 0,
+                state: State::Search,
+                n_frames: 0,
+            },
+        )
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.n_frames
+    }
+
+    // correlation of the two halves of the sync symbol and the timing metric
+    fn metric(&self, d: usize) -> (Complex<f32>, f32) {
+        let l = self.format.fft_len() / 2;
+        let mut p = Complex::new(0.0, 0.0);
+        let mut r = 0.0;
+        for m in d..d + l {
+            p += self.buf[m].conj() * self.buf[m + l];
+            r += self.buf[m + l].norm_sqr();
+        }
+        if r < f32::EPSILON {
+            (p, 0.0)
+        } else {
+            (p, p.norm_sqr() / (r * r))
+        }
+    }
+
+    // locate the plateau caused by the cyclic prefix and its center
+    fn search(&mut self) -> bool {
+        let fft_len = self.format.fft_len();
+        let cp_len = self.format.cp_len();
+        let window = 2 * cp_len + 2;
+
+        while self.pos + window + fft_len <= self.buf.len() {
+            if self.metric(self.pos).1 < self.threshold {
+                self.pos += 1;
+                continue;
+            }
+
+            let m: Vec<(Complex<f32>, f32)> = (self.pos..self.pos + window)
+                .map(|d| self.metric(d))
+                .collect();
+            let max = m.iter().map(|x| x.1).fold(0.0, f32::max);
+            let first = m.iter().position(|x| x.1 >= 0.9 * max).unwrap();
+            let last = m.iter().rposition(|x| x.1 >= 0.9 * max).unwrap();
+            let mid = (first + last) / 2;
+
+            let omega = m[mid].0.arg() / (fft_len / 2) as f32;
+            // start early, within the cyclic prefix
+            self.pos = (self.pos + mid).saturating_sub(3 * cp_len / 4);
+            self.state = State::Frame {
+                remaining: self.format.frame_len(),
+                omega,
+                n: 0,
+            };
+            self.n_frames += 1;
+            debug!("schmidl cox: frame, cfo {} rad/sample", omega);
+            return true;
+        }
+        false
+    }
+}
+
+#[async_trait]
+impl SyncKernel for SchmidlCox {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+
+        self.buf.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        let mut produced = 0;
+        loop {
+            match self.state {
+                State::Search => {
+                    if !self.search() {
+                        break;
+                    }
+                }
+                State::Frame {
+                    remaining,
+                    omega,
+                    n,
+                } => {
+                    let k = cmp::min(
+                        remaining,
+                        cmp::min(self.buf.len() - self.pos, o.len() - produced),
+                    );
+                    for j in 0..k {
+                        o[produced + j] = self.buf[self.pos + j]
+                            * Complex::from_polar(1.0, -omega * (n + j) as f32);
+                    }
+                    produced += k;
+                    self.pos += k;
+
+                    if k == remaining {
+                        self.state = State::Search;
+                    } else {
+                        self.state = State::Frame {
+                            remaining: remaining - k,
+                            omega,
+                            n: n + k,
+                        };
+                        break;
+                    }
+                }
+            }
+        }
+
+        self.buf.drain(0..self.pos);
+        self.pos = 0;
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && (matches!(self.state, State::Search) || self.buf.is_empty()) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SchmidlCoxBuilder {
+    format: FrameFormat,
+    threshold: f32,
+}
+
+impl SchmidlCoxBuilder {
+    pub fn new() -> SchmidlCoxBuilder {
+        SchmidlCoxBuilder {
+            format: FrameFormat::default(),
+            threshold: 0.6,
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> SchmidlCoxBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> SchmidlCoxBuilder {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        SchmidlCox::new(self.format, self.threshold)
+    }
+}
+
+impl Default for SchmidlCoxBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::cmp;
+use std::mem::size_of;
+
+use crate::blocks::ofdm::FrameFormat;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::SyncKernel;
+use crate::runtime::WorkIo;
+
+// extracts the data carriers of frequency-domain vectors
+pub struct Serializer {
+    format: FrameFormat,
+}
+
+impl Serializer {
+    pub fn new(format: FrameFormat) -> Block {
+        let fft_len = format.fft_len();
+        Block::new_sync(
+            BlockMetaBuilder::new("Serializer").build(),
+            StreamIoBuilder::new()
+                .add_input("in", fft_len * size_of::<Complex<f32>>())
+                .add_output("out", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::<Serializer>::new().build(),
+            Serializer { format },
+        )
+    }
+}
+
+#[async_trait]
+impl SyncKernel for Serializer {
+    fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let fft_len = self.format.fft_len();
+        let n_data = self.format.data_carriers().len();
+
+        let n = cmp::min(i.len() / fft_len, o.len() / n_data);
+        for k in 0..n {
+            let symbol = &i[k * fft_len..(k + 1) * fft_len];
+            for (o, c) in o[k * n_data..(k + 1) * n_data]
+                .iter_mut()
+                .zip(self.format.data_carriers().iter())
+            {
+                *o = symbol[self.format.bin(*c)];
+            }
+        }
+
+        sio.input(0).consume(n);
+        sio.output(0).produce(n * n_data);
+
+        if sio.input(0).finished() && n == i.len() / fft_len {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+pub struct SerializerBuilder {
+    format: FrameFormat,
+}
+
+impl SerializerBuilder {
+    pub fn new() -> SerializerBuilder {
+        SerializerBuilder {
+            format: FrameFormat::default(),
+        }
+    }
+
+    pub fn format(mut self, format: FrameFormat) -> SerializerBuilder {
+        self.format = format;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Serializer::new(self.format)
+    }
+}
+
+impl Default for SerializerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,9 @@
+mod protocol;
+pub use protocol::{
+    decode, encode, Command, DongleInfo, COMMAND_LEN, HEADER_LEN, MAGIC, TUNER_E4000, TUNER_FC0012,
+    TUNER_FC0013, TUNER_FC2580, TUNER_R820T, TUNER_R828D, TUNER_UNKNOWN,
+};
+mod sink;
+pub use sink::{RtlTcpSink, RtlTcpSinkBuilder};
+mod source;
+pub use source::{RtlTcpSource, RtlTcpSourceBuilder};
@@ -0,0 +1,124 @@
+use anyhow::{bail, Result};
+use num_complex::Complex;
+
+// "RTL0", tuner type, and number of gain steps, all big endian
+pub const HEADER_LEN: usize = 12;
+pub const MAGIC: &[u8; 4] = b"RTL0";
+// command byte followed by a big endian u32 parameter
+pub const COMMAND_LEN: usize = 5;
+
+pub const TUNER_UNKNOWN: u32 = 0;
+pub const TUNER_E4000: u32 = 1;
+pub const TUNER_FC0012: u32 = 2;
+pub const TUNER_FC0013: u32 = 3;
+pub const TUNER_FC2580: u32 = 4;
+pub const TUNER_R820T: u32 = 5;
+pub const TUNER_R828D: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DongleInfo {
+    pub tuner_type: u32,
+    pub gain_count: u32,
+}
+
+impl DongleInfo {
+    pub fn parse(b: &[u8]) -> Result<DongleInfo> {
+        if b.len() < HEADER_LEN || &b[0..4] != MAGIC {
+            bail!("rtl_tcp: invalid dongle info header {:?}", b);
+        }
+        Ok(DongleInfo {
+            tuner_type: u32::from_be_bytes([b[4], b[5], b[6], b[7]]),
+            gain_count: u32::from_be_bytes([b[8], b[9], b[10], b[11]]),
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; HEADER_LEN] {
+        let mut b = [0; HEADER_LEN];
+        b[0..4].copy_from_slice(MAGIC);
+        b[4..8].copy_from_slice(&self.tuner_type.to_be_bytes());
+        b[8..12].copy_from_slice(&self.gain_count.to_be_bytes());
+        b
+    }
+}
+
+// gains are in tenth of a dB, frequency correction in ppm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Frequency(u32),
+    SampleRate(u32),
+    // false: automatic, true: manual
+    GainMode(bool),
+    Gain(i32),
+    FreqCorrection(i32),
+    // stage in the upper, gain in the lower 16 bits
+    IfGain(u32),
+    TestMode(bool),
+    Agc(bool),
+    DirectSampling(u32),
+    OffsetTuning(bool),
+    RtlXtal(u32),
+    TunerXtal(u32),
+    GainByIndex(u32),
+    BiasTee(bool),
+}
+
+impl Command {
+    pub fn parse(b: &[u8; COMMAND_LEN]) -> Option<Command> {
+        let v = u32::from_be_bytes([b[1], b[2], b[3], b[4]]);
+        Some(match b[0] {
+            0x01 => Command::Frequency(v),
+            0x02 => Command::SampleRate(v),
+            0x03 => Command::GainMode(v != 0),
+            0x04 => Command::Gain(v as i32),
+            0x05 => Command::FreqCorrection(v as i32),
+            0x06 => Command::IfGain(v),
+            0x07 => Command::TestMode(v != 0),
+            0x08 => Command::Agc(v != 0),
+            0x09 => Command::DirectSampling(v),
+            0x0a => Command::OffsetTuning(v != 0),
+            0x0b => Command::RtlXtal(v),
+            0x0c => Command::TunerXtal(v),
+            0x0d => Command::GainByIndex(v),
+            0x0e => Command::BiasTee(v != 0),
+            _ => return None,
+        })
+    }
+
+    pub fn to_bytes(&self) -> [u8; COMMAND_LEN] {
+        let (c, v) = match *self {
+            Command::Frequency(v) => (0x01, v),
+            Command::SampleRate(v) => (0x02, v),
+            Command::GainMode(v) => (0x03, v as u32),
+            Command::Gain(v) => (0x04, v as u32),
+            Command::FreqCorrection(v) => (0x05, v as u32),
+            Command::IfGain(v) => (0x06, v),
+            Command::TestMode(v) => (0x07, v as u32),
+            Command::Agc(v) => (0x08, v as u32),
+            Command::DirectSampling(v) => (0x09, v),
+            Command::OffsetTuning(v) => (0x0a, v as u32),
+            Command::RtlXtal(v) => (0x0b, v),
+            Command::TunerXtal(v) => (0x0c, v),
+            Command::GainByIndex(v) => (0x0d, v),
+            Command::BiasTee(v) => (0x0e, v as u32),
+        };
+        let mut b = [c; COMMAND_LEN];
+        b[1..].copy_from_slice(&v.to_be_bytes());
+        b
+    }
+}
+
+// interleaved offset binary IQ samples
+pub fn decode(bytes: &[u8], out: &mut [Complex<f32>]) {
+    for (b, o) in bytes.chunks_exact(2).zip(out.iter_mut()) {
+        *o = Complex::new((b[0] as f32 - 127.5) / 127.5, (b[1] as f32 - 127.5) / 127.5);
+    }
+}
+
+// samples are clipped to [-1, 1]
+pub fn encode(samples: &[Complex<f32>], out: &mut Vec<u8>) {
+    let q = |x: f32| (x.clamp(-1.0, 1.0) * 127.5 + 127.5).round() as u8;
+    for s in samples {
+        out.push(q(s.re));
+        out.push(q(s.im));
+    }
+}
@@ -0,0 +1,241 @@
+use anyhow::{Context, Result};
+use async_io::Timer;
+use async_net::{Shutdown, TcpListener, TcpStream};
+use futures::AsyncReadExt;
+use futures::AsyncWriteExt;
+use futures::FutureExt;
+use futures_lite::future;
+use num_complex::Complex;
+use std::mem::size_of;
+use std::time::Duration;
+
+use crate::blocks::rtl_tcp::{encode, Command, DongleInfo, COMMAND_LEN, TUNER_R820T};
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// wait for the client to close the connection
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(1);
+
+// Serves the Complex<f32> input stream to one rtl_tcp client at a time. The
+// block waits for a client, sends the dongle info, and streams the samples as
+// offset binary u8. When the client disconnects, it waits for the next one.
+//
+// Commands from the client are posted on the message outputs "freq" and
+// "sample_rate" (U32 Hz), "gain" (Double dB, Null for automatic gain), and
+// "freq_correction" (U32 ppm, two's complement), so that they can be forwarded
+// to the actual source of the stream.
+pub struct RtlTcpSink {
+    address: String,
+    info: DongleInfo,
+    listener: Option<TcpListener>,
+    socket: Option<TcpStream>,
+    command: [u8; COMMAND_LEN],
+    command_len: usize,
+    manual_gain: bool,
+    buffer: Vec<u8>,
+    commands: Vec<Command>,
+}
+
+impl RtlTcpSink {
+    pub fn new(address: &str) -> Block {
+        RtlTcpSinkBuilder::new(address).build()
+    }
+
+    // commands received from clients
+    pub fn commands(&self) -> &Vec<Command> {
+        &self.commands
+    }
+
+    // read commands without waiting for the client
+    fn poll_commands(&mut self) -> Vec<Command> {
+        let mut cmds = Vec::new();
+        while let Some(socket) = self.socket.as_mut() {
+            match socket
+                .read(&mut self.command[self.command_len..])
+                .now_or_never()
+            {
+                Some(Ok(n)) if n > 0 => {
+                    self.command_len += n;
+                    if self.command_len == COMMAND_LEN {
+                        self.command_len = 0;
+                        match Command::parse(&self.command) {
+                            Some(c) => cmds.push(c),
+                            None => warn!("rtl_tcp sink: unknown command {:?}", self.command),
+                        }
+                    }
+                }
+                Some(_) => {
+                    debug!("rtl_tcp sink: client disconnected");
+                    self.socket = None;
+                }
+                None => break,
+            }
+        }
+        cmds
+    }
+
+    async fn handle(&mut self, mio: &mut MessageIo<Self>, cmd: Command) {
+        debug!("rtl_tcp sink: received {:?}", cmd);
+        self.commands.push(cmd);
+        match cmd {
+            Command::Frequency(f) => mio.post(0, Pmt::U32(f)).await,
+            Command::SampleRate(r) => mio.post(1, Pmt::U32(r)).await,
+            Command::GainMode(manual) => {
+                self.manual_gain = manual;
+                if !manual {
+                    mio.post(2, Pmt::Null).await;
+                }
+            }
+            Command::Gain(g) if self.manual_gain => mio.post(2, Pmt::Double(g as f64 / 10.0)).await,
+            Command::FreqCorrection(ppm) => mio.post(3, Pmt::U32(ppm as u32)).await,
+            _ => {}
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for RtlTcpSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.socket.is_none() {
+            let (mut socket, peer) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            debug!("rtl_tcp sink: accepted connection from {}", peer);
+            if socket.write_all(&self.info.to_bytes()).await.is_err() {
+                io.call_again = true;
+                return Ok(());
+            }
+            self.socket = Some(socket);
+            self.command_len = 0;
+        }
+
+        for cmd in self.poll_commands() {
+            self.handle(mio, cmd).await;
+        }
+
+        let i = sio.input(0).slice::<Complex<f32>>();
+        if let Some(socket) = self.socket.as_mut() {
+            self.buffer.clear();
+            encode(i, &mut self.buffer);
+            if let Err(e) = socket.write_all(&self.buffer).await {
+                debug!("rtl_tcp sink: client disconnected ({})", e);
+                self.socket = None;
+            }
+        }
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        } else if self.socket.is_none() {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.listener = Some(
+            TcpListener::bind(&self.address)
+                .await
+                .with_context(|| format!("rtl_tcp sink: cannot bind {}", self.address))?,
+        );
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // Close gracefully, since closing a socket with unread commands resets
+        // the connection, and the client might lose the last samples.
+        if let Some(mut s) = self.socket.take() {
+            let _ = s.shutdown(Shutdown::Write);
+            let drain = async {
+                let mut b = [0u8; 64];
+                while let Ok(n) = s.read(&mut b).await {
+                    if n == 0 {
+                        break;
+                    }
+                }
+            };
+            future::or(drain, async {
+                Timer::after(CLOSE_TIMEOUT).await;
+            })
+            .await;
+        }
+        Ok(())
+    }
+}
+
+pub struct RtlTcpSinkBuilder {
+    address: String,
+    info: DongleInfo,
+}
+
+impl RtlTcpSinkBuilder {
+    // local address, e.g., 0.0.0.0:1234
+    pub fn new(address: &str) -> RtlTcpSinkBuilder {
+        RtlTcpSinkBuilder {
+            address: address.to_string(),
+            info: DongleInfo {
+                tuner_type: TUNER_R820T,
+                gain_count: 29,
+            },
+        }
+    }
+
+    // tuner and gain steps reported to clients
+    pub fn dongle_info(mut self, info: DongleInfo) -> RtlTcpSinkBuilder {
+        self.info = info;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("RtlTcpSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_output("freq")
+                .add_output("sample_rate")
+                .add_output("gain")
+                .add_output("freq_correction")
+                .build(),
+            RtlTcpSink {
+                address: self.address,
+                info: self.info,
+                listener: None,
+                socket: None,
+                command: [0; COMMAND_LEN],
+                command_len: 0,
+                manual_gain: false,
+                buffer: Vec::new(),
+                commands: Vec::new(),
+            },
+        )
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::{bail, Context, Result};
+use async_net::TcpStream;
+use futures::AsyncReadExt;
+use futures::AsyncWriteExt;
+use futures::FutureExt;
+use num_complex::Complex;
+use std::future::Future;
+use std::mem::size_of;
+use std::pin::Pin;
+
+use crate::blocks::rtl_tcp::{decode, Command, DongleInfo, HEADER_LEN};
+use crate::blocks::ItemReader;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const READ_SIZE: usize = 1 << 16;
+
+// Connects to an rtl_tcp server and outputs the samples as Complex<f32>.
+//
+// Message handlers send commands to the server and return the requested value:
+// "freq" and "sample_rate" (Hz as U32, U64, or Double), "gain" (dB as Double or
+// U32; Null for automatic gain), and "freq_correction" (ppm as U32 or Double).
+// Values of another type or out of range are an error.
+pub struct RtlTcpSource {
+    address: String,
+    freq: Option<u32>,
+    sample_rate: Option<u32>,
+    gain: Option<Option<f64>>,
+    socket: Option<TcpStream>,
+    info: Option<DongleInfo>,
+    buffer: Vec<u8>,
+    reader: ItemReader,
+}
+
+impl RtlTcpSource {
+    pub fn new(address: &str) -> Block {
+        RtlTcpSourceBuilder::new(address).build()
+    }
+
+    // tuner and gain steps reported by the server
+    pub fn dongle_info(&self) -> Option<DongleInfo> {
+        self.info
+    }
+
+    async fn send(&mut self, cmd: Command) -> Result<()> {
+        debug!("rtl_tcp source: sending {:?}", cmd);
+        self.socket
+            .as_mut()
+            .context("rtl_tcp source: not connected")?
+            .write_all(&cmd.to_bytes())
+            .await
+            .context("rtl_tcp source: cannot send command")
+    }
+
+    async fn set_gain(&mut self, gain: Option<f64>) -> Result<()> {
+        match gain {
+            Some(g) => {
+                self.send(Command::GainMode(true)).await?;
+                self.send(Command::Gain((g * 10.0).round() as i32)).await
+            }
+            None => self.send(Command::GainMode(false)).await,
+        }
+    }
+
+    fn freq_handler<'a>(
+        &'a mut self,
+        _mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match pmt_to_u32(&p) {
+                Some(f) => self.send(Command::Frequency(f)).await?,
+                None => bail!("rtl_tcp source: invalid frequency {:?}", p),
+            }
+            Ok(p)
+        }
+        .boxed()
+    }
+
+    fn sample_rate_handler<'a>(
+        &'a mut self,
+        _mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match pmt_to_u32(&p) {
+                Some(r) => self.send(Command::SampleRate(r)).await?,
+                None => bail!("rtl_tcp source: invalid sample rate {:?}", p),
+            }
+            Ok(p)
+        }
+        .boxed()
+    }
+
+    fn gain_handler<'a>(
+        &'a mut self,
+        _mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Null => self.set_gain(None).await?,
+                Pmt::Double(g) => self.set_gain(Some(g)).await?,
+                Pmt::U32(g) => self.set_gain(Some(g as f64)).await?,
+                _ => bail!("rtl_tcp source: invalid gain {:?}", p),
+            }
+            Ok(p)
+        }
+        .boxed()
+    }
+
+    fn freq_correction_handler<'a>(
+        &'a mut self,
+        _mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::U32(ppm) => self.send(Command::FreqCorrection(ppm as i32)).await?,
+                Pmt::Double(ppm) => {
+                    self.send(Command::FreqCorrection(ppm.round() as i32))
+                        .await?
+                }
+                _ => bail!("rtl_tcp source: invalid frequency correction {:?}", p),
+            }
+            Ok(p)
+        }
+        .boxed()
+    }
+}
+
+fn pmt_to_u32(p: &Pmt) -> Option<u32> {
+    match *p {
+        Pmt::U32(v) => Some(v),
+        Pmt::U64(v) if v <= u32::MAX as u64 => Some(v as u32),
+        Pmt::Double(v) if v >= 0.0 && v <= u32::MAX as f64 => Some(v.round() as u32),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for RtlTcpSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<Complex<f32>>();
+        if out.is_empty() {
+            return Ok(());
+        }
+
+        let max = std::cmp::min(out.len() * 2, self.buffer.len());
+        let socket = self
+            .socket
+            .as_mut()
+            .context("rtl_tcp source: not connected")?;
+
+        // poll, so that message handlers are not blocked while waiting for samples
+        let items = match self
+            .reader
+            .read(socket, &mut self.buffer[..max])
+            .now_or_never()
+        {
+            Some(r) => r.context("rtl_tcp source: read error")?,
+            None => {
+                let socket = socket.clone();
+                io.block_on(async move {
+                    let _ = socket.peek(&mut [0u8; 1]).await;
+                });
+                return Ok(());
+            }
+        };
+
+        match items {
+            Some(items) => {
+                decode(&self.buffer[..items * 2], out);
+                sio.output(0).produce(items);
+                io.call_again = true;
+            }
+            None => {
+                debug!("rtl_tcp source: connection closed");
+                io.finished = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let mut socket = TcpStream::connect(&self.address)
+            .await
+            .with_context(|| format!("rtl_tcp source: cannot connect to {}", self.address))?;
+
+        let mut header = [0u8; HEADER_LEN];
+        socket
+            .read_exact(&mut header)
+            .await
+            .context("rtl_tcp source: no dongle info")?;
+        let info = DongleInfo::parse(&header)?;
+        debug!("rtl_tcp source: connected to {}, {:?}", self.address, info);
+        self.info = Some(info);
+        self.socket = Some(socket);
+
+        if let Some(r) = self.sample_rate {
+            self.send(Command::SampleRate(r)).await?;
+        }
+        if let Some(f) = self.freq {
+            self.send(Command::Frequency(f)).await?;
+        }
+        if let Some(g) = self.gain {
+            self.set_gain(g).await?;
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(mut s) = self.socket.take() {
+            let _ = s.close().await;
+        }
+        Ok(())
+    }
+}
+
+pub struct RtlTcpSourceBuilder {
+    address: String,
+    freq: Option<u32>,
+    sample_rate: Option<u32>,
+    gain: Option<Option<f64>>,
+}
+
+impl RtlTcpSourceBuilder {
+    // server address, e.g., 127.0.0.1:1234
+    pub fn new(address: &str) -> RtlTcpSourceBuilder {
+        RtlTcpSourceBuilder {
+            address: address.to_string(),
+            freq: None,
+            sample_rate: None,
+            gain: None,
+        }
+    }
+
+    pub fn freq(mut self, freq: u32) -> RtlTcpSourceBuilder {
+        self.freq = Some(freq);
+        self
+    }
+
+    pub fn sample_rate(mut self, sample_rate: u32) -> RtlTcpSourceBuilder {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    // gain in dB, None for automatic gain
+    pub fn gain(mut self, gain: Option<f64>) -> RtlTcpSourceBuilder {
+        self.gain = Some(gain);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("RtlTcpSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_async_input("freq", RtlTcpSource::freq_handler)
+                .add_async_input("sample_rate", RtlTcpSource::sample_rate_handler)
+                .add_async_input("gain", RtlTcpSource::gain_handler)
+                .add_async_input("freq_correction", RtlTcpSource::freq_correction_handler)
+                .input_kinds("freq", &[PmtKind::U32, PmtKind::U64, PmtKind::Double])
+                .input_kinds(
+                    "sample_rate",
+                    &[PmtKind::U32, PmtKind::U64, PmtKind::Double],
+                )
+                .input_kinds("gain", &[PmtKind::Double, PmtKind::U32])
+                .input_kinds("freq_correction", &[PmtKind::U32, PmtKind::Double])
+                .build(),
+            RtlTcpSource {
+                address: self.address,
+                freq: self.freq,
+                sample_rate: self.sample_rate,
+                gain: self.gain,
+                socket: None,
+                info: None,
+                buffer: vec![0; READ_SIZE],
+                reader: ItemReader::new(2),
+            },
+        )
+    }
+}
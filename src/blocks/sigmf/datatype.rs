@@ -0,0 +1,154 @@
+use anyhow::{bail, Error, Result};
+use num_complex::Complex;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    F64,
+    F32,
+    I32,
+    I16,
+    I8,
+    U32,
+    U16,
+    U8,
+}
+
+impl SampleFormat {
+    pub fn size(&self) -> usize {
+        match self {
+            SampleFormat::F64 => 8,
+            SampleFormat::F32 | SampleFormat::I32 | SampleFormat::U32 => 4,
+            SampleFormat::I16 | SampleFormat::U16 => 2,
+            SampleFormat::I8 | SampleFormat::U8 => 1,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SampleFormat::F64 => "f64",
+            SampleFormat::F32 => "f32",
+            SampleFormat::I32 => "i32",
+            SampleFormat::I16 => "i16",
+            SampleFormat::I8 => "i8",
+            SampleFormat::U32 => "u32",
+            SampleFormat::U16 => "u16",
+            SampleFormat::U8 => "u8",
+        }
+    }
+}
+
+// SigMF core:datatype, e.g., cf32_le or ru8
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Datatype {
+    pub complex: bool,
+    pub format: SampleFormat,
+    pub big_endian: bool,
+}
+
+impl Datatype {
+    pub fn item_size(&self) -> usize {
+        if self.complex {
+            2 * self.format.size()
+        } else {
+            self.format.size()
+        }
+    }
+
+    // the samples can be copied from/to the file without byte swapping
+    pub fn is_native(&self) -> bool {
+        self.format.size() == 1 || self.big_endian == cfg!(target_endian = "big")
+    }
+}
+
+impl fmt::Display for Datatype {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            if self.complex { "c" } else { "r" },
+            self.format.name()
+        )?;
+        if self.format.size() > 1 {
+            write!(f, "{}", if self.big_endian { "_be" } else { "_le" })?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Datatype {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Datatype> {
+        let complex = match s.get(..1) {
+            Some("c") => true,
+            Some("r") => false,
+            _ => bail!("invalid SigMF datatype {:?}", s),
+        };
+
+        let (format, endianness) = match s[1..].split_once('_') {
+            Some((f, e)) => (f, Some(e)),
+            None => (&s[1..], None),
+        };
+
+        let format = match format {
+            "f64" => SampleFormat::F64,
+            "f32" => SampleFormat::F32,
+            "i32" => SampleFormat::I32,
+            "i16" => SampleFormat::I16,
+            "i8" => SampleFormat::I8,
+            "u32" => SampleFormat::U32,
+            "u16" => SampleFormat::U16,
+            "u8" => SampleFormat::U8,
+            _ => bail!("invalid SigMF datatype {:?}", s),
+        };
+
+        let big_endian = match (format.size(), endianness) {
+            (1, None) => false,
+            (n, Some("le")) if n > 1 => false,
+            (n, Some("be")) if n > 1 => true,
+            _ => bail!("invalid SigMF datatype {:?}", s),
+        };
+
+        Ok(Datatype {
+            complex,
+            format,
+            big_endian,
+        })
+    }
+}
+
+// stream item types with a SigMF representation in native byte order
+pub trait SigMfFormat: Copy + Send + Sync + 'static {
+    const DATATYPE: Datatype;
+}
+
+macro_rules! impl_sigmf_format {
+    ($t:ty, $format:expr) => {
+        impl SigMfFormat for $t {
+            const DATATYPE: Datatype = Datatype {
+                complex: false,
+                format: $format,
+                big_endian: cfg!(target_endian = "big"),
+            };
+        }
+
+        impl SigMfFormat for Complex<$t> {
+            const DATATYPE: Datatype = Datatype {
+                complex: true,
+                format: $format,
+                big_endian: cfg!(target_endian = "big"),
+            };
+        }
+    };
+}
+
+impl_sigmf_format!(f64, SampleFormat::F64);
+impl_sigmf_format!(f32, SampleFormat::F32);
+impl_sigmf_format!(i32, SampleFormat::I32);
+impl_sigmf_format!(i16, SampleFormat::I16);
+impl_sigmf_format!(i8, SampleFormat::I8);
+impl_sigmf_format!(u32, SampleFormat::U32);
+impl_sigmf_format!(u16, SampleFormat::U16);
+impl_sigmf_format!(u8, SampleFormat::U8);
@@ -0,0 +1,221 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+use crate::blocks::sigmf::Datatype;
+
+pub const VERSION: &str = "1.0.0";
+
+// the .sigmf-meta and .sigmf-data paths of a recording, given either the
+// base name or one of the two files
+pub fn paths<P: AsRef<Path>>(path: P) -> (PathBuf, PathBuf) {
+    let path = path.as_ref();
+    let base = match path.extension().and_then(|e| e.to_str()) {
+        Some("sigmf-meta") | Some("sigmf-data") => path.with_extension(""),
+        _ => path.to_path_buf(),
+    };
+    let mut meta = base.clone().into_os_string();
+    meta.push(".sigmf-meta");
+    let mut data = base.into_os_string();
+    data.push(".sigmf-data");
+    (meta.into(), data.into())
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Description {
+    pub global: Global,
+    #[serde(default)]
+    pub captures: Vec<Capture>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+}
+
+impl Description {
+    pub fn new(datatype: Datatype) -> Description {
+        Description {
+            global: Global::new(datatype),
+            captures: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Description> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("cannot open SigMF metadata {:?}", path))?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("invalid SigMF metadata {:?}", path))
+    }
+
+    pub fn to_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let file = std::fs::File::create(path)
+            .with_context(|| format!("cannot create SigMF metadata {:?}", path))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    // the spec requires captures and annotations to be sorted by sample_start
+    pub fn sort(&mut self) {
+        self.captures.sort_by_key(|c| c.sample_start);
+        self.annotations.sort_by_key(|a| a.sample_start);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Global {
+    #[serde(rename = "core:datatype", with = "datatype")]
+    pub datatype: Datatype,
+    #[serde(rename = "core:version")]
+    pub version: String,
+    #[serde(
+        rename = "core:sample_rate",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sample_rate: Option<f64>,
+    #[serde(
+        rename = "core:description",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub description: Option<String>,
+    #[serde(
+        rename = "core:author",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub author: Option<String>,
+    #[serde(rename = "core:hw", default, skip_serializing_if = "Option::is_none")]
+    pub hw: Option<String>,
+    #[serde(
+        rename = "core:recorder",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub recorder: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Global {
+    pub fn new(datatype: Datatype) -> Global {
+        Global {
+            datatype,
+            version: VERSION.to_string(),
+            sample_rate: None,
+            description: None,
+            author: None,
+            hw: None,
+            recorder: Some("FutureSDR".to_string()),
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Capture {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(
+        rename = "core:frequency",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub frequency: Option<f64>,
+    #[serde(
+        rename = "core:datetime",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub datetime: Option<String>,
+    #[serde(
+        rename = "core:global_index",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub global_index: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Capture {
+    pub fn new(sample_start: u64) -> Capture {
+        Capture {
+            sample_start,
+            frequency: None,
+            datetime: None,
+            global_index: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    #[serde(
+        rename = "core:sample_count",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub sample_count: Option<u64>,
+    #[serde(
+        rename = "core:freq_lower_edge",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_lower_edge: Option<f64>,
+    #[serde(
+        rename = "core:freq_upper_edge",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_upper_edge: Option<f64>,
+    #[serde(
+        rename = "core:label",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub label: Option<String>,
+    #[serde(
+        rename = "core:comment",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub comment: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Annotation {
+    pub fn new(sample_start: u64) -> Annotation {
+        Annotation {
+            sample_start,
+            sample_count: None,
+            freq_lower_edge: None,
+            freq_upper_edge: None,
+            label: None,
+            comment: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+mod datatype {
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use crate::blocks::sigmf::Datatype;
+
+    pub fn serialize<S: Serializer>(d: &Datatype, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_str(d)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Datatype, D::Error> {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,8 @@
+mod datatype;
+pub use datatype::{Datatype, SampleFormat, SigMfFormat};
+mod description;
+pub use description::{paths, Annotation, Capture, Description, Global, VERSION};
+mod sink;
+pub use sink::{SigMfSink, SigMfSinkBuilder};
+mod source;
+pub use source::{SigMfSource, SigMfSourceBuilder};
@@ -0,0 +1,241 @@
+use anyhow::{Context, Result};
+use async_fs::File;
+use futures::io::AsyncWriteExt;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::marker::PhantomData;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::blocks::sigmf::paths;
+use crate::blocks::sigmf::Annotation;
+use crate::blocks::sigmf::Capture;
+use crate::blocks::sigmf::Description;
+use crate::blocks::sigmf::SigMfFormat;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Records samples to a .sigmf-data file and writes the .sigmf-meta file on
+// shutdown. The "captures" and "annotations" message inputs take SigMF JSON
+// objects; if they do not have a core:sample_start, the current sample is used.
+// A Double on "captures" starts a new capture at that center frequency and a
+// plain String on "annotations" is recorded as label.
+pub struct SigMfSink<T: SigMfFormat> {
+    description: Description,
+    meta_path: PathBuf,
+    data_path: PathBuf,
+    file: Option<File>,
+    n_written: u64,
+    _type: PhantomData<T>,
+}
+
+impl<T: SigMfFormat> SigMfSink<T> {
+    pub fn new<P: AsRef<Path>>(path: P, description: Description) -> Block {
+        let (meta_path, data_path) = paths(path);
+        let mut description = description;
+        description.global.datatype = T::DATATYPE;
+
+        Block::new_async(
+            BlockMetaBuilder::new("SigMfSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", mem::size_of::<T>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_sync_input("captures", Self::capture)
+                .add_sync_input("annotations", Self::annotation)
+                .build(),
+            SigMfSink::<T> {
+                description,
+                meta_path,
+                data_path,
+                file: None,
+                n_written: 0,
+                _type: PhantomData,
+            },
+        )
+    }
+
+    pub fn description(&self) -> &Description {
+        &self.description
+    }
+
+    pub fn n_written(&self) -> u64 {
+        self.n_written
+    }
+
+    fn parse<M: DeserializeOwned>(&self, s: &str) -> Result<M> {
+        let mut v: Value = serde_json::from_str(s)?;
+        if let Value::Object(o) = &mut v {
+            o.entry("core:sample_start")
+                .or_insert_with(|| self.n_written.into());
+        }
+        Ok(serde_json::from_value(v)?)
+    }
+
+    // captures have to start at different samples, a new one replaces the old
+    fn add_capture(&mut self, c: Capture) {
+        let captures = &mut self.description.captures;
+        match captures
+            .iter_mut()
+            .find(|o| o.sample_start == c.sample_start)
+        {
+            Some(o) => *o = c,
+            None => captures.push(c),
+        }
+    }
+
+    fn capture(
+        &mut self,
+        _mio: &mut MessageIo<SigMfSink<T>>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::String(s) => match self.parse::<Capture>(&s) {
+                Ok(c) => self.add_capture(c),
+                Err(e) => warn!("sigmf sink: invalid capture {:?} ({})", s, e),
+            },
+            Pmt::Double(f) => {
+                let mut c = Capture::new(self.n_written);
+                c.frequency = Some(f);
+                self.add_capture(c);
+            }
+            _ => warn!("sigmf sink: unsupported capture {:?}", p),
+        }
+        Ok(Pmt::Null)
+    }
+
+    fn annotation(
+        &mut self,
+        _mio: &mut MessageIo<SigMfSink<T>>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::String(s) => match self.parse::<Annotation>(&s) {
+                Ok(a) => self.description.annotations.push(a),
+                Err(_) => {
+                    let mut a = Annotation::new(self.n_written);
+                    a.label = Some(s);
+                    self.description.annotations.push(a);
+                }
+            },
+            _ => warn!("sigmf sink: unsupported annotation {:?}", p),
+        }
+        Ok(Pmt::Null)
+    }
+}
+
+#[async_trait]
+impl<T: SigMfFormat> AsyncKernel for SigMfSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let n = i.len() / mem::size_of::<T>();
+
+        self.file
+            .as_mut()
+            .unwrap()
+            .write_all(&i[..n * mem::size_of::<T>()])
+            .await
+            .with_context(|| format!("error writing SigMF data {:?}", self.data_path))?;
+
+        self.n_written += n as u64;
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let file = File::create(&self.data_path)
+            .await
+            .with_context(|| format!("cannot create SigMF data {:?}", self.data_path))?;
+        self.file = Some(file);
+        self.description.to_file(&self.meta_path)
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("sigmf sink: n_written {}", self.n_written);
+        self.file.as_mut().unwrap().flush().await?;
+        self.description.sort();
+        self.description.to_file(&self.meta_path)
+    }
+}
+
+pub struct SigMfSinkBuilder<T: SigMfFormat> {
+    path: PathBuf,
+    description: Description,
+    _type: PhantomData<T>,
+}
+
+impl<T: SigMfFormat> SigMfSinkBuilder<T> {
+    pub fn new<P: AsRef<Path>>(path: P) -> SigMfSinkBuilder<T> {
+        let mut description = Description::new(T::DATATYPE);
+        description.captures.push(Capture::new(0));
+        SigMfSinkBuilder {
+            path: path.as_ref().to_path_buf(),
+            description,
+            _type: PhantomData,
+        }
+    }
+
+    pub fn sample_rate(mut self, sample_rate: f64) -> SigMfSinkBuilder<T> {
+        self.description.global.sample_rate = Some(sample_rate);
+        self
+    }
+
+    pub fn frequency(mut self, frequency: f64) -> SigMfSinkBuilder<T> {
+        self.description.captures[0].frequency = Some(frequency);
+        self
+    }
+
+    pub fn datetime(mut self, datetime: &str) -> SigMfSinkBuilder<T> {
+        self.description.captures[0].datetime = Some(datetime.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> SigMfSinkBuilder<T> {
+        self.description.global.description = Some(description.to_string());
+        self
+    }
+
+    pub fn author(mut self, author: &str) -> SigMfSinkBuilder<T> {
+        self.description.global.author = Some(author.to_string());
+        self
+    }
+
+    pub fn hw(mut self, hw: &str) -> SigMfSinkBuilder<T> {
+        self.description.global.hw = Some(hw.to_string());
+        self
+    }
+
+    pub fn build(self) -> Block {
+        SigMfSink::<T>::new(self.path, self.description)
+    }
+}
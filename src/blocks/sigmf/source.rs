@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use futures::AsyncReadExt;
+use std::marker::PhantomData;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use crate::blocks::sigmf::paths;
+use crate::blocks::sigmf::Description;
+use crate::blocks::sigmf::SigMfFormat;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Streams the samples of a recording. Captures and annotations are posted as
+// SigMF JSON objects on the "captures" and "annotations" message outputs, right
+// before the sample they start at is produced.
+pub struct SigMfSource<T: SigMfFormat> {
+    description: Description,
+    data_path: PathBuf,
+    file: Option<async_fs::File>,
+    n_items: u64,
+    n_produced: u64,
+    next_capture: usize,
+    next_annotation: usize,
+    _type: PhantomData<T>,
+}
+
+impl<T: SigMfFormat> SigMfSource<T> {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Block> {
+        let (meta_path, data_path) = paths(path);
+        let mut description = Description::from_file(&meta_path)?;
+        description.sort();
+
+        if description.global.datatype != T::DATATYPE {
+            bail!(
+                "SigMF datatype {} of {:?} does not match the output type ({})",
+                description.global.datatype,
+                meta_path,
+                T::DATATYPE
+            );
+        }
+
+        Ok(Block::new_async(
+            BlockMetaBuilder::new("SigMfSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", mem::size_of::<T>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_output("captures")
+                .add_output("annotations")
+                .build(),
+            SigMfSource::<T> {
+                description,
+                data_path,
+                file: None,
+                n_items: 0,
+                n_produced: 0,
+                next_capture: 0,
+                next_annotation: 0,
+                _type: PhantomData,
+            },
+        ))
+    }
+
+    pub fn description(&self) -> &Description {
+        &self.description
+    }
+
+    pub fn n_produced(&self) -> u64 {
+        self.n_produced
+    }
+
+    // post everything that starts at or before the current sample and return
+    // the start of the next capture or annotation
+    async fn post_metadata(&mut self, mio: &mut MessageIo<Self>) -> Result<u64> {
+        let captures = &self.description.captures;
+        while self.next_capture < captures.len()
+            && captures[self.next_capture].sample_start <= self.n_produced
+        {
+            let c = serde_json::to_string(&captures[self.next_capture])?;
+            mio.post(0, Pmt::String(c)).await;
+            self.next_capture += 1;
+        }
+
+        let annotations = &self.description.annotations;
+        while self.next_annotation < annotations.len()
+            && annotations[self.next_annotation].sample_start <= self.n_produced
+        {
+            let a = serde_json::to_string(&annotations[self.next_annotation])?;
+            mio.post(1, Pmt::String(a)).await;
+            self.next_annotation += 1;
+        }
+
+        let capture = captures.get(self.next_capture).map(|c| c.sample_start);
+        let annotation = annotations
+            .get(self.next_annotation)
+            .map(|a| a.sample_start);
+        Ok(capture
+            .into_iter()
+            .chain(annotation)
+            .min()
+            .unwrap_or(u64::MAX))
+    }
+}
+
+#[async_trait]
+impl<T: SigMfFormat> AsyncKernel for SigMfSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let next = self.post_metadata(mio).await?;
+
+        let out = sio.output(0).slice::<u8>();
+        let item_size = mem::size_of::<T>();
+        let n = std::cmp::min(
+            (out.len() / item_size) as u64,
+            std::cmp::min(self.n_items, next) - self.n_produced,
+        ) as usize;
+
+        self.file
+            .as_mut()
+            .unwrap()
+            .read_exact(&mut out[..n * item_size])
+            .await
+            .with_context(|| format!("error reading SigMF data {:?}", self.data_path))?;
+        self.n_produced += n as u64;
+        sio.output(0).produce(n);
+
+        if self.n_produced == self.n_items {
+            // metadata that starts at the very end of the recording
+            self.post_metadata(mio).await?;
+            io.finished = true;
+        } else if self.n_produced == next {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let file = async_fs::File::open(&self.data_path)
+            .await
+            .with_context(|| format!("cannot open SigMF data {:?}", self.data_path))?;
+        let size = file.metadata().await?.len();
+        let item_size = mem::size_of::<T>() as u64;
+        if size % item_size != 0 {
+            warn!(
+                "SigMF data {:?} is not a multiple of the item size, ignoring {} trailing bytes",
+                self.data_path,
+                size % item_size
+            );
+        }
+        self.n_items = size / item_size;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("sigmf source: n_produced {}", self.n_produced);
+        Ok(())
+    }
+}
+
+pub struct SigMfSourceBuilder<T: SigMfFormat> {
+    path: PathBuf,
+    _type: PhantomData<T>,
+}
+
+impl<T: SigMfFormat> SigMfSourceBuilder<T> {
+    pub fn new<P: AsRef<Path>>(path: P) -> SigMfSourceBuilder<T> {
+        SigMfSourceBuilder {
+            path: path.as_ref().to_path_buf(),
+            _type: PhantomData,
+        }
+    }
+
+    pub fn build(self) -> Result<Block> {
+        SigMfSource::<T>::new(self.path)
+    }
+}
@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use blocking::Unblock;
+use std::io::Stdin;
+
+use crate::blocks::ItemReader;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Reads items from stdin, e.g., to feed a flowgraph from a shell pipeline. The
+// block finishes at the end of the input. Trailing bytes that do not form a
+// complete item are dropped.
+pub struct StdinSource {
+    item_size: usize,
+    stdin: Unblock<Stdin>,
+    reader: ItemReader,
+    n_produced: usize,
+}
+
+impl StdinSource {
+    pub fn new(item_size: usize) -> Block {
+        StdinSourceBuilder::new(item_size).build()
+    }
+
+    pub fn n_produced(&self) -> usize {
+        self.n_produced
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for StdinSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<u8>();
+        if out.len() < self.item_size {
+            return Ok(());
+        }
+
+        let items = match self
+            .reader
+            .read(&mut self.stdin, out)
+            .await
+            .context("stdin source: cannot read stdin")?
+        {
+            Some(items) => items,
+            None => {
+                let p = self.reader.reset();
+                if p > 0 {
+                    warn!("stdin source: dropping {} bytes of a partial item", p);
+                }
+                io.finished = true;
+                return Ok(());
+            }
+        };
+        sio.output(0).produce(items);
+        self.n_produced += items;
+
+        io.call_again = true;
+        Ok(())
+    }
+}
+
+pub struct StdinSourceBuilder {
+    item_size: usize,
+}
+
+impl StdinSourceBuilder {
+    pub fn new(item_size: usize) -> StdinSourceBuilder {
+        StdinSourceBuilder { item_size }
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.item_size > 0,
+            "stdin source: item size must be positive"
+        );
+        Block::new_async(
+            BlockMetaBuilder::new("StdinSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            StdinSource {
+                item_size: self.item_size,
+                stdin: Unblock::new(std::io::stdin()),
+                reader: ItemReader::new(self.item_size),
+                n_produced: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use anyhow::{Context, Result};
+use blocking::Unblock;
+use futures::AsyncWriteExt;
+use std::io::{ErrorKind, Stdout};
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Writes items to stdout, e.g., to pipe the output of a flowgraph into another
+// program. Only complete items are written. If the reader closes the pipe (like
+// `head` does), the block finishes.
+//
+// The runtime logger prints to stdout as well, which mixes log lines into the
+// items. Turn it off when piping, e.g., with `FUTURESDR_LOG_LEVEL=off`.
+pub struct StdoutSink {
+    item_size: usize,
+    stdout: Unblock<Stdout>,
+    n_written: usize,
+}
+
+impl StdoutSink {
+    pub fn new(item_size: usize) -> Block {
+        StdoutSinkBuilder::new(item_size).build()
+    }
+
+    pub fn n_written(&self) -> usize {
+        self.n_written
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for StdoutSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let items = i.len() / self.item_size;
+
+        if items > 0 {
+            let r = match self.stdout.write_all(&i[..items * self.item_size]).await {
+                Ok(()) => self.stdout.flush().await,
+                Err(e) => Err(e),
+            };
+            match r {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                    debug!("stdout sink: pipe closed");
+                    io.finished = true;
+                    return Ok(());
+                }
+                Err(e) => return Err(e).context("stdout sink: cannot write stdout"),
+            }
+            self.n_written += items;
+        }
+
+        sio.input(0).consume(items);
+
+        if sio.input(0).finished() && items * self.item_size == i.len() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let _ = self.stdout.flush().await;
+        Ok(())
+    }
+}
+
+pub struct StdoutSinkBuilder {
+    item_size: usize,
+}
+
+impl StdoutSinkBuilder {
+    pub fn new(item_size: usize) -> StdoutSinkBuilder {
+        StdoutSinkBuilder { item_size }
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.item_size > 0,
+            "stdout sink: item size must be positive"
+        );
+        Block::new_async(
+            BlockMetaBuilder::new("StdoutSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            StdoutSink {
+                item_size: self.item_size,
+                stdout: Unblock::new(std::io::stdout()),
+                n_written: 0,
+            },
+        )
+    }
+}
@@ -1,7 +1,14 @@
 use anyhow::{bail, Context, Result};
+use async_io::{Async, Timer};
 use async_net::{TcpListener, TcpStream};
-use futures::AsyncWriteExt;
+use futures::future::select_all;
+use futures::FutureExt;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Write};
+use std::net::Shutdown;
+use std::sync::Arc;
 
+use crate::blocks::tcp_source::RECONNECT_INTERVAL;
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -12,24 +19,198 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+// items queued per client by default
+const DEFAULT_QUEUE_SIZE: usize = 64 * 1024;
+
+// How items are sent to a client. With `Block`, the flowgraph waits until the
+// client accepted the items. With `Drop`, items are queued per client and
+// dropped if the queue of the client is full, so that slow clients do not stall
+// the flowgraph or the other clients. Only complete items are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpClientPolicy {
+    Block,
+    Drop,
+}
+
+struct Client {
+    socket: Arc<Async<std::net::TcpStream>>,
+    // bytes that are not written yet
+    queue: VecDeque<u8>,
+}
+
+impl Client {
+    fn new(socket: TcpStream) -> Client {
+        Client {
+            socket: socket.into(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    // writes as much of the queue as possible without blocking, returns false if
+    // the connection is closed
+    fn flush(&mut self) -> bool {
+        while !self.queue.is_empty() {
+            let (b, _) = self.queue.as_slices();
+            match self.socket.get_ref().write(b) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    self.queue.drain(..n);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return true,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    debug!("tcp sink: connection closed ({})", e);
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // waits until the queue is written, returns false if the connection is closed
+    async fn drain(&mut self) -> bool {
+        loop {
+            if !self.flush() {
+                return false;
+            }
+            if self.queue.is_empty() {
+                return true;
+            }
+            if self.socket.writable().await.is_err() {
+                return false;
+            }
+        }
+    }
+}
+
+// Writes items to TCP connections. As server, it binds the local address, waits
+// for a first client, and sends the stream to all connected clients (clients
+// connecting later receive the stream from that point on). As client, it
+// connects to the remote address. Only complete items are written.
+//
+// Without `reconnect`, the block fails once all connections are closed. With
+// `reconnect`, it waits for the next client (server) or connects again (client).
 pub struct TcpSink {
-    port: u32,
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    policy: TcpClientPolicy,
+    queue_size: usize,
     listener: Option<TcpListener>,
-    socket: Option<TcpStream>,
+    clients: Vec<Client>,
+    n_connections: usize,
+    n_dropped: usize,
 }
 
 impl TcpSink {
+    // server on 127.0.0.1:port
     pub fn new(port: u32) -> Block {
-        Block::new_async(
-            BlockMetaBuilder::new("TcpSink").build(),
-            StreamIoBuilder::new().add_input("in", 1).build(),
-            MessageIoBuilder::new().build(),
-            TcpSink {
-                port,
-                listener: None,
-                socket: None,
-            },
-        )
+        TcpSinkBuilder::new(&format!("127.0.0.1:{}", port)).build()
+    }
+
+    pub fn n_connections(&self) -> usize {
+        self.n_connections
+    }
+
+    // items dropped for clients that did not keep up with `TcpClientPolicy::Drop`
+    pub fn n_dropped(&self) -> usize {
+        self.n_dropped
+    }
+
+    // wait for the first connection
+    async fn open(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.client {
+            match TcpStream::connect(&self.address).await {
+                Ok(socket) => {
+                    debug!("tcp sink: connected to {}", self.address);
+                    self.clients.push(Client::new(socket));
+                    self.n_connections += 1;
+                }
+                Err(e) if self.reconnect => {
+                    debug!("tcp sink: cannot connect to {}: {}", self.address, e);
+                    io.block_on(async {
+                        Timer::after(RECONNECT_INTERVAL).await;
+                    });
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("tcp sink: cannot connect to {}", self.address))
+                }
+            }
+        } else {
+            let (socket, peer) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            debug!("tcp sink: accepted connection from {}", peer);
+            self.clients.push(Client::new(socket));
+            self.n_connections += 1;
+        }
+        Ok(())
+    }
+
+    // add clients that connected in the meantime
+    fn accept_pending(&mut self) -> Result<()> {
+        if let Some(listener) = self.listener.as_ref() {
+            while let Some(r) = listener.accept().now_or_never() {
+                let (socket, peer) = r?;
+                debug!("tcp sink: accepted connection from {}", peer);
+                self.clients.push(Client::new(socket));
+                self.n_connections += 1;
+            }
+        }
+        Ok(())
+    }
+
+    // hands the items to the clients, returns the indices of closed connections
+    async fn send(&mut self, bytes: &[u8]) -> Vec<usize> {
+        let item_size = self.item_size;
+        let max_queued = self.queue_size * item_size;
+        let mut closed = Vec::new();
+
+        for (n, c) in self.clients.iter_mut().enumerate() {
+            let open = match self.policy {
+                TcpClientPolicy::Block => {
+                    c.queue.extend(bytes);
+                    c.drain().await
+                }
+                TcpClientPolicy::Drop => {
+                    let free = max_queued.saturating_sub(c.queue.len()) / item_size;
+                    let items = std::cmp::min(free, bytes.len() / item_size);
+                    c.queue.extend(&bytes[..items * item_size]);
+                    self.n_dropped += bytes.len() / item_size - items;
+                    c.flush()
+                }
+            };
+            if !open {
+                closed.push(n);
+            }
+        }
+        closed
+    }
+
+    // wait until a client with queued items can take more, or for new input
+    fn wait_writable(&self, io: &mut WorkIo) {
+        let writable: Vec<_> = self
+            .clients
+            .iter()
+            .filter(|c| !c.queue.is_empty())
+            .map(|c| {
+                let socket = c.socket.clone();
+                async move {
+                    let _ = socket.writable().await;
+                }
+                .boxed()
+            })
+            .collect();
+        if !writable.is_empty() {
+            io.block_on(async move {
+                select_all(writable).await;
+            });
+        }
     }
 }
 
@@ -42,36 +223,58 @@ impl AsyncKernel for TcpSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp sink accepted connection");
+        if self.clients.is_empty() {
+            self.open(io).await?;
+            if self.clients.is_empty() {
+                return Ok(());
+            }
         }
+        self.accept_pending()?;
 
         let i = sio.input(0).slice::<u8>();
+        let items = i.len() / self.item_size;
+        let bytes = &i[..items * self.item_size];
+
+        let mut closed = if bytes.is_empty() {
+            Vec::new()
+        } else {
+            debug!("tcp sink: sending bytes {}", bytes.len());
+            self.send(bytes).await
+        };
+
+        let finished = sio.input(0).finished() && items * self.item_size == i.len();
+        sio.input(0).consume(items);
 
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .write_all(i)
-            .await
-        {
-            Ok(()) => {}
-            Err(_) => bail!("tcp sink socket error"),
+        // write what is queued for the clients
+        for (n, c) in self.clients.iter_mut().enumerate() {
+            if closed.contains(&n) {
+                continue;
+            }
+            let open = if finished { c.drain().await } else { c.flush() };
+            if !open {
+                closed.push(n);
+            }
+        }
+        closed.sort_unstable();
+        for n in closed.into_iter().rev() {
+            self.clients.remove(n);
         }
 
-        if sio.input(0).finished() {
-            io.finished = true;
+        if self.clients.is_empty() && items > 0 {
+            if !self.reconnect {
+                bail!("tcp sink: connection closed");
+            }
+            // the items are lost, since nobody was connected to receive them
+            warn!("tcp sink: all connections closed, waiting for a new one");
         }
 
-        debug!("tcp sink wrote bytes {}", i.len());
-        sio.input(0).consume(i.len());
+        if finished {
+            io.finished = true;
+        } else if self.clients.is_empty() {
+            io.call_again = true;
+        } else {
+            self.wait_writable(io);
+        }
 
         Ok(())
     }
@@ -82,21 +285,103 @@ impl AsyncKernel for TcpSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?);
+        if !self.client {
+            self.listener = Some(
+                TcpListener::bind(&self.address)
+                    .await
+                    .with_context(|| format!("tcp sink: cannot bind {}", self.address))?,
+            );
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for c in self.clients.iter() {
+            let _ = c.socket.get_ref().shutdown(Shutdown::Write);
+        }
+        debug!(
+            "tcp sink: n_connections {}, n_dropped {}",
+            self.n_connections, self.n_dropped
+        );
         Ok(())
     }
 }
 
 pub struct TcpSinkBuilder {
-    port: u32,
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    policy: TcpClientPolicy,
+    queue_size: usize,
 }
 
 impl TcpSinkBuilder {
-    pub fn new(port: u32) -> TcpSinkBuilder {
-        TcpSinkBuilder { port }
+    // server on a local address, e.g., 0.0.0.0:1234
+    pub fn new(address: &str) -> TcpSinkBuilder {
+        TcpSinkBuilder {
+            address: address.to_string(),
+            client: false,
+            reconnect: false,
+            item_size: 1,
+            policy: TcpClientPolicy::Block,
+            queue_size: DEFAULT_QUEUE_SIZE,
+        }
+    }
+
+    // connect to the address as client, e.g., 192.168.1.2:1234
+    pub fn client(mut self, client: bool) -> TcpSinkBuilder {
+        self.client = client;
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> TcpSinkBuilder {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> TcpSinkBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn policy(mut self, policy: TcpClientPolicy) -> TcpSinkBuilder {
+        self.policy = policy;
+        self
+    }
+
+    // in items, per client with `TcpClientPolicy::Drop`
+    pub fn queue_size(mut self, queue_size: usize) -> TcpSinkBuilder {
+        self.queue_size = queue_size;
+        self
     }
 
     pub fn build(self) -> Block {
-        TcpSink::new(self.port)
+        assert!(self.item_size > 0, "tcp sink: item size must be positive");
+        assert!(self.queue_size > 0, "tcp sink: queue size must be positive");
+        Block::new_async(
+            BlockMetaBuilder::new("TcpSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            TcpSink {
+                address: self.address,
+                client: self.client,
+                reconnect: self.reconnect,
+                item_size: self.item_size,
+                policy: self.policy,
+                queue_size: self.queue_size,
+                listener: None,
+                clients: Vec::new(),
+                n_connections: 0,
+                n_dropped: 0,
+            },
+        )
     }
 }
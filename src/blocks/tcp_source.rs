@@ -1,7 +1,9 @@
 use anyhow::{Context, Result};
+use async_io::Timer;
 use async_net::{TcpListener, TcpStream};
-use futures::AsyncReadExt;
+use std::time::Duration;
 
+use crate::blocks::ItemReader;
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -12,24 +14,66 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+// wait before retrying to connect to a remote host
+pub(crate) const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
+// Reads items from a TCP connection. As server, it binds the local address and
+// accepts one connection at a time; as client, it connects to the remote address.
+// Without `reconnect`, the block finishes when the connection is closed. With
+// `reconnect`, it waits for the next connection (server) or connects again
+// (client). Partial items at the end of a connection are dropped.
 pub struct TcpSource {
-    port: u32,
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
     listener: Option<TcpListener>,
     socket: Option<TcpStream>,
+    reader: ItemReader,
+    n_connections: usize,
 }
 
 impl TcpSource {
+    // server on 127.0.0.1:port
     pub fn new(port: u32) -> Block {
-        Block::new_async(
-            BlockMetaBuilder::new("TcpSource").build(),
-            StreamIoBuilder::new().add_output("out", 1).build(),
-            MessageIoBuilder::new().build(),
-            TcpSource {
-                port,
-                listener: None,
-                socket: None,
-            },
-        )
+        TcpSourceBuilder::new(&format!("127.0.0.1:{}", port)).build()
+    }
+
+    pub fn n_connections(&self) -> usize {
+        self.n_connections
+    }
+
+    async fn open(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.client {
+            match TcpStream::connect(&self.address).await {
+                Ok(socket) => {
+                    debug!("tcp source: connected to {}", self.address);
+                    self.socket = Some(socket);
+                }
+                Err(e) if self.reconnect => {
+                    debug!("tcp source: cannot connect to {}: {}", self.address, e);
+                    io.block_on(async {
+                        Timer::after(RECONNECT_INTERVAL).await;
+                    });
+                    return Ok(());
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("tcp source: cannot connect to {}", self.address))
+                }
+            }
+        } else {
+            let (socket, peer) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            debug!("tcp source: accepted connection from {}", peer);
+            self.socket = Some(socket);
+        }
+        self.n_connections += 1;
+        Ok(())
     }
 }
 
@@ -43,38 +87,47 @@ impl AsyncKernel for TcpSource {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp source accepted connection");
+            self.open(io).await?;
+            if self.socket.is_none() {
+                return Ok(());
+            }
         }
 
         let out = sio.output(0).slice::<u8>();
-        if out.is_empty() {
+        if out.len() < self.item_size {
             return Ok(());
         }
 
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .read_exact(out)
-            .await
-        {
-            Ok(_) => {
-                debug!("tcp source read bytes {}", out.len());
-                sio.output(0).produce(out.len());
+        let socket = self.socket.as_mut().context("no socket")?;
+        let items = match self.reader.read(socket, out).await {
+            Ok(items) => items,
+            Err(e) if self.reconnect => {
+                warn!("tcp source: read error {}", e);
+                None
             }
-            Err(_) => {
-                debug!("tcp source socket closed");
-                io.finished = true;
+            Err(e) => return Err(e).context("tcp source: read error"),
+        };
+
+        let items = match items {
+            Some(items) => items,
+            None => {
+                debug!("tcp source: socket closed");
+                let p = self.reader.reset();
+                if p > 0 {
+                    warn!("tcp source: dropping {} bytes of a partial item", p);
+                }
+                self.socket = None;
+                if self.reconnect {
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
+                return Ok(());
             }
-        }
+        };
+        sio.output(0).produce(items);
 
+        io.call_again = true;
         Ok(())
     }
 
@@ -84,21 +137,69 @@ impl AsyncKernel for TcpSource {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?);
+        if !self.client {
+            self.listener = Some(
+                TcpListener::bind(&self.address)
+                    .await
+                    .with_context(|| format!("tcp source: cannot bind {}", self.address))?,
+            );
+        }
         Ok(())
     }
 }
 
 pub struct TcpSourceBuilder {
-    port: u32,
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
 }
 
 impl TcpSourceBuilder {
-    pub fn new(port: u32) -> TcpSourceBuilder {
-        TcpSourceBuilder { port }
+    // server on a local address, e.g., 0.0.0.0:1234
+    pub fn new(address: &str) -> TcpSourceBuilder {
+        TcpSourceBuilder {
+            address: address.to_string(),
+            client: false,
+            reconnect: false,
+            item_size: 1,
+        }
+    }
+
+    // connect to the address as client, e.g., 192.168.1.2:1234
+    pub fn client(mut self, client: bool) -> TcpSourceBuilder {
+        self.client = client;
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> TcpSourceBuilder {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> TcpSourceBuilder {
+        self.item_size = item_size;
+        self
     }
 
     pub fn build(self) -> Block {
-        TcpSource::new(self.port)
+        assert!(self.item_size > 0, "tcp source: item size must be positive");
+        Block::new_async(
+            BlockMetaBuilder::new("TcpSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            TcpSource {
+                address: self.address,
+                client: self.client,
+                reconnect: self.reconnect,
+                item_size: self.item_size,
+                listener: None,
+                socket: None,
+                reader: ItemReader::new(self.item_size),
+                n_connections: 0,
+            },
+        )
     }
 }
@@ -74,7 +74,7 @@ impl AsyncKernel for Throttle {
             sio.output(0).produce(m);
         }
 
-        if sio.input(0).finished() && i.len() == m * self.item_size {
+        if sio.input(0).finished() && i.len() == m {
             io.finished = true;
         }
 
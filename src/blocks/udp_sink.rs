@@ -0,0 +1,240 @@
+use anyhow::{Context, Result};
+use async_net::{SocketAddr, UdpSocket};
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::blocks::udp_source::{DEFAULT_PAYLOAD_SIZE, SEQUENCE_LEN};
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Sends datagrams to a unicast or multicast address. In stream mode, items from
+// the "in" stream input are packed into datagrams of up to `payload_size` bytes.
+// In PDU mode, each Pmt::Blob on the "in" message input is sent as one datagram.
+// With `eof`, an empty datagram is sent on shutdown.
+pub struct UdpSink {
+    destination: String,
+    addr: Option<SocketAddr>,
+    item_size: usize,
+    payload_size: usize,
+    sequence_numbers: bool,
+    eof: bool,
+    multicast_ttl: Option<u32>,
+    socket: Option<UdpSocket>,
+    buffer: Vec<u8>,
+    sequence: u32,
+    n_sent: usize,
+}
+
+impl UdpSink {
+    pub fn new(destination: &str) -> Block {
+        UdpSinkBuilder::new(destination).build()
+    }
+
+    pub fn n_sent(&self) -> usize {
+        self.n_sent
+    }
+
+    async fn send(&mut self, payload: &[u8]) -> Result<()> {
+        self.buffer.clear();
+        if self.sequence_numbers {
+            self.buffer.extend_from_slice(&self.sequence.to_be_bytes());
+            self.sequence = self.sequence.wrapping_add(1);
+        }
+        self.buffer.extend_from_slice(payload);
+
+        self.socket
+            .as_ref()
+            .context("no socket")?
+            .send_to(&self.buffer, self.addr.context("no address")?)
+            .await
+            .with_context(|| format!("udp sink: cannot send to {}", self.destination))?;
+        self.n_sent += 1;
+        Ok(())
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        _mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(b) => self.send(&b).await?,
+                p => warn!("udp sink: expected Pmt::Blob, got {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for UdpSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // PDU mode
+        if sio.inputs().is_empty() {
+            return Ok(());
+        }
+
+        let items_per_datagram = std::cmp::max(self.payload_size / self.item_size, 1);
+        let i = sio.input(0).slice::<u8>();
+        let n_items = i.len() / self.item_size;
+        let finished = sio.input(0).finished();
+
+        let mut n = 0;
+        while n_items - n >= items_per_datagram || (finished && n < n_items) {
+            let k = std::cmp::min(items_per_datagram, n_items - n);
+            let payload = &i[n * self.item_size..(n + k) * self.item_size];
+            self.send(payload).await?;
+            n += k;
+        }
+        sio.input(0).consume(n);
+
+        if finished && n == n_items {
+            io.finished = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let addr = *async_net::resolve(&self.destination)
+            .await
+            .with_context(|| format!("udp sink: cannot resolve {}", self.destination))?
+            .first()
+            .with_context(|| format!("udp sink: cannot resolve {}", self.destination))?;
+
+        let socket = if addr.is_ipv4() {
+            UdpSocket::bind("0.0.0.0:0").await?
+        } else {
+            UdpSocket::bind("[::]:0").await?
+        };
+        if let Some(ttl) = self.multicast_ttl {
+            socket.set_multicast_ttl_v4(ttl)?;
+        }
+
+        self.addr = Some(addr);
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.eof {
+            self.send(&[]).await?;
+        }
+        debug!("udp sink: n_sent {}", self.n_sent);
+        Ok(())
+    }
+}
+
+pub struct UdpSinkBuilder {
+    destination: String,
+    item_size: usize,
+    payload_size: usize,
+    pdu: bool,
+    sequence_numbers: bool,
+    eof: bool,
+    multicast_ttl: Option<u32>,
+}
+
+impl UdpSinkBuilder {
+    // remote address, e.g., 127.0.0.1:1234 or 239.0.0.1:1234
+    pub fn new(destination: &str) -> UdpSinkBuilder {
+        UdpSinkBuilder {
+            destination: destination.to_string(),
+            item_size: 1,
+            payload_size: DEFAULT_PAYLOAD_SIZE,
+            pdu: false,
+            sequence_numbers: false,
+            eof: false,
+            multicast_ttl: None,
+        }
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> UdpSinkBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    // bytes per datagram in stream mode, rounded down to complete items
+    pub fn payload_size(mut self, payload_size: usize) -> UdpSinkBuilder {
+        self.payload_size = payload_size;
+        self
+    }
+
+    pub fn pdu(mut self, pdu: bool) -> UdpSinkBuilder {
+        self.pdu = pdu;
+        self
+    }
+
+    pub fn sequence_numbers(mut self, sequence_numbers: bool) -> UdpSinkBuilder {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
+
+    pub fn eof(mut self, eof: bool) -> UdpSinkBuilder {
+        self.eof = eof;
+        self
+    }
+
+    pub fn multicast_ttl(mut self, ttl: u32) -> UdpSinkBuilder {
+        self.multicast_ttl = Some(ttl);
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(self.item_size > 0, "udp sink: item size must be positive");
+
+        let mut sio = StreamIoBuilder::new();
+        let mut mio = MessageIoBuilder::new();
+        if self.pdu {
+            mio = mio.add_async_input("in", UdpSink::handler);
+        } else {
+            sio = sio.add_input("in", self.item_size);
+        }
+
+        Block::new_async(
+            BlockMetaBuilder::new("UdpSink").build(),
+            sio.build(),
+            mio.build(),
+            UdpSink {
+                destination: self.destination,
+                addr: None,
+                item_size: self.item_size,
+                payload_size: self.payload_size,
+                sequence_numbers: self.sequence_numbers,
+                eof: self.eof,
+                multicast_ttl: self.multicast_ttl,
+                socket: None,
+                buffer: Vec::with_capacity(SEQUENCE_LEN + self.payload_size),
+                sequence: 0,
+                n_sent: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,285 @@
+use anyhow::{Context, Result};
+use async_net::{Ipv4Addr, UdpSocket};
+use futures::FutureExt;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// optional sequence number (u32, big endian) in front of the payload
+pub(crate) const SEQUENCE_LEN: usize = 4;
+// maximum UDP payload without fragmentation on Ethernet
+pub(crate) const DEFAULT_PAYLOAD_SIZE: usize = 1472;
+
+// Receives datagrams on a local address, optionally joining a multicast group.
+// In stream mode, the payload is written to the "out" stream output. In PDU mode,
+// each datagram is posted as Pmt::Blob on the "out" message output. With `eof`,
+// an empty datagram ends the stream. In stream mode, items do not span
+// datagrams; trailing bytes that do not form a complete item are dropped.
+pub struct UdpSource {
+    bind: String,
+    multicast: Option<(Ipv4Addr, Ipv4Addr)>,
+    item_size: usize,
+    pdu: bool,
+    sequence_numbers: bool,
+    eof: bool,
+    socket: Option<UdpSocket>,
+    buffer: Vec<u8>,
+    pending: Vec<u8>,
+    next_sequence: Option<u32>,
+    n_received: usize,
+    n_lost: usize,
+}
+
+impl UdpSource {
+    pub fn new(bind: &str) -> Block {
+        UdpSourceBuilder::new(bind).build()
+    }
+
+    pub fn n_received(&self) -> usize {
+        self.n_received
+    }
+
+    // datagrams missing according to the sequence numbers
+    pub fn n_lost(&self) -> usize {
+        self.n_lost
+    }
+
+    // strip and check the sequence number
+    fn payload(&mut self, n: usize) -> Option<(usize, usize)> {
+        if !self.sequence_numbers {
+            return Some((0, n));
+        }
+        if n < SEQUENCE_LEN {
+            warn!("udp source: datagram without sequence number");
+            return None;
+        }
+
+        let b = &self.buffer;
+        let seq = u32::from_be_bytes([b[0], b[1], b[2], b[3]]);
+        if let Some(expected) = self.next_sequence {
+            let gap = seq.wrapping_sub(expected);
+            if gap > u32::MAX / 2 {
+                warn!("udp source: out of order datagram {}", seq);
+                return None;
+            }
+            if gap > 0 {
+                warn!("udp source: lost {} datagrams", gap);
+                self.n_lost += gap as usize;
+            }
+        }
+        self.next_sequence = Some(seq.wrapping_add(1));
+        Some((SEQUENCE_LEN, n))
+    }
+
+    // poll for a datagram without blocking the block's event loop
+    fn try_recv(&mut self, io: &mut WorkIo) -> Result<Option<usize>> {
+        let socket = self.socket.as_ref().context("no socket")?;
+        match socket.recv_from(&mut self.buffer).now_or_never() {
+            Some(r) => Ok(Some(r.context("udp source: receive error")?.0)),
+            None => {
+                let socket = socket.clone();
+                io.block_on(async move {
+                    let _ = socket.peek_from(&mut [0u8; 1]).await;
+                });
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for UdpSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.pdu {
+            if let Some(n) = self.try_recv(io)? {
+                if let Some((start, end)) = self.payload(n) {
+                    self.n_received += 1;
+                    if self.eof && start == end {
+                        io.finished = true;
+                        return Ok(());
+                    }
+                    mio.post(0, Pmt::Blob(self.buffer[start..end].to_vec()))
+                        .await;
+                }
+                io.call_again = true;
+            }
+            return Ok(());
+        }
+
+        if self.pending.len() < self.item_size {
+            if let Some(n) = self.try_recv(io)? {
+                if let Some((start, end)) = self.payload(n) {
+                    self.n_received += 1;
+                    if self.eof && start == end {
+                        io.finished = true;
+                        return Ok(());
+                    }
+                    // a partial item at the end of the datagram is dropped, so that a
+                    // lost datagram does not misalign all following items
+                    let len = (end - start) / self.item_size * self.item_size;
+                    if len < end - start {
+                        warn!(
+                            "udp source: dropping partial item of {} bytes",
+                            end - start - len
+                        );
+                    }
+                    self.pending
+                        .extend_from_slice(&self.buffer[start..start + len]);
+                }
+            }
+        }
+
+        let out = sio.output(0).slice::<u8>();
+        let n = std::cmp::min(out.len(), self.pending.len()) / self.item_size;
+        if n > 0 {
+            let bytes = n * self.item_size;
+            out[..bytes].copy_from_slice(&self.pending[..bytes]);
+            self.pending.drain(..bytes);
+            sio.output(0).produce(n);
+        }
+
+        if self.pending.len() < self.item_size && io.block_on.is_none() {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = UdpSocket::bind(&self.bind)
+            .await
+            .with_context(|| format!("udp source: cannot bind {}", self.bind))?;
+        if let Some((group, interface)) = self.multicast {
+            socket
+                .join_multicast_v4(group, interface)
+                .with_context(|| format!("udp source: cannot join {}", group))?;
+        }
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!(
+            "udp source: n_received {}, n_lost {}",
+            self.n_received, self.n_lost
+        );
+        Ok(())
+    }
+}
+
+pub struct UdpSourceBuilder {
+    bind: String,
+    multicast: Option<(Ipv4Addr, Ipv4Addr)>,
+    item_size: usize,
+    payload_size: usize,
+    pdu: bool,
+    sequence_numbers: bool,
+    eof: bool,
+}
+
+impl UdpSourceBuilder {
+    // local address, e.g., 0.0.0.0:1234
+    pub fn new(bind: &str) -> UdpSourceBuilder {
+        UdpSourceBuilder {
+            bind: bind.to_string(),
+            multicast: None,
+            item_size: 1,
+            payload_size: DEFAULT_PAYLOAD_SIZE,
+            pdu: false,
+            sequence_numbers: false,
+            eof: false,
+        }
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> UdpSourceBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    // maximum payload of a datagram, larger ones are truncated
+    pub fn payload_size(mut self, payload_size: usize) -> UdpSourceBuilder {
+        self.payload_size = payload_size;
+        self
+    }
+
+    pub fn multicast(mut self, group: Ipv4Addr, interface: Ipv4Addr) -> UdpSourceBuilder {
+        self.multicast = Some((group, interface));
+        self
+    }
+
+    pub fn pdu(mut self, pdu: bool) -> UdpSourceBuilder {
+        self.pdu = pdu;
+        self
+    }
+
+    pub fn sequence_numbers(mut self, sequence_numbers: bool) -> UdpSourceBuilder {
+        self.sequence_numbers = sequence_numbers;
+        self
+    }
+
+    pub fn eof(mut self, eof: bool) -> UdpSourceBuilder {
+        self.eof = eof;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(self.item_size > 0, "udp source: item size must be positive");
+
+        let mut sio = StreamIoBuilder::new();
+        let mut mio = MessageIoBuilder::new();
+        if self.pdu {
+            mio = mio.add_output("out");
+        } else {
+            sio = sio.add_output("out", self.item_size);
+        }
+
+        let header = if self.sequence_numbers {
+            SEQUENCE_LEN
+        } else {
+            0
+        };
+
+        Block::new_async(
+            BlockMetaBuilder::new("UdpSource").build(),
+            sio.build(),
+            mio.build(),
+            UdpSource {
+                bind: self.bind,
+                multicast: self.multicast,
+                item_size: self.item_size,
+                pdu: self.pdu,
+                sequence_numbers: self.sequence_numbers,
+                eof: self.eof,
+                socket: None,
+                buffer: vec![0; header + self.payload_size],
+                pending: Vec::new(),
+                next_sequence: None,
+                n_received: 0,
+                n_lost: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::{bail, Context, Result};
+use async_io::Timer;
+use async_net::unix::{UnixListener, UnixStream};
+use futures::AsyncWriteExt;
+use futures::FutureExt;
+use std::path::PathBuf;
+
+use crate::blocks::tcp_source::RECONNECT_INTERVAL;
+use crate::blocks::unix_source::bind;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Writes items to Unix domain stream sockets. As server, it binds the path,
+// waits for a first client, and sends the stream to all connected clients
+// (clients connecting later receive the stream from that point on). As client,
+// it connects to the path. Only complete items are written.
+//
+// Without `reconnect`, the block fails once all connections are closed. With
+// `reconnect`, it waits for the next client (server) or connects again (client).
+pub struct UnixSink {
+    path: PathBuf,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    listener: Option<UnixListener>,
+    sockets: Vec<UnixStream>,
+    n_connections: usize,
+}
+
+impl UnixSink {
+    pub fn new(path: &str) -> Block {
+        UnixSinkBuilder::new(path).build()
+    }
+
+    pub fn n_connections(&self) -> usize {
+        self.n_connections
+    }
+
+    // wait for the first connection
+    async fn open(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.client {
+            match UnixStream::connect(&self.path).await {
+                Ok(socket) => {
+                    debug!("unix sink: connected to {:?}", self.path);
+                    self.sockets.push(socket);
+                    self.n_connections += 1;
+                }
+                Err(e) if self.reconnect => {
+                    debug!("unix sink: cannot connect to {:?}: {}", self.path, e);
+                    io.block_on(async {
+                        Timer::after(RECONNECT_INTERVAL).await;
+                    });
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("unix sink: cannot connect to {:?}", self.path))
+                }
+            }
+        } else {
+            let (socket, _) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            debug!("unix sink: accepted connection on {:?}", self.path);
+            self.sockets.push(socket);
+            self.n_connections += 1;
+        }
+        Ok(())
+    }
+
+    // add clients that connected in the meantime
+    fn accept_pending(&mut self) -> Result<()> {
+        if let Some(listener) = self.listener.as_ref() {
+            while let Some(r) = listener.accept().now_or_never() {
+                let (socket, _) = r?;
+                debug!("unix sink: accepted connection on {:?}", self.path);
+                self.sockets.push(socket);
+                self.n_connections += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for UnixSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.sockets.is_empty() {
+            self.open(io).await?;
+            if self.sockets.is_empty() {
+                return Ok(());
+            }
+        }
+        self.accept_pending()?;
+
+        let i = sio.input(0).slice::<u8>();
+        let items = i.len() / self.item_size;
+        let bytes = &i[..items * self.item_size];
+
+        if !bytes.is_empty() {
+            let mut closed = Vec::new();
+            for (n, s) in self.sockets.iter_mut().enumerate() {
+                if let Err(e) = s.write_all(bytes).await {
+                    debug!("unix sink: connection closed ({})", e);
+                    closed.push(n);
+                }
+            }
+            for n in closed.into_iter().rev() {
+                self.sockets.remove(n);
+            }
+
+            if self.sockets.is_empty() {
+                if !self.reconnect {
+                    bail!("unix sink: connection closed");
+                }
+                // the items are lost, since nobody was connected to receive them
+                warn!("unix sink: all connections closed, waiting for a new one");
+            }
+        }
+
+        sio.input(0).consume(items);
+
+        if sio.input(0).finished() && items * self.item_size == i.len() {
+            io.finished = true;
+        } else if self.sockets.is_empty() {
+            io.call_again = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.client {
+            self.listener = Some(bind(&self.path).context("unix sink")?);
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for s in self.sockets.iter_mut() {
+            let _ = s.close().await;
+        }
+        if self.listener.take().is_some() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        Ok(())
+    }
+}
+
+pub struct UnixSinkBuilder {
+    path: PathBuf,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+}
+
+impl UnixSinkBuilder {
+    // server on the socket path, e.g., /tmp/futuresdr.sock
+    pub fn new(path: &str) -> UnixSinkBuilder {
+        UnixSinkBuilder {
+            path: PathBuf::from(path),
+            client: false,
+            reconnect: false,
+            item_size: 1,
+        }
+    }
+
+    // connect to the path instead of binding it
+    pub fn client(mut self, client: bool) -> UnixSinkBuilder {
+        self.client = client;
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> UnixSinkBuilder {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> UnixSinkBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(self.item_size > 0, "unix sink: item size must be positive");
+        Block::new_async(
+            BlockMetaBuilder::new("UnixSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            UnixSink {
+                path: self.path,
+                client: self.client,
+                reconnect: self.reconnect,
+                item_size: self.item_size,
+                listener: None,
+                sockets: Vec::new(),
+                n_connections: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,227 @@
+use anyhow::{Context, Result};
+use async_io::Timer;
+use async_net::unix::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+
+use crate::blocks::tcp_source::RECONNECT_INTERVAL;
+use crate::blocks::ItemReader;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Reads items from a Unix domain stream socket. As server, it binds the path
+// (replacing a stale socket file) and accepts one connection at a time; as
+// client, it connects to the path. Without `reconnect`, the block finishes when
+// the connection is closed. With `reconnect`, it waits for the next connection
+// (server) or connects again (client). Partial items at the end of a connection
+// are dropped.
+pub struct UnixSource {
+    path: PathBuf,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    listener: Option<UnixListener>,
+    socket: Option<UnixStream>,
+    reader: ItemReader,
+    n_connections: usize,
+}
+
+impl UnixSource {
+    pub fn new(path: &str) -> Block {
+        UnixSourceBuilder::new(path).build()
+    }
+
+    pub fn n_connections(&self) -> usize {
+        self.n_connections
+    }
+
+    async fn open(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.client {
+            match UnixStream::connect(&self.path).await {
+                Ok(socket) => {
+                    debug!("unix source: connected to {:?}", self.path);
+                    self.socket = Some(socket);
+                }
+                Err(e) if self.reconnect => {
+                    debug!("unix source: cannot connect to {:?}: {}", self.path, e);
+                    io.block_on(async {
+                        Timer::after(RECONNECT_INTERVAL).await;
+                    });
+                    return Ok(());
+                }
+                Err(e) => {
+                    return Err(e)
+                        .with_context(|| format!("unix source: cannot connect to {:?}", self.path))
+                }
+            }
+        } else {
+            let (socket, _) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            debug!("unix source: accepted connection on {:?}", self.path);
+            self.socket = Some(socket);
+        }
+        self.n_connections += 1;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for UnixSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.socket.is_none() {
+            self.open(io).await?;
+            if self.socket.is_none() {
+                return Ok(());
+            }
+        }
+
+        let out = sio.output(0).slice::<u8>();
+        if out.len() < self.item_size {
+            return Ok(());
+        }
+
+        let socket = self.socket.as_mut().context("no socket")?;
+        let items = match self.reader.read(socket, out).await {
+            Ok(items) => items,
+            Err(e) if self.reconnect => {
+                warn!("unix source: read error {}", e);
+                None
+            }
+            Err(e) => return Err(e).context("unix source: read error"),
+        };
+
+        let items = match items {
+            Some(items) => items,
+            None => {
+                debug!("unix source: socket closed");
+                let p = self.reader.reset();
+                if p > 0 {
+                    warn!("unix source: dropping {} bytes of a partial item", p);
+                }
+                self.socket = None;
+                if self.reconnect {
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
+                return Ok(());
+            }
+        };
+        sio.output(0).produce(items);
+
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.client {
+            self.listener = Some(bind(&self.path).context("unix source")?);
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.listener.take().is_some() {
+            let _ = std::fs::remove_file(&self.path);
+        }
+        Ok(())
+    }
+}
+
+// bind a listener, removing the socket file of a previous run
+pub(crate) fn bind(path: &Path) -> Result<UnixListener> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(m) = std::fs::symlink_metadata(path) {
+        if m.file_type().is_socket() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("cannot remove stale socket {:?}", path))?;
+        }
+    }
+    UnixListener::bind(path).with_context(|| format!("cannot bind {:?}", path))
+}
+
+pub struct UnixSourceBuilder {
+    path: PathBuf,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+}
+
+impl UnixSourceBuilder {
+    // server on the socket path, e.g., /tmp/futuresdr.sock
+    pub fn new(path: &str) -> UnixSourceBuilder {
+        UnixSourceBuilder {
+            path: PathBuf::from(path),
+            client: false,
+            reconnect: false,
+            item_size: 1,
+        }
+    }
+
+    // connect to the path instead of binding it
+    pub fn client(mut self, client: bool) -> UnixSourceBuilder {
+        self.client = client;
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> UnixSourceBuilder {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> UnixSourceBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.item_size > 0,
+            "unix source: item size must be positive"
+        );
+        Block::new_async(
+            BlockMetaBuilder::new("UnixSource").build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            UnixSource {
+                path: self.path,
+                client: self.client,
+                reconnect: self.reconnect,
+                item_size: self.item_size,
+                listener: None,
+                socket: None,
+                reader: ItemReader::new(self.item_size),
+                n_connections: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,6 @@
+mod sink;
+pub use sink::{WavSink, WavSinkBuilder};
+mod source;
+pub use source::{WavSource, WavSourceBuilder};
+mod spec;
+pub use spec::{read_header, write_header, SampleFormat, WavHeader, WavSpec, HEADER_LEN};
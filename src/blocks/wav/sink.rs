@@ -0,0 +1,208 @@
+use anyhow::{Context, Result};
+use async_fs::File;
+use futures::io::AsyncWriteExt;
+use futures::AsyncSeekExt;
+use std::io::SeekFrom;
+use std::mem;
+
+use crate::blocks::wav::write_header;
+use crate::blocks::wav::SampleFormat;
+use crate::blocks::wav::WavSpec;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Writes f32 samples to a WAV file, either interleaved from "in" or with one
+// input per channel ("in0", ...). The header is written with the final length
+// in `deinit`.
+pub struct WavSink {
+    file_name: String,
+    spec: WavSpec,
+    interleaved: bool,
+    file: Option<File>,
+    samples: Vec<f32>,
+    buffer: Vec<u8>,
+    n_written: u64,
+}
+
+impl WavSink {
+    pub fn new(file_name: &str, spec: WavSpec, interleaved: bool) -> Result<Block> {
+        spec.validate()?;
+
+        let mut sio = StreamIoBuilder::new();
+        if interleaved {
+            sio = sio.add_input("in", mem::size_of::<f32>());
+        } else {
+            for c in 0..spec.channels {
+                sio = sio.add_input(&format!("in{}", c), mem::size_of::<f32>());
+            }
+        }
+
+        Ok(Block::new_async(
+            BlockMetaBuilder::new("WavSink").build(),
+            sio.build(),
+            MessageIoBuilder::new().build(),
+            WavSink {
+                file_name: file_name.to_string(),
+                spec,
+                interleaved,
+                file: None,
+                samples: Vec::new(),
+                buffer: Vec::new(),
+                n_written: 0,
+            },
+        ))
+    }
+
+    // number of frames, i.e., samples per channel
+    pub fn n_written(&self) -> u64 {
+        self.n_written
+    }
+
+    async fn write_header(&mut self) -> Result<()> {
+        let data_len = self.n_written * self.spec.block_align() as u64;
+        let mut header = Vec::new();
+        write_header(&mut header, &self.spec, data_len)?;
+
+        let file = self.file.as_mut().unwrap();
+        file.seek(SeekFrom::Start(0)).await?;
+        file.write_all(&header).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for WavSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let channels = self.spec.channels as usize;
+        let n = if self.interleaved {
+            sio.input(0).slice::<f32>().len() / channels
+        } else {
+            (0..channels)
+                .map(|c| sio.input(c).slice::<f32>().len())
+                .min()
+                .unwrap()
+        };
+
+        if n > 0 {
+            self.buffer.clear();
+            if self.interleaved {
+                let i = sio.input(0).slice::<f32>();
+                self.spec.encode(&i[..n * channels], &mut self.buffer);
+                sio.input(0).consume(n * channels);
+            } else {
+                self.samples.resize(n * channels, 0.0);
+                for c in 0..channels {
+                    let i = sio.input(c).slice::<f32>();
+                    for (s, v) in self
+                        .samples
+                        .iter_mut()
+                        .skip(c)
+                        .step_by(channels)
+                        .zip(i.iter())
+                    {
+                        *s = *v;
+                    }
+                    sio.input(c).consume(n);
+                }
+                self.spec.encode(&self.samples, &mut self.buffer);
+            }
+
+            self.file
+                .as_mut()
+                .unwrap()
+                .write_all(&self.buffer)
+                .await
+                .with_context(|| format!("error writing {:?}", self.file_name))?;
+            self.n_written += n as u64;
+        }
+
+        // stop once an input is done and cannot complete another frame
+        let inputs = if self.interleaved { 1 } else { channels };
+        let per_frame = if self.interleaved { channels } else { 1 };
+        if (0..inputs)
+            .any(|i| sio.input(i).finished() && sio.input(i).slice::<f32>().len() < per_frame)
+        {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let file = File::create(&self.file_name)
+            .await
+            .with_context(|| format!("cannot create {:?}", self.file_name))?;
+        self.file = Some(file);
+        self.write_header().await
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("wav sink: n_written {} frames", self.n_written);
+        let data_len = self.n_written * self.spec.block_align() as u64;
+        if data_len & 1 == 1 {
+            self.file.as_mut().unwrap().write_all(&[0]).await?;
+        }
+        self.write_header().await?;
+        self.file.as_mut().unwrap().flush().await?;
+        Ok(())
+    }
+}
+
+pub struct WavSinkBuilder {
+    file_name: String,
+    spec: WavSpec,
+    interleaved: bool,
+}
+
+impl WavSinkBuilder {
+    // 16-bit PCM by default
+    pub fn new(file_name: &str, sample_rate: u32, channels: u16) -> WavSinkBuilder {
+        WavSinkBuilder {
+            file_name: file_name.to_string(),
+            spec: WavSpec::new(channels, sample_rate),
+            interleaved: true,
+        }
+    }
+
+    pub fn bits_per_sample(mut self, bits: u16) -> WavSinkBuilder {
+        self.spec.bits_per_sample = bits;
+        self
+    }
+
+    pub fn sample_format(mut self, format: SampleFormat) -> WavSinkBuilder {
+        self.spec.sample_format = format;
+        self
+    }
+
+    pub fn interleaved(mut self, interleaved: bool) -> WavSinkBuilder {
+        self.interleaved = interleaved;
+        self
+    }
+
+    pub fn build(self) -> Result<Block> {
+        WavSink::new(&self.file_name, self.spec, self.interleaved)
+    }
+}
@@ -0,0 +1,180 @@
+use anyhow::{Context, Result};
+use futures::AsyncReadExt;
+use futures::AsyncSeekExt;
+use std::io::SeekFrom;
+use std::mem;
+
+use crate::blocks::wav::read_header;
+use crate::blocks::wav::WavHeader;
+use crate::blocks::wav::WavSpec;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Reads a WAV file and outputs f32 samples in [-1, 1). Multi-channel files are
+// either interleaved on "out" or split to one output per channel ("out0", ...).
+pub struct WavSource {
+    file_name: String,
+    header: WavHeader,
+    interleaved: bool,
+    file: Option<async_fs::File>,
+    buffer: Vec<u8>,
+    samples: Vec<f32>,
+    n_frames: u64,
+    n_produced: u64,
+}
+
+impl WavSource {
+    pub fn new(file_name: &str, interleaved: bool) -> Result<Block> {
+        let mut file = std::fs::File::open(file_name)
+            .with_context(|| format!("cannot open {:?}", file_name))?;
+        let header =
+            read_header(&mut file).with_context(|| format!("invalid WAV file {:?}", file_name))?;
+
+        let mut sio = StreamIoBuilder::new();
+        if interleaved {
+            sio = sio.add_output("out", mem::size_of::<f32>());
+        } else {
+            for c in 0..header.spec.channels {
+                sio = sio.add_output(&format!("out{}", c), mem::size_of::<f32>());
+            }
+        }
+
+        Ok(Block::new_async(
+            BlockMetaBuilder::new("WavSource").build(),
+            sio.build(),
+            MessageIoBuilder::new().build(),
+            WavSource {
+                file_name: file_name.to_string(),
+                header,
+                interleaved,
+                file: None,
+                buffer: Vec::new(),
+                samples: Vec::new(),
+                n_frames: header.data_len / header.spec.block_align() as u64,
+                n_produced: 0,
+            },
+        ))
+    }
+
+    pub fn spec(&self) -> WavSpec {
+        self.header.spec
+    }
+
+    // number of frames, i.e., samples per channel
+    pub fn n_frames(&self) -> u64 {
+        self.n_frames
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for WavSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let channels = self.header.spec.channels as usize;
+        let space = if self.interleaved {
+            sio.output(0).slice::<f32>().len() / channels
+        } else {
+            (0..channels)
+                .map(|c| sio.output(c).slice::<f32>().len())
+                .min()
+                .unwrap()
+        };
+        let n = std::cmp::min(space as u64, self.n_frames - self.n_produced) as usize;
+
+        if n > 0 {
+            self.buffer.resize(n * self.header.spec.block_align(), 0);
+            self.file
+                .as_mut()
+                .unwrap()
+                .read_exact(&mut self.buffer)
+                .await
+                .with_context(|| format!("error reading {:?}", self.file_name))?;
+
+            if self.interleaved {
+                let out = sio.output(0).slice::<f32>();
+                self.header
+                    .spec
+                    .decode(&self.buffer, &mut out[..n * channels]);
+                sio.output(0).produce(n * channels);
+            } else {
+                self.samples.resize(n * channels, 0.0);
+                self.header.spec.decode(&self.buffer, &mut self.samples);
+                for c in 0..channels {
+                    let out = sio.output(c).slice::<f32>();
+                    for (o, s) in out
+                        .iter_mut()
+                        .zip(self.samples.iter().skip(c).step_by(channels))
+                    {
+                        *o = *s;
+                    }
+                    sio.output(c).produce(n);
+                }
+            }
+            self.n_produced += n as u64;
+        }
+
+        if self.n_produced == self.n_frames {
+            io.finished = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let mut file = async_fs::File::open(&self.file_name)
+            .await
+            .with_context(|| format!("cannot open {:?}", self.file_name))?;
+        file.seek(SeekFrom::Start(self.header.data_offset)).await?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("wav source: n_produced {} frames", self.n_produced);
+        Ok(())
+    }
+}
+
+pub struct WavSourceBuilder {
+    file_name: String,
+    interleaved: bool,
+}
+
+impl WavSourceBuilder {
+    pub fn new(file_name: &str) -> WavSourceBuilder {
+        WavSourceBuilder {
+            file_name: file_name.to_string(),
+            interleaved: true,
+        }
+    }
+
+    pub fn interleaved(mut self, interleaved: bool) -> WavSourceBuilder {
+        self.interleaved = interleaved;
+        self
+    }
+
+    pub fn build(self) -> Result<Block> {
+        WavSource::new(&self.file_name, self.interleaved)
+    }
+}
@@ -0,0 +1,220 @@
+use anyhow::{bail, Context, Result};
+use std::io::{Read, Seek, SeekFrom, Write};
+
+const WAVE_FORMAT_PCM: u16 = 1;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 3;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+// size of the header written by `write_header`
+pub const HEADER_LEN: u64 = 44;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleFormat {
+    Int,
+    Float,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavSpec {
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+    pub sample_format: SampleFormat,
+}
+
+impl WavSpec {
+    pub fn new(channels: u16, sample_rate: u32) -> WavSpec {
+        WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.channels == 0 {
+            bail!("WAV file without channels");
+        }
+        match (self.sample_format, self.bits_per_sample) {
+            (SampleFormat::Int, 8 | 16 | 24 | 32) => Ok(()),
+            (SampleFormat::Float, 32 | 64) => Ok(()),
+            (f, b) => bail!("unsupported WAV sample format {:?} with {} bits", f, b),
+        }
+    }
+
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample as usize / 8
+    }
+
+    // bytes per frame, i.e., one sample of each channel
+    pub fn block_align(&self) -> usize {
+        self.bytes_per_sample() * self.channels as usize
+    }
+
+    // decode samples to f32 in [-1, 1)
+    pub fn decode(&self, bytes: &[u8], out: &mut [f32]) {
+        let n = self.bytes_per_sample();
+        for (b, o) in bytes.chunks_exact(n).zip(out.iter_mut()) {
+            *o = match (self.sample_format, n) {
+                (SampleFormat::Int, 1) => (b[0] as f32 - 128.0) / 128.0,
+                (SampleFormat::Int, 2) => i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+                (SampleFormat::Int, 3) => {
+                    (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8388608.0
+                }
+                (SampleFormat::Int, _) => {
+                    i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0
+                }
+                (SampleFormat::Float, 4) => f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+                (SampleFormat::Float, _) => {
+                    f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32
+                }
+            };
+        }
+    }
+
+    // encode f32 samples, integer formats are clipped to [-1, 1]
+    pub fn encode(&self, samples: &[f32], out: &mut Vec<u8>) {
+        for s in samples {
+            let c = s.clamp(-1.0, 1.0) as f64;
+            match (self.sample_format, self.bits_per_sample) {
+                (SampleFormat::Int, 8) => out.push((c * 127.0 + 128.0).round() as u8),
+                (SampleFormat::Int, 16) => {
+                    out.extend_from_slice(&((c * 32767.0).round() as i16).to_le_bytes())
+                }
+                (SampleFormat::Int, 24) => {
+                    out.extend_from_slice(&((c * 8388607.0).round() as i32).to_le_bytes()[..3])
+                }
+                (SampleFormat::Int, _) => {
+                    out.extend_from_slice(&((c * 2147483647.0).round() as i32).to_le_bytes())
+                }
+                (SampleFormat::Float, 32) => out.extend_from_slice(&s.to_le_bytes()),
+                (SampleFormat::Float, _) => out.extend_from_slice(&(*s as f64).to_le_bytes()),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavHeader {
+    pub spec: WavSpec,
+    // position and length of the sample data in bytes
+    pub data_offset: u64,
+    pub data_len: u64,
+}
+
+fn read_u16(b: &[u8]) -> u16 {
+    u16::from_le_bytes([b[0], b[1]])
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+pub fn read_header<R: Read + Seek>(r: &mut R) -> Result<WavHeader> {
+    let file_len = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(0))?;
+
+    let mut riff = [0u8; 12];
+    r.read_exact(&mut riff).context("WAV file too short")?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        bail!("not a RIFF/WAVE file");
+    }
+
+    let mut spec = None;
+    let mut pos = 12u64;
+    loop {
+        let mut chunk = [0u8; 8];
+        r.read_exact(&mut chunk)
+            .context("WAV file without data chunk")?;
+        let len = read_u32(&chunk[4..8]) as u64;
+        pos += 8;
+
+        match &chunk[0..4] {
+            b"fmt " => {
+                if len < 16 {
+                    bail!("invalid WAV fmt chunk");
+                }
+                let mut fmt = vec![0u8; len as usize];
+                r.read_exact(&mut fmt)?;
+
+                let mut format = read_u16(&fmt[0..2]);
+                if format == WAVE_FORMAT_EXTENSIBLE {
+                    if len < 40 {
+                        bail!("invalid WAV extensible fmt chunk");
+                    }
+                    // first two bytes of the sub format GUID
+                    format = read_u16(&fmt[24..26]);
+                }
+                let sample_format = match format {
+                    WAVE_FORMAT_PCM => SampleFormat::Int,
+                    WAVE_FORMAT_IEEE_FLOAT => SampleFormat::Float,
+                    f => bail!("unsupported WAV format tag {:#x}", f),
+                };
+
+                let s = WavSpec {
+                    channels: read_u16(&fmt[2..4]),
+                    sample_rate: read_u32(&fmt[4..8]),
+                    bits_per_sample: read_u16(&fmt[14..16]),
+                    sample_format,
+                };
+                s.validate()?;
+                if read_u16(&fmt[12..14]) as usize != s.block_align() {
+                    bail!("unsupported WAV block align {}", read_u16(&fmt[12..14]));
+                }
+                spec = Some(s);
+            }
+            b"data" => {
+                let spec = spec.context("WAV data chunk before fmt chunk")?;
+                // streaming writers leave the length at 0 or u32::MAX
+                let mut data_len = std::cmp::min(len, file_len - pos);
+                if len == 0 {
+                    data_len = file_len - pos;
+                }
+                data_len -= data_len % spec.block_align() as u64;
+                return Ok(WavHeader {
+                    spec,
+                    data_offset: pos,
+                    data_len,
+                });
+            }
+            _ => {
+                r.seek(SeekFrom::Current(len as i64))?;
+            }
+        }
+
+        // chunks are padded to an even length
+        pos += len;
+        if len & 1 == 1 {
+            r.seek(SeekFrom::Current(1))?;
+            pos += 1;
+        }
+    }
+}
+
+pub fn write_header<W: Write>(w: &mut W, spec: &WavSpec, data_len: u64) -> Result<()> {
+    let format = match spec.sample_format {
+        SampleFormat::Int => WAVE_FORMAT_PCM,
+        SampleFormat::Float => WAVE_FORMAT_IEEE_FLOAT,
+    };
+    let data_len = std::cmp::min(data_len, u32::MAX as u64 - HEADER_LEN) as u32;
+    let riff_len = HEADER_LEN as u32 - 8 + data_len + (data_len & 1);
+    let block_align = spec.block_align() as u32;
+
+    let mut h = Vec::with_capacity(HEADER_LEN as usize);
+    h.extend_from_slice(b"RIFF");
+    h.extend_from_slice(&riff_len.to_le_bytes());
+    h.extend_from_slice(b"WAVEfmt ");
+    h.extend_from_slice(&16u32.to_le_bytes());
+    h.extend_from_slice(&format.to_le_bytes());
+    h.extend_from_slice(&spec.channels.to_le_bytes());
+    h.extend_from_slice(&spec.sample_rate.to_le_bytes());
+    h.extend_from_slice(&(spec.sample_rate * block_align).to_le_bytes());
+    h.extend_from_slice(&(block_align as u16).to_le_bytes());
+    h.extend_from_slice(&spec.bits_per_sample.to_le_bytes());
+    h.extend_from_slice(b"data");
+    h.extend_from_slice(&data_len.to_le_bytes());
+
+    w.write_all(&h)?;
+    Ok(())
+}
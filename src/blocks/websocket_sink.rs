@@ -1,17 +1,21 @@
 use anyhow::Context as _;
 use anyhow::Result;
 use async_io::Async;
+use async_tungstenite::tungstenite::handshake::server::{
+    Callback, ErrorResponse, Request, Response,
+};
 use async_tungstenite::tungstenite::Message;
 use async_tungstenite::WebSocketStream;
-use futures::future;
-use futures::future::Either;
+use futures::channel::mpsc;
+use futures::future::{poll_fn, select};
 use futures::sink::{Sink, SinkExt};
-use futures::Stream;
+use futures::FutureExt;
+use futures::{Stream, StreamExt};
 use std::marker::PhantomData;
 use std::mem::size_of;
 use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
 
 use crate::runtime::AsyncKernel;
@@ -30,30 +34,156 @@ pub enum WebsocketSinkMode {
     FixedDropping(usize),
 }
 
+// How frames are sent to a client. With `Block`, the flowgraph waits until the
+// client accepted the frame. With `Drop`, frames are dropped for clients that
+// are not ready, so that slow clients do not stall the flowgraph or the other
+// clients. Clients can override the default with a query string, e.g.,
+// ws://127.0.0.1:9001/?policy=drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebsocketClientPolicy {
+    Block,
+    Drop,
+}
+
+impl WebsocketClientPolicy {
+    fn from_query(query: Option<&str>) -> Option<WebsocketClientPolicy> {
+        query?.split('&').find_map(|kv| match kv {
+            "policy=block" => Some(WebsocketClientPolicy::Block),
+            "policy=drop" => Some(WebsocketClientPolicy::Drop),
+            _ => None,
+        })
+    }
+}
+
+// reads the policy from the handshake request
+struct PolicyCallback<'a>(&'a mut WebsocketClientPolicy);
+
+impl Callback for PolicyCallback<'_> {
+    fn on_request(self, req: &Request, res: Response) -> Result<Response, ErrorResponse> {
+        if let Some(p) = WebsocketClientPolicy::from_query(req.uri().query()) {
+            *self.0 = p;
+        }
+        Ok(res)
+    }
+}
+
+struct Client {
+    conn: WsStream,
+    policy: WebsocketClientPolicy,
+    peer: SocketAddr,
+}
+
+// handshakes that run in the background, so that a client that does not
+// complete its handshake does not stall the flowgraph
+#[derive(Default)]
+struct Handshakes {
+    running: usize,
+    done: Vec<Client>,
+}
+
 pub struct WebsocketSink<T: Send + Sync + 'static> {
     port: u32,
     listener: Option<Arc<Async<TcpListener>>>,
-    conn: Option<WsStream>,
+    clients: Vec<Client>,
+    handshakes: Arc<Mutex<Handshakes>>,
+    // notified when a handshake is done
+    handshake_tx: mpsc::UnboundedSender<()>,
+    handshake_rx: Arc<async_lock::Mutex<mpsc::UnboundedReceiver<()>>>,
     mode: WebsocketSinkMode,
+    policy: WebsocketClientPolicy,
+    max_clients: usize,
+    n_clients: usize,
+    n_dropped: usize,
     _p: PhantomData<T>,
 }
 
 impl<T: Send + Sync + 'static> WebsocketSink<T> {
     pub fn new(port: u32, mode: WebsocketSinkMode) -> Block {
-        Block::new_async(
-            BlockMetaBuilder::new("WebsocketSink").build(),
-            StreamIoBuilder::new()
-                .add_input("in", size_of::<T>())
-                .build(),
-            MessageIoBuilder::<Self>::new().build(),
-            WebsocketSink {
-                port,
-                listener: None,
-                conn: None,
-                mode,
-                _p: PhantomData,
-            },
-        )
+        WebsocketSinkBuilder::<T>::new(port).mode(mode).build()
+    }
+
+    // number of clients that connected so far
+    pub fn n_clients(&self) -> usize {
+        self.n_clients
+    }
+
+    // frames dropped for clients with `WebsocketClientPolicy::Drop`
+    pub fn n_dropped(&self) -> usize {
+        self.n_dropped
+    }
+
+    fn accept(&mut self) -> Result<()> {
+        let mut handshakes = self.handshakes.lock().unwrap();
+        for c in handshakes.done.drain(..) {
+            debug!("websocket: accepted client {} ({:?})", c.peer, c.policy);
+            self.clients.push(c);
+            self.n_clients += 1;
+        }
+
+        let listener = self.listener.as_ref().context("no listener")?;
+        while let Ok((stream, peer)) = listener.get_ref().accept() {
+            if self.clients.len() + handshakes.running >= self.max_clients {
+                warn!("websocket: rejecting client {}, too many clients", peer);
+                continue;
+            }
+            handshakes.running += 1;
+
+            let stream = Async::new(stream)?;
+            let mut policy = self.policy;
+            let shared = self.handshakes.clone();
+            let tx = self.handshake_tx.clone();
+            // the block has no handle to the executor, so the handshake runs on the
+            // blocking thread pool
+            blocking::unblock(move || {
+                async_io::block_on(async move {
+                    let res =
+                        async_tungstenite::accept_hdr_async(stream, PolicyCallback(&mut policy))
+                            .await;
+                    let mut handshakes = shared.lock().unwrap();
+                    handshakes.running -= 1;
+                    match res {
+                        Ok(inner) => handshakes.done.push(Client {
+                            conn: WsStream { inner },
+                            policy,
+                            peer,
+                        }),
+                        Err(e) => warn!("websocket: handshake with {} failed: {}", peer, e),
+                    }
+                    drop(handshakes);
+                    let _ = tx.unbounded_send(());
+                })
+            })
+            .detach();
+        }
+        Ok(())
+    }
+
+    async fn send(&mut self, v: Vec<u8>) {
+        let mut closed = Vec::new();
+        for (i, c) in self.clients.iter_mut().enumerate() {
+            let msg = Message::Binary(v.clone());
+            let res = match c.policy {
+                WebsocketClientPolicy::Block => c.conn.send(msg).await.map(|_| true),
+                // only start the frame if the connection can take it, so that frames
+                // are dropped as a whole
+                WebsocketClientPolicy::Drop => {
+                    match poll_fn(|cx| Poll::Ready(c.conn.poll_ready_unpin(cx))).await {
+                        Poll::Ready(Ok(())) => c.conn.start_send_unpin(msg).map(|_| true),
+                        Poll::Ready(Err(e)) => Err(e),
+                        Poll::Pending => Ok(false),
+                    }
+                }
+            };
+            match res {
+                Ok(true) => {}
+                Ok(false) => self.n_dropped += 1,
+                Err(_) => closed.push(i),
+            }
+        }
+        for i in closed.into_iter().rev() {
+            debug!("websocket: client {} disconnected", self.clients[i].peer);
+            self.clients.remove(i);
+        }
     }
 }
 
@@ -66,6 +196,8 @@ impl<T: Send + Sync + 'static> AsyncKernel for WebsocketSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        self.accept()?;
+
         let i = sio.input(0).slice::<u8>();
         debug_assert_eq!(i.len() % size_of::<T>(), 0);
 
@@ -76,76 +208,55 @@ impl<T: Send + Sync + 'static> AsyncKernel for WebsocketSink<T> {
         let item_size = size_of::<T>();
         let items = i.len() / item_size;
 
-        if let Some(ref mut conn) = self.conn {
-            if i.is_empty() {
-                return Ok(());
+        if self.clients.is_empty() {
+            if let WebsocketSinkMode::FixedDropping(block_size) = &self.mode {
+                let n = items / block_size;
+                sio.input(0).consume(n * block_size);
             }
 
-            let mut v = Vec::new();
+            // wait for a connection or the end of a handshake
+            let l = self.listener.as_ref().context("no listener")?.clone();
+            let rx = self.handshake_rx.clone();
+            io.block_on(async move {
+                let handshake = async move {
+                    rx.lock().await.next().await;
+                };
+                select(l.readable().boxed(), handshake.boxed()).await;
+            });
+            return Ok(());
+        }
 
-            match &self.mode {
-                WebsocketSinkMode::Blocking => {
-                    v.extend_from_slice(i);
-                    sio.input(0).consume(i.len() / size_of::<T>());
-                }
-                WebsocketSinkMode::FixedBlocking(block_size) => {
-                    if *block_size <= items {
-                        v.extend_from_slice(&i[0..(block_size * item_size)]);
-                        sio.input(0).consume(*block_size);
-                    }
-                }
-                WebsocketSinkMode::FixedDropping(block_size) => {
-                    let n = items / block_size;
-                    if n != 0 {
-                        v.extend_from_slice(
-                            &i[((n - 1) * block_size * item_size)..(n * block_size * item_size)],
-                        );
-                        sio.input(0).consume(n * block_size);
-                    }
-                }
-            }
+        if i.is_empty() {
+            return Ok(());
+        }
 
-            if !v.is_empty() {
-                let acc = Box::pin(self.listener.as_ref().context("no listener")?.accept());
-                let send = conn.send(Message::Binary(v));
-
-                match future::select(acc, send).await {
-                    Either::Left((a, _)) => {
-                        if let Ok((stream, _)) = a {
-                            self.conn = Some(WsStream {
-                                inner: async_tungstenite::accept_async(stream).await?,
-                            });
-                        }
-                    }
-                    Either::Right((s, _)) => {
-                        if s.is_err() {
-                            debug!("websocket: client disconnected");
-                            self.conn = None;
-                        }
-                    }
+        let mut v = Vec::new();
+
+        match &self.mode {
+            WebsocketSinkMode::Blocking => {
+                v.extend_from_slice(i);
+                sio.input(0).consume(items);
+            }
+            WebsocketSinkMode::FixedBlocking(block_size) => {
+                if *block_size <= items {
+                    v.extend_from_slice(&i[0..(block_size * item_size)]);
+                    sio.input(0).consume(*block_size);
                 }
             }
-        } else if let Ok((stream, socket)) = self
-            .listener
-            .as_ref()
-            .context("no listener")?
-            .get_ref()
-            .accept()
-        {
-            debug!("Websocket Accepted client: {}", socket);
-            self.conn = Some(WsStream {
-                inner: async_tungstenite::accept_async(Async::new(stream)?).await?,
-            });
-        } else {
-            if let WebsocketSinkMode::FixedDropping(block_size) = &self.mode {
+            WebsocketSinkMode::FixedDropping(block_size) => {
                 let n = items / block_size;
-                sio.input(0).consume(n * block_size);
+                if n != 0 {
+                    v.extend_from_slice(
+                        &i[((n - 1) * block_size * item_size)..(n * block_size * item_size)],
+                    );
+                    sio.input(0).consume(n * block_size);
+                }
             }
+        }
 
-            let l = self.listener.as_ref().unwrap().clone();
-            io.block_on(async move {
-                l.readable().await.unwrap();
-            });
+        if !v.is_empty() {
+            self.send(v).await;
+            io.call_again = true;
         }
 
         Ok(())
@@ -162,11 +273,26 @@ impl<T: Send + Sync + 'static> AsyncKernel for WebsocketSink<T> {
         )?));
         Ok(())
     }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.listener = None;
+        for c in self.clients.iter_mut() {
+            let _ = c.conn.close().await;
+        }
+        Ok(())
+    }
 }
 
 pub struct WebsocketSinkBuilder<T: Send + Sync + 'static> {
     port: u32,
     mode: WebsocketSinkMode,
+    policy: WebsocketClientPolicy,
+    max_clients: usize,
     _p: PhantomData<T>,
 }
 
@@ -175,6 +301,8 @@ impl<T: Send + Sync + 'static> WebsocketSinkBuilder<T> {
         WebsocketSinkBuilder {
             port,
             mode: WebsocketSinkMode::Blocking,
+            policy: WebsocketClientPolicy::Block,
+            max_clients: usize::MAX,
             _p: PhantomData,
         }
     }
@@ -184,8 +312,40 @@ impl<T: Send + Sync + 'static> WebsocketSinkBuilder<T> {
         self
     }
 
+    // default policy for clients that do not set one
+    pub fn client_policy(mut self, policy: WebsocketClientPolicy) -> WebsocketSinkBuilder<T> {
+        self.policy = policy;
+        self
+    }
+
+    pub fn max_clients(mut self, max_clients: usize) -> WebsocketSinkBuilder<T> {
+        self.max_clients = max_clients;
+        self
+    }
+
     pub fn build(self) -> Block {
-        WebsocketSink::<T>::new(self.port, self.mode)
+        let (handshake_tx, handshake_rx) = mpsc::unbounded();
+        Block::new_async(
+            BlockMetaBuilder::new("WebsocketSink").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<T>())
+                .build(),
+            MessageIoBuilder::<WebsocketSink<T>>::new().build(),
+            WebsocketSink::<T> {
+                port: self.port,
+                listener: None,
+                clients: Vec::new(),
+                handshakes: Arc::new(Mutex::new(Handshakes::default())),
+                handshake_tx,
+                handshake_rx: Arc::new(async_lock::Mutex::new(handshake_rx)),
+                mode: self.mode,
+                policy: self.policy,
+                max_clients: self.max_clients,
+                n_clients: 0,
+                n_dropped: 0,
+                _p: PhantomData,
+            },
+        )
     }
 }
 
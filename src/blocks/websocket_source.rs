@@ -0,0 +1,282 @@
+use anyhow::{bail, Context, Result};
+use async_io::Timer;
+use async_net::{TcpListener, TcpStream};
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use futures::StreamExt;
+
+use crate::blocks::tcp_source::RECONNECT_INTERVAL;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Receives WebSocket frames, either as server on a local address or as client
+// of a ws:// URL. In stream mode, the payload of binary frames is written to the
+// "out" stream output. In PDU mode, binary frames are posted as Pmt::Blob and
+// text frames as Pmt::String on the "out" message output.
+//
+// Without `reconnect`, the block finishes when the connection is closed. With
+// `reconnect`, it waits for the next client (server) or connects again (client).
+pub struct WebsocketSource {
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    pdu: bool,
+    listener: Option<TcpListener>,
+    conn: Option<WebSocketStream<TcpStream>>,
+    pending: Vec<u8>,
+    n_connections: usize,
+}
+
+impl WebsocketSource {
+    pub fn new(port: u32) -> Block {
+        WebsocketSourceBuilder::new(port).build()
+    }
+
+    pub fn n_connections(&self) -> usize {
+        self.n_connections
+    }
+
+    async fn open(&mut self, io: &mut WorkIo) -> Result<()> {
+        if self.client {
+            match self.connect().await {
+                Ok(conn) => {
+                    debug!("websocket source: connected to {}", self.address);
+                    self.conn = Some(conn);
+                }
+                Err(e) if self.reconnect => {
+                    debug!("websocket source: {:?}", e);
+                    io.block_on(async {
+                        Timer::after(RECONNECT_INTERVAL).await;
+                    });
+                    return Ok(());
+                }
+                Err(e) => return Err(e),
+            }
+        } else {
+            let (stream, peer) = self
+                .listener
+                .as_ref()
+                .context("no listener")?
+                .accept()
+                .await?;
+            match async_tungstenite::accept_async(stream).await {
+                Ok(conn) => {
+                    debug!("websocket source: accepted client {}", peer);
+                    self.conn = Some(conn);
+                }
+                Err(e) => {
+                    warn!("websocket source: handshake with {} failed: {}", peer, e);
+                    return Ok(());
+                }
+            }
+        }
+        self.n_connections += 1;
+        Ok(())
+    }
+
+    async fn connect(&self) -> Result<WebSocketStream<TcpStream>> {
+        let host = self
+            .address
+            .strip_prefix("ws://")
+            .and_then(|s| s.split('/').next())
+            .with_context(|| format!("websocket source: invalid URL {}", self.address))?;
+        let stream = TcpStream::connect(host)
+            .await
+            .with_context(|| format!("websocket source: cannot connect to {}", host))?;
+        let (conn, _) = async_tungstenite::client_async(self.address.as_str(), stream)
+            .await
+            .with_context(|| format!("websocket source: handshake with {} failed", host))?;
+        Ok(conn)
+    }
+
+    // next frame or None if the connection was closed
+    async fn next(&mut self) -> Result<Option<Message>> {
+        let conn = self.conn.as_mut().context("not connected")?;
+        loop {
+            match conn.next().await {
+                Some(Ok(Message::Close(_))) | None => return Ok(None),
+                Some(Ok(m @ Message::Binary(_))) | Some(Ok(m @ Message::Text(_))) => {
+                    return Ok(Some(m))
+                }
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    debug!("websocket source: connection error {}", e);
+                    return Ok(None);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for WebsocketSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.conn.is_none() {
+            self.open(io).await?;
+            if self.conn.is_none() {
+                if io.block_on.is_none() {
+                    io.call_again = true;
+                }
+                return Ok(());
+            }
+        }
+
+        if self.pdu || self.pending.len() < self.item_size {
+            match self.next().await? {
+                Some(Message::Binary(b)) if self.pdu => mio.post(0, Pmt::Blob(b)).await,
+                Some(Message::Text(s)) if self.pdu => mio.post(0, Pmt::String(s)).await,
+                Some(Message::Binary(b)) => self.pending.extend_from_slice(&b),
+                Some(m) => warn!("websocket source: ignoring frame {:?}", m),
+                None => {
+                    debug!("websocket source: connection closed");
+                    self.conn = None;
+                    if !self.pending.is_empty() {
+                        warn!(
+                            "websocket source: dropping {} bytes of a partial item",
+                            self.pending.len()
+                        );
+                        self.pending.clear();
+                    }
+                    if self.reconnect {
+                        io.call_again = true;
+                    } else {
+                        io.finished = true;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+
+        if !self.pdu {
+            let o = sio.output(0).slice::<u8>();
+            let n = std::cmp::min(o.len(), self.pending.len()) / self.item_size;
+            if n > 0 {
+                let bytes = n * self.item_size;
+                o[..bytes].copy_from_slice(&self.pending[..bytes]);
+                self.pending.drain(..bytes);
+                sio.output(0).produce(n);
+            }
+            if self.pending.len() >= self.item_size {
+                return Ok(());
+            }
+        }
+
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if !self.client {
+            self.listener = Some(
+                TcpListener::bind(&self.address)
+                    .await
+                    .with_context(|| format!("websocket source: cannot bind {}", self.address))?,
+            );
+        } else if !self.address.starts_with("ws://") {
+            bail!("websocket source: only ws:// URLs are supported");
+        }
+        Ok(())
+    }
+}
+
+pub struct WebsocketSourceBuilder {
+    address: String,
+    client: bool,
+    reconnect: bool,
+    item_size: usize,
+    pdu: bool,
+}
+
+impl WebsocketSourceBuilder {
+    // server on 127.0.0.1:port
+    pub fn new(port: u32) -> WebsocketSourceBuilder {
+        WebsocketSourceBuilder {
+            address: format!("127.0.0.1:{}", port),
+            client: false,
+            reconnect: false,
+            item_size: 1,
+            pdu: false,
+        }
+    }
+
+    // server on a local address, e.g., 0.0.0.0:9001
+    pub fn bind(mut self, address: &str) -> WebsocketSourceBuilder {
+        self.address = address.to_string();
+        self.client = false;
+        self
+    }
+
+    // client of a URL, e.g., ws://127.0.0.1:9001/
+    pub fn connect(mut self, url: &str) -> WebsocketSourceBuilder {
+        self.address = url.to_string();
+        self.client = true;
+        self
+    }
+
+    pub fn reconnect(mut self, reconnect: bool) -> WebsocketSourceBuilder {
+        self.reconnect = reconnect;
+        self
+    }
+
+    pub fn item_size(mut self, item_size: usize) -> WebsocketSourceBuilder {
+        self.item_size = item_size;
+        self
+    }
+
+    pub fn pdu(mut self, pdu: bool) -> WebsocketSourceBuilder {
+        self.pdu = pdu;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        assert!(
+            self.item_size > 0,
+            "websocket source: item size must be positive"
+        );
+
+        let mut sio = StreamIoBuilder::new();
+        let mut mio = MessageIoBuilder::new();
+        if self.pdu {
+            mio = mio.add_output("out");
+        } else {
+            sio = sio.add_output("out", self.item_size);
+        }
+
+        Block::new_async(
+            BlockMetaBuilder::new("WebsocketSource").build(),
+            sio.build(),
+            mio.build(),
+            WebsocketSource {
+                address: self.address,
+                client: self.client,
+                reconnect: self.reconnect,
+                item_size: self.item_size,
+                pdu: self.pdu,
+                listener: None,
+                conn: None,
+                pending: Vec::new(),
+                n_connections: 0,
+            },
+        )
+    }
+}
@@ -0,0 +1,48 @@
+use anyhow::{Context, Result};
+use futuresdr_pmt::wire;
+
+use crate::runtime::Pmt;
+
+// timeout of blocking receive calls, so that blocks still handle messages and
+// shut down in time
+pub(crate) const RECV_TIMEOUT_MS: i32 = 100;
+
+// serialization of Pmts in ZeroMQ messages; Binary is the versioned wire format
+// of the pmt crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Binary,
+}
+
+pub fn encode(p: &Pmt, format: Format) -> Result<Vec<u8>> {
+    match format {
+        Format::Json => serde_json::to_vec(p).context("cannot serialize Pmt to JSON"),
+        Format::Binary => Ok(wire::encode(p)),
+    }
+}
+
+pub fn decode(b: &[u8], format: Format) -> Result<Pmt> {
+    match format {
+        Format::Json => serde_json::from_slice(b).context("cannot deserialize Pmt from JSON"),
+        Format::Binary => wire::decode(b).context("cannot deserialize Pmt"),
+    }
+}
+
+// receive a message, dropping topic frames; None on timeout
+pub(crate) fn recv(socket: &zmq::Socket) -> Result<Option<Vec<u8>>> {
+    match socket.recv_multipart(0) {
+        Ok(mut frames) => Ok(frames.pop()),
+        Err(zmq::Error::EAGAIN) => Ok(None),
+        Err(e) => Err(e).context("zeromq: receive error"),
+    }
+}
+
+// send a message with an optional topic frame
+pub(crate) fn send(socket: &zmq::Socket, topic: Option<&str>, data: &[u8]) -> Result<()> {
+    match topic {
+        Some(t) => socket.send_multipart([t.as_bytes(), data], 0),
+        None => socket.send(data, 0),
+    }
+    .context("zeromq: send error")
+}
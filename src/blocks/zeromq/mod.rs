@@ -1,5 +1,63 @@
+// Adds the builder methods for the SocketConfig in the `config` field of the
+// builder. With `format`, the builder also gets the serialization of the Pmts.
+macro_rules! socket_options {
+    ($builder:ident) => {
+        impl $builder {
+            pub fn address(mut self, address: &str) -> $builder {
+                self.config.address = address.to_string();
+                self
+            }
+
+            // maximum number of messages queued by the socket
+            pub fn hwm(mut self, hwm: i32) -> $builder {
+                self.config.hwm = Some(hwm);
+                self
+            }
+        }
+    };
+    ($builder:ident, format) => {
+        socket_options!($builder);
+
+        impl $builder {
+            pub fn format(mut self, format: crate::blocks::zeromq::Format) -> $builder {
+                self.config.format = format;
+                self
+            }
+        }
+    };
+}
+
+mod format;
+pub use format::{decode, encode, Format};
+
+mod socket;
+
 pub mod pub_sink;
 pub use pub_sink::{PubSink, PubSinkBuilder};
 
 pub mod sub_source;
 pub use sub_source::{SubSource, SubSourceBuilder};
+
+pub mod push_sink;
+pub use push_sink::{PushSink, PushSinkBuilder};
+
+pub mod pull_source;
+pub use pull_source::{PullSource, PullSourceBuilder};
+
+pub mod pub_message_sink;
+pub use pub_message_sink::{PubMessageSink, PubMessageSinkBuilder};
+
+pub mod sub_message_source;
+pub use sub_message_source::{SubMessageSource, SubMessageSourceBuilder};
+
+pub mod push_message_sink;
+pub use push_message_sink::{PushMessageSink, PushMessageSinkBuilder};
+
+pub mod pull_message_source;
+pub use pull_message_source::{PullMessageSource, PullMessageSourceBuilder};
+
+pub mod req_message_client;
+pub use req_message_client::{ReqMessageClient, ReqMessageClientBuilder};
+
+pub mod rep_message_server;
+pub use rep_message_server::{RepMessageServer, RepMessageServerBuilder};
@@ -0,0 +1,110 @@
+use anyhow::{Context, Result};
+use log::warn;
+
+use crate::blocks::zeromq::format::{encode, send};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// Publishes each Pmt on the "in" message input as one ZeroMQ message.
+pub struct PubMessageSink {
+    config: SocketConfig,
+    topic: Option<String>,
+    publisher: Option<zmq::Socket>,
+    n_sent: usize,
+}
+
+impl PubMessageSink {
+    pub fn new(address: &str) -> Block {
+        PubMessageSinkBuilder::new().address(address).build()
+    }
+
+    pub fn n_sent(&self) -> usize {
+        self.n_sent
+    }
+
+    fn handler(
+        &mut self,
+        _mio: &mut MessageIo<PubMessageSink>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match encode(&p, self.config.format) {
+            Ok(data) => {
+                send(
+                    self.publisher.as_ref().context("no socket")?,
+                    self.topic.as_deref(),
+                    &data,
+                )?;
+                self.n_sent += 1;
+            }
+            Err(e) => warn!("PubMessageSink: {:?}", e),
+        }
+        Ok(Pmt::Null)
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for PubMessageSink {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let publisher = self.config.bind(zmq::PUB)?;
+        self.publisher = Some(publisher);
+        Ok(())
+    }
+}
+
+pub struct PubMessageSinkBuilder {
+    config: SocketConfig,
+    topic: Option<String>,
+}
+
+impl PubMessageSinkBuilder {
+    pub fn new() -> PubMessageSinkBuilder {
+        PubMessageSinkBuilder {
+            config: SocketConfig::new("tcp://*:5555"),
+            topic: None,
+        }
+    }
+
+    // send the topic as first frame of each message
+    pub fn topic(mut self, topic: &str) -> PubMessageSinkBuilder {
+        self.topic = Some(topic.to_string());
+        self
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("PubMessageSink").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input("in", PubMessageSink::handler)
+                .build(),
+            PubMessageSink {
+                config: self.config.clone(),
+                topic: self.topic.clone(),
+                publisher: None,
+                n_sent: 0,
+            },
+        )
+    }
+}
+
+socket_options!(PubMessageSinkBuilder, format);
+
+impl Default for PubMessageSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,6 +1,7 @@
-use anyhow::Result;
-use log::info;
+use anyhow::{Context, Result};
 
+use crate::blocks::zeromq::format::send;
+use crate::blocks::zeromq::socket::SocketConfig;
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -13,22 +14,14 @@ use crate::runtime::WorkIo;
 
 pub struct PubSink {
     item_size: usize,
-    address: String,
+    config: SocketConfig,
+    topic: Option<String>,
     publisher: Option<zmq::Socket>,
 }
 
 impl PubSink {
     pub fn new(item_size: usize, address: &str) -> Block {
-        Block::new_async(
-            BlockMetaBuilder::new("PubSink").blocking().build(),
-            StreamIoBuilder::new().add_input("in", item_size).build(),
-            MessageIoBuilder::new().build(),
-            PubSink {
-                item_size,
-                address: address.to_string(),
-                publisher: None,
-            },
-        )
+        PubSinkBuilder::new(item_size).address(address).build()
     }
 }
 
@@ -46,7 +39,11 @@ impl AsyncKernel for PubSink {
 
         let n = i.len() / self.item_size;
         if n > 0 {
-            self.publisher.as_mut().unwrap().send(&*i, 0).unwrap();
+            send(
+                self.publisher.as_ref().context("no socket")?,
+                self.topic.as_deref(),
+                i,
+            )?;
             sio.input(0).consume(n);
         }
 
@@ -63,10 +60,7 @@ impl AsyncKernel for PubSink {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let context = zmq::Context::new();
-        let publisher = context.socket(zmq::PUB)?;
-        info!("SubSource Binding to {:?}", self.address);
-        publisher.bind(&self.address)?;
+        let publisher = self.config.bind(zmq::PUB)?;
         self.publisher = Some(publisher);
 
         Ok(())
@@ -75,23 +69,40 @@ impl AsyncKernel for PubSink {
 
 pub struct PubSinkBuilder {
     item_size: usize,
-    address: String,
+    config: SocketConfig,
+    topic: Option<String>,
 }
 
 impl PubSinkBuilder {
     pub fn new(item_size: usize) -> PubSinkBuilder {
         PubSinkBuilder {
             item_size,
-            address: "tcp://*:5555".into(),
+            config: SocketConfig::new("tcp://*:5555"),
+            topic: None,
         }
     }
 
-    pub fn address(mut self, address: &str) -> PubSinkBuilder {
-        self.address = address.to_string();
+    // send the topic as first frame of each message
+    pub fn topic(mut self, topic: &str) -> PubSinkBuilder {
+        self.topic = Some(topic.to_string());
         self
     }
 
     pub fn build(&mut self) -> Block {
-        PubSink::new(self.item_size, &*self.address)
+        Block::new_async(
+            BlockMetaBuilder::new("PubSink").blocking().build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            PubSink {
+                item_size: self.item_size,
+                config: self.config.clone(),
+                topic: self.topic.clone(),
+                publisher: None,
+            },
+        )
     }
 }
+
+socket_options!(PubSinkBuilder);
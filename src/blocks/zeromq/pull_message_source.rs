@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use crate::blocks::zeromq::format::{decode, recv};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Posts each received ZeroMQ message as Pmt on the "out" message output.
+// Messages that cannot be deserialized are dropped.
+pub struct PullMessageSource {
+    config: SocketConfig,
+    receiver: Option<zmq::Socket>,
+    n_received: usize,
+}
+
+impl PullMessageSource {
+    pub fn new(address: &str) -> Block {
+        PullMessageSourceBuilder::new().address(address).build()
+    }
+
+    pub fn n_received(&self) -> usize {
+        self.n_received
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for PullMessageSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(data) = recv(self.receiver.as_ref().context("no socket")?)? {
+            match decode(&data, self.config.format) {
+                Ok(p) => {
+                    debug!("PullMessageSource received {:?}", p);
+                    self.n_received += 1;
+                    mio.post(0, p).await;
+                }
+                Err(e) => warn!("PullMessageSource: {:?}", e),
+            }
+        }
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let receiver = self.config.connect(zmq::PULL)?;
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+}
+
+pub struct PullMessageSourceBuilder {
+    config: SocketConfig,
+}
+
+impl PullMessageSourceBuilder {
+    pub fn new() -> PullMessageSourceBuilder {
+        PullMessageSourceBuilder {
+            config: SocketConfig::new("tcp://127.0.0.1:5555"),
+        }
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("PullMessageSource")
+                .blocking()
+                .build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            PullMessageSource {
+                config: self.config.clone(),
+                receiver: None,
+                n_received: 0,
+            },
+        )
+    }
+}
+
+socket_options!(PullMessageSourceBuilder, format);
+
+impl Default for PullMessageSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,107 @@
+use anyhow::{Context, Result};
+use log::debug;
+
+use crate::blocks::zeromq::format::recv;
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Messages from all connected PUSH sockets are fair-queued. Of multipart
+// messages, only the last frame is forwarded.
+pub struct PullSource {
+    item_size: usize,
+    config: SocketConfig,
+    receiver: Option<zmq::Socket>,
+    pending: Vec<u8>,
+}
+
+impl PullSource {
+    pub fn new(item_size: usize, address: &str) -> Block {
+        PullSourceBuilder::new(item_size).address(address).build()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for PullSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.pending.len() < self.item_size {
+            if let Some(data) = recv(self.receiver.as_ref().context("no socket")?)? {
+                debug!("PullSource received {} bytes", data.len());
+                self.pending.extend_from_slice(&data);
+            }
+        }
+
+        let o = sio.output(0).slice::<u8>();
+        debug_assert_eq!(o.len() % self.item_size, 0);
+        let n = std::cmp::min(o.len(), self.pending.len()) / self.item_size;
+        if n > 0 {
+            let bytes = n * self.item_size;
+            o[..bytes].copy_from_slice(&self.pending[..bytes]);
+            self.pending.drain(..bytes);
+            sio.output(0).produce(n);
+        }
+
+        if self.pending.len() < self.item_size {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("PullSource Init");
+
+        let receiver = self.config.connect(zmq::PULL)?;
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+}
+
+pub struct PullSourceBuilder {
+    item_size: usize,
+    config: SocketConfig,
+}
+
+impl PullSourceBuilder {
+    pub fn new(item_size: usize) -> PullSourceBuilder {
+        PullSourceBuilder {
+            item_size,
+            config: SocketConfig::new("tcp://127.0.0.1:5555"),
+        }
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("PullSource").blocking().build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            PullSource {
+                item_size: self.item_size,
+                config: self.config.clone(),
+                receiver: None,
+                pending: Vec::new(),
+            },
+        )
+    }
+}
+
+socket_options!(PullSourceBuilder);
@@ -0,0 +1,97 @@
+use anyhow::{Context, Result};
+use log::warn;
+
+use crate::blocks::zeromq::format::{encode, send};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// Sends each Pmt on the "in" message input as one ZeroMQ message to the
+// connected PULL sockets (round-robin).
+pub struct PushMessageSink {
+    config: SocketConfig,
+    sender: Option<zmq::Socket>,
+    n_sent: usize,
+}
+
+impl PushMessageSink {
+    pub fn new(address: &str) -> Block {
+        PushMessageSinkBuilder::new().address(address).build()
+    }
+
+    pub fn n_sent(&self) -> usize {
+        self.n_sent
+    }
+
+    fn handler(
+        &mut self,
+        _mio: &mut MessageIo<PushMessageSink>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match encode(&p, self.config.format) {
+            Ok(data) => {
+                send(self.sender.as_ref().context("no socket")?, None, &data)?;
+                self.n_sent += 1;
+            }
+            Err(e) => warn!("PushMessageSink: {:?}", e),
+        }
+        Ok(Pmt::Null)
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for PushMessageSink {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let sender = self.config.bind(zmq::PUSH)?;
+        self.sender = Some(sender);
+        Ok(())
+    }
+}
+
+pub struct PushMessageSinkBuilder {
+    config: SocketConfig,
+}
+
+impl PushMessageSinkBuilder {
+    pub fn new() -> PushMessageSinkBuilder {
+        PushMessageSinkBuilder {
+            config: SocketConfig::new("tcp://*:5555"),
+        }
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("PushMessageSink").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input("in", PushMessageSink::handler)
+                .build(),
+            PushMessageSink {
+                config: self.config.clone(),
+                sender: None,
+                n_sent: 0,
+            },
+        )
+    }
+}
+
+socket_options!(PushMessageSinkBuilder, format);
+
+impl Default for PushMessageSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
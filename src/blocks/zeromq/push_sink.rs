@@ -0,0 +1,94 @@
+use anyhow::{Context, Result};
+
+use crate::blocks::zeromq::format::send;
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+pub struct PushSink {
+    item_size: usize,
+    config: SocketConfig,
+    sender: Option<zmq::Socket>,
+}
+
+impl PushSink {
+    pub fn new(item_size: usize, address: &str) -> Block {
+        PushSinkBuilder::new(item_size).address(address).build()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for PushSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        debug_assert_eq!(i.len() % self.item_size, 0);
+
+        let n = i.len() / self.item_size;
+        if n > 0 {
+            send(self.sender.as_ref().context("no socket")?, None, i)?;
+            sio.input(0).consume(n);
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let sender = self.config.bind(zmq::PUSH)?;
+        self.sender = Some(sender);
+
+        Ok(())
+    }
+}
+
+pub struct PushSinkBuilder {
+    item_size: usize,
+    config: SocketConfig,
+}
+
+impl PushSinkBuilder {
+    pub fn new(item_size: usize) -> PushSinkBuilder {
+        PushSinkBuilder {
+            item_size,
+            config: SocketConfig::new("tcp://*:5555"),
+        }
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("PushSink").blocking().build(),
+            StreamIoBuilder::new()
+                .add_input("in", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            PushSink {
+                item_size: self.item_size,
+                config: self.config.clone(),
+                sender: None,
+            },
+        )
+    }
+}
+
+socket_options!(PushSinkBuilder);
@@ -0,0 +1,158 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use crate::blocks::zeromq::format::{decode, encode, recv, send};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Receives requests from REQ sockets and posts them on the "out" message output.
+// By default, each request is acknowledged right away with Pmt::Null. With
+// `reply`, the block waits for the reply on the "reply" message input before it
+// receives the next request.
+pub struct RepMessageServer {
+    config: SocketConfig,
+    reply: bool,
+    socket: Option<zmq::Socket>,
+    waiting: bool,
+    n_received: usize,
+}
+
+impl RepMessageServer {
+    pub fn new(address: &str) -> Block {
+        RepMessageServerBuilder::new().address(address).build()
+    }
+
+    pub fn n_received(&self) -> usize {
+        self.n_received
+    }
+
+    fn send_reply(&mut self, p: &Pmt) -> Result<()> {
+        send(
+            self.socket.as_ref().context("no socket")?,
+            None,
+            &encode(p, self.config.format)?,
+        )?;
+        self.waiting = false;
+        Ok(())
+    }
+
+    fn reply_handler(
+        &mut self,
+        _mio: &mut MessageIo<RepMessageServer>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if self.waiting {
+            self.send_reply(&p)?;
+        } else {
+            warn!("RepMessageServer: reply {:?} without request", p);
+        }
+        Ok(Pmt::Null)
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for RepMessageServer {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // wait for the reply handler
+        if self.waiting {
+            return Ok(());
+        }
+
+        if let Some(data) = recv(self.socket.as_ref().context("no socket")?)? {
+            self.waiting = true;
+            match decode(&data, self.config.format) {
+                Ok(p) => {
+                    debug!("RepMessageServer received {:?}", p);
+                    self.n_received += 1;
+                    mio.post(0, p).await;
+                    if !self.reply {
+                        self.send_reply(&Pmt::Null)?;
+                    }
+                }
+                Err(e) => {
+                    // the REQ socket expects a reply in any case
+                    warn!("RepMessageServer: {:?}", e);
+                    self.send_reply(&Pmt::Null)?;
+                }
+            }
+        }
+
+        if !self.waiting {
+            io.call_again = true;
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = self.config.bind(zmq::REP)?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+}
+
+pub struct RepMessageServerBuilder {
+    config: SocketConfig,
+    reply: bool,
+}
+
+impl RepMessageServerBuilder {
+    pub fn new() -> RepMessageServerBuilder {
+        RepMessageServerBuilder {
+            config: SocketConfig::new("tcp://*:5555"),
+            reply: false,
+        }
+    }
+
+    // wait for the reply on the "reply" message input
+    pub fn reply(mut self, reply: bool) -> RepMessageServerBuilder {
+        self.reply = reply;
+        self
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("RepMessageServer").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input("reply", RepMessageServer::reply_handler)
+                .add_output("out")
+                .build(),
+            RepMessageServer {
+                config: self.config.clone(),
+                reply: self.reply,
+                socket: None,
+                waiting: false,
+                n_received: 0,
+            },
+        )
+    }
+}
+
+socket_options!(RepMessageServerBuilder, format);
+
+impl Default for RepMessageServerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::{Context, Result};
+use futures::FutureExt;
+use log::warn;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::blocks::zeromq::format::{decode, encode, recv, send};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// Sends each Pmt on the "in" message input as request to a REP socket. The
+// reply is posted on the "out" message output and returned by the handler, so
+// it can also be used through `FlowgraphHandle::callback`. If no reply arrives
+// within the timeout, the handler returns Pmt::Null. Requests or replies that
+// cannot be (de)serialized and socket errors are returned as errors.
+pub struct ReqMessageClient {
+    config: SocketConfig,
+    timeout_ms: i32,
+    socket: Option<zmq::Socket>,
+}
+
+impl ReqMessageClient {
+    pub fn new(address: &str) -> Block {
+        ReqMessageClientBuilder::new().address(address).build()
+    }
+
+    fn request(&mut self, p: &Pmt) -> Result<Option<Pmt>> {
+        let socket = self.socket.as_ref().context("no socket")?;
+        send(socket, None, &encode(p, self.config.format)?)?;
+        match recv(socket)? {
+            Some(data) => Ok(Some(decode(&data, self.config.format)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn handler<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match self.request(&p)? {
+                Some(reply) => {
+                    mio.post(0, reply.clone()).await;
+                    Ok(reply)
+                }
+                None => {
+                    warn!("ReqMessageClient: no reply to {:?}", p);
+                    Ok(Pmt::Null)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for ReqMessageClient {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = self.config.connect(zmq::REQ)?;
+        socket.set_rcvtimeo(self.timeout_ms)?;
+        // allow a new request after a missing reply
+        socket.set_req_relaxed(true)?;
+        socket.set_req_correlate(true)?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+}
+
+pub struct ReqMessageClientBuilder {
+    config: SocketConfig,
+    timeout_ms: i32,
+}
+
+impl ReqMessageClientBuilder {
+    pub fn new() -> ReqMessageClientBuilder {
+        ReqMessageClientBuilder {
+            config: SocketConfig::new("tcp://127.0.0.1:5555"),
+            timeout_ms: 1000,
+        }
+    }
+
+    // time to wait for a reply in milliseconds
+    pub fn timeout(mut self, timeout_ms: i32) -> ReqMessageClientBuilder {
+        self.timeout_ms = timeout_ms;
+        self
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("ReqMessageClient").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_async_input("in", ReqMessageClient::handler)
+                .add_output("out")
+                .build(),
+            ReqMessageClient {
+                config: self.config.clone(),
+                timeout_ms: self.timeout_ms,
+                socket: None,
+            },
+        )
+    }
+}
+
+socket_options!(ReqMessageClientBuilder, format);
+
+impl Default for ReqMessageClientBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,55 @@
+use anyhow::{Context, Result};
+use log::info;
+use once_cell::sync::Lazy;
+
+use crate::blocks::zeromq::format::{Format, RECV_TIMEOUT_MS};
+
+// one context for all blocks, so that inproc:// endpoints connect across blocks
+static CONTEXT: Lazy<zmq::Context> = Lazy::new(zmq::Context::new);
+
+// Socket options of the ZeroMQ blocks, set through the builder methods that
+// `socket_options!` adds. The format only applies to the message blocks.
+#[derive(Debug, Clone)]
+pub(crate) struct SocketConfig {
+    pub(crate) address: String,
+    pub(crate) hwm: Option<i32>,
+    pub(crate) format: Format,
+}
+
+impl SocketConfig {
+    pub(crate) fn new(address: &str) -> SocketConfig {
+        SocketConfig {
+            address: address.to_string(),
+            hwm: None,
+            format: Format::Json,
+        }
+    }
+
+    fn socket(&self, kind: zmq::SocketType) -> Result<zmq::Socket> {
+        let socket = CONTEXT.socket(kind)?;
+        if let Some(hwm) = self.hwm {
+            socket.set_sndhwm(hwm)?;
+            socket.set_rcvhwm(hwm)?;
+        }
+        socket.set_rcvtimeo(RECV_TIMEOUT_MS)?;
+        Ok(socket)
+    }
+
+    pub(crate) fn bind(&self, kind: zmq::SocketType) -> Result<zmq::Socket> {
+        let socket = self.socket(kind)?;
+        info!("zeromq: binding {:?} socket to {:?}", kind, self.address);
+        socket
+            .bind(&self.address)
+            .with_context(|| format!("zeromq: cannot bind {:?}", self.address))?;
+        Ok(socket)
+    }
+
+    pub(crate) fn connect(&self, kind: zmq::SocketType) -> Result<zmq::Socket> {
+        let socket = self.socket(kind)?;
+        info!("zeromq: connecting {:?} socket to {:?}", kind, self.address);
+        socket
+            .connect(&self.address)
+            .with_context(|| format!("zeromq: cannot connect to {:?}", self.address))?;
+        Ok(socket)
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use log::{debug, warn};
+
+use crate::blocks::zeromq::format::{decode, recv};
+use crate::blocks::zeromq::socket::SocketConfig;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+// Posts each received ZeroMQ message as Pmt on the "out" message output.
+// Messages that cannot be deserialized are dropped.
+pub struct SubMessageSource {
+    config: SocketConfig,
+    topics: Vec<String>,
+    receiver: Option<zmq::Socket>,
+    n_received: usize,
+}
+
+impl SubMessageSource {
+    pub fn new(address: &str) -> Block {
+        SubMessageSourceBuilder::new().address(address).build()
+    }
+
+    pub fn n_received(&self) -> usize {
+        self.n_received
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for SubMessageSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(data) = recv(self.receiver.as_ref().context("no socket")?)? {
+            match decode(&data, self.config.format) {
+                Ok(p) => {
+                    debug!("SubMessageSource received {:?}", p);
+                    self.n_received += 1;
+                    mio.post(0, p).await;
+                }
+                Err(e) => warn!("SubMessageSource: {:?}", e),
+            }
+        }
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let receiver = self.config.connect(zmq::SUB)?;
+        if self.topics.is_empty() {
+            receiver.set_subscribe(b"")?;
+        }
+        for t in self.topics.iter() {
+            receiver.set_subscribe(t.as_bytes())?;
+        }
+        self.receiver = Some(receiver);
+        Ok(())
+    }
+}
+
+pub struct SubMessageSourceBuilder {
+    config: SocketConfig,
+    topics: Vec<String>,
+}
+
+impl SubMessageSourceBuilder {
+    pub fn new() -> SubMessageSourceBuilder {
+        SubMessageSourceBuilder {
+            config: SocketConfig::new("tcp://127.0.0.1:5555"),
+            topics: Vec::new(),
+        }
+    }
+
+    // subscribe to a topic prefix, can be called multiple times
+    pub fn subscribe(mut self, topic: &str) -> SubMessageSourceBuilder {
+        self.topics.push(topic.to_string());
+        self
+    }
+
+    pub fn build(&mut self) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("SubMessageSource").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            SubMessageSource {
+                config: self.config.clone(),
+                topics: self.topics.clone(),
+                receiver: None,
+                n_received: 0,
+            },
+        )
+    }
+}
+
+socket_options!(SubMessageSourceBuilder, format);
+
+impl Default for SubMessageSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,7 +1,8 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use log::debug;
-use log::info;
 
+use crate::blocks::zeromq::format::recv;
+use crate::blocks::zeromq::socket::SocketConfig;
 use crate::runtime::AsyncKernel;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
@@ -12,24 +13,19 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+// Messages with a topic frame are matched against the subscriptions, and only
+// the last frame is forwarded. Without subscriptions, all messages are received.
 pub struct SubSource {
     item_size: usize,
-    address: String,
+    config: SocketConfig,
+    topics: Vec<String>,
     receiver: Option<zmq::Socket>,
+    pending: Vec<u8>,
 }
 
 impl SubSource {
     pub fn new(item_size: usize, address: &str) -> Block {
-        Block::new_async(
-            BlockMetaBuilder::new("SubSource").blocking().build(),
-            StreamIoBuilder::new().add_output("out", item_size).build(),
-            MessageIoBuilder::new().build(),
-            SubSource {
-                item_size,
-                address: address.to_string(),
-                receiver: None,
-            },
-        )
+        SubSourceBuilder::new(item_size).address(address).build()
     }
 }
 
@@ -37,19 +33,31 @@ impl SubSource {
 impl AsyncKernel for SubSource {
     async fn work(
         &mut self,
-        _io: &mut WorkIo,
+        io: &mut WorkIo,
         sio: &mut StreamIo,
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        if self.pending.len() < self.item_size {
+            if let Some(data) = recv(self.receiver.as_ref().context("no socket")?)? {
+                debug!("SubSource received {} bytes", data.len());
+                self.pending.extend_from_slice(&data);
+            }
+        }
+
         let o = sio.output(0).slice::<u8>();
-        let mut n_bytes = self.receiver.as_mut().unwrap().recv_into(o, 0)?;
-        n_bytes = std::cmp::min(n_bytes, o.len());
         debug_assert_eq!(o.len() % self.item_size, 0);
-        let n = n_bytes / self.item_size;
-        debug!("SubSource received {}", n);
-        sio.output(0).produce(n);
+        let n = std::cmp::min(o.len(), self.pending.len()) / self.item_size;
+        if n > 0 {
+            let bytes = n * self.item_size;
+            o[..bytes].copy_from_slice(&self.pending[..bytes]);
+            self.pending.drain(..bytes);
+            sio.output(0).produce(n);
+        }
 
+        if self.pending.len() < self.item_size {
+            io.call_again = true;
+        }
         Ok(())
     }
 
@@ -61,11 +69,13 @@ impl AsyncKernel for SubSource {
     ) -> Result<()> {
         debug!("SubSource Init");
 
-        let context = zmq::Context::new();
-        let receiver = context.socket(zmq::SUB).unwrap();
-        info!("SubSource Connecting to {:?}", self.address);
-        receiver.connect(&self.address)?;
-        receiver.set_subscribe(b"")?;
+        let receiver = self.config.connect(zmq::SUB)?;
+        if self.topics.is_empty() {
+            receiver.set_subscribe(b"")?;
+        }
+        for t in self.topics.iter() {
+            receiver.set_subscribe(t.as_bytes())?;
+        }
         self.receiver = Some(receiver);
         Ok(())
     }
@@ -73,23 +83,41 @@ impl AsyncKernel for SubSource {
 
 pub struct SubSourceBuilder {
     item_size: usize,
-    address: String,
+    config: SocketConfig,
+    topics: Vec<String>,
 }
 
 impl SubSourceBuilder {
     pub fn new(item_size: usize) -> SubSourceBuilder {
         SubSourceBuilder {
             item_size,
-            address: "tcp://*:5555".into(),
+            config: SocketConfig::new("tcp://127.0.0.1:5555"),
+            topics: Vec::new(),
         }
     }
 
-    pub fn address(mut self, address: &str) -> SubSourceBuilder {
-        self.address = address.to_string();
+    // subscribe to a topic prefix, can be called multiple times
+    pub fn subscribe(mut self, topic: &str) -> SubSourceBuilder {
+        self.topics.push(topic.to_string());
         self
     }
 
     pub fn build(&mut self) -> Block {
-        SubSource::new(self.item_size, &*self.address)
+        Block::new_async(
+            BlockMetaBuilder::new("SubSource").blocking().build(),
+            StreamIoBuilder::new()
+                .add_output("out", self.item_size)
+                .build(),
+            MessageIoBuilder::new().build(),
+            SubSource {
+                item_size: self.item_size,
+                config: self.config.clone(),
+                topics: self.topics.clone(),
+                receiver: None,
+                pending: Vec::new(),
+            },
+        )
     }
 }
+
+socket_options!(SubSourceBuilder);
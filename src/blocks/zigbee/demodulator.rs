@@ -0,0 +1,277 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::mem::size_of;
+
+use crate::blocks::zigbee::modulator::waveform;
+use crate::blocks::zigbee::SAMPLES_PER_CHIP;
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const SYMBOL_LEN: usize = 32 * SAMPLES_PER_CHIP;
+const MAX_PREAMBLE_SYMBOLS: usize = 12;
+
+enum State {
+    Search,
+    Preamble {
+        n_zeros: usize,
+    },
+    Sfd,
+    Header {
+        low: Option<u8>,
+    },
+    Payload {
+        len: usize,
+        low: Option<u8>,
+        data: Vec<u8>,
+    },
+}
+
+pub struct Demodulator {
+    threshold: f32,
+    symbols: Vec<Vec<Complex<f32>>>,
+    energy: f32,
+    buf: Vec<Complex<f32>>,
+    pos: usize,
+    phase: Complex<f32>,
+    state: State,
+    n_received: u64,
+}
+
+impl Demodulator {
+    pub fn new(threshold: f32) -> Block {
+        let symbols: Vec<Vec<Complex<f32>>> = (0..16u8)
+            .map(|s| waveform(&[s])[0..SYMBOL_LEN].to_vec())
+            .collect();
+        let energy = symbols[0].iter().map(|x| x.norm_sqr()).sum();
+
+        Block::new_async(
+            BlockMetaBuilder::new("Demodulator").build(),
+            StreamIoBuilder::new()
+                .add_input("in", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Demodulator {
+                threshold,
+                symbols,
+                energy,
+                buf: Vec::new(),
+                pos: 0,
+                phase: Complex::new(1.0, 0.0),
+                state: State::Search,
+                n_received: 0,
+            },
+        )
+    }
+
+    pub fn received(&self) -> u64 {
+        self.n_received
+    }
+
+    fn correlate(&self, pos: usize, symbol: usize) -> Complex<f32> {
+        self.buf[pos..pos + SYMBOL_LEN]
+            .iter()
+            .zip(self.symbols[symbol].iter())
+            .map(|(x, r)| x * r.conj())
+            .sum()
+    }
+
+    // normalized correlation with the preamble symbol
+    fn detect(&self, pos: usize) -> (f32, Complex<f32>) {
+        let e: f32 = self.buf[pos..pos + SYMBOL_LEN]
+            .iter()
+            .map(|x| x.norm_sqr())
+            .sum();
+        if e < f32::EPSILON {
+            return (0.0, Complex::new(0.0, 0.0));
+        }
+        let c = self.correlate(pos, 0);
+        (c.norm_sqr() / (e * self.energy), c)
+    }
+
+    // coherent decision, tracking the carrier phase on the decided symbol
+    fn decide(&mut self) -> u8 {
+        let mut symbol = 0;
+        let mut best = Complex::new(f32::MIN, 0.0);
+
+        for s in 0..self.symbols.len() {
+            let c = self.correlate(self.pos, s) * self.phase.conj();
+            if c.re > best.re {
+                symbol = s;
+                best = c;
+            }
+        }
+
+        if best.norm() > 0.0 {
+            self.phase *= best / best.norm();
+            self.phase /= self.phase.norm();
+        }
+        self.pos += SYMBOL_LEN;
+        symbol as u8
+    }
+
+    fn process(&mut self, frames: &mut Vec<Vec<u8>>) {
+        loop {
+            if let State::Search = self.state {
+                if self.pos + 2 * SAMPLES_PER_CHIP + SYMBOL_LEN > self.buf.len() {
+                    break;
+                }
+
+                let (n, _) = self.detect(self.pos);
+                if n < self.threshold {
+                    self.pos += 1;
+                    continue;
+                }
+
+                let mut best = (n, Complex::new(0.0, 0.0), self.pos);
+                for p in self.pos..self.pos + 2 * SAMPLES_PER_CHIP {
+                    let (n, c) = self.detect(p);
+                    if n >= best.0 {
+                        best = (n, c, p);
+                    }
+                }
+                self.pos = best.2;
+                self.phase = best.1 / best.1.norm();
+                self.state = State::Preamble { n_zeros: 0 };
+                continue;
+            }
+
+            if self.pos + SYMBOL_LEN > self.buf.len() {
+                break;
+            }
+            let s = self.decide();
+
+            self.state = match std::mem::replace(&mut self.state, State::Search) {
+                State::Preamble { n_zeros } => match s {
+                    0 if n_zeros < MAX_PREAMBLE_SYMBOLS => State::Preamble {
+                        n_zeros: n_zeros + 1,
+                    },
+                    0x7 => State::Sfd,
+                    _ => State::Search,
+                },
+                State::Sfd => {
+                    if s == 0xa {
+                        State::Header { low: None }
+                    } else {
+                        State::Search
+                    }
+                }
+                State::Header { low: None } => State::Header { low: Some(s) },
+                State::Header { low: Some(l) } => {
+                    let len = ((s << 4) | l) as usize & 0x7f;
+                    if len == 0 {
+                        State::Search
+                    } else {
+                        State::Payload {
+                            len,
+                            low: None,
+                            data: Vec::with_capacity(len),
+                        }
+                    }
+                }
+                State::Payload {
+                    len,
+                    low: None,
+                    data,
+                } => State::Payload {
+                    len,
+                    low: Some(s),
+                    data,
+                },
+                State::Payload {
+                    len,
+                    low: Some(l),
+                    mut data,
+                } => {
+                    data.push((s << 4) | l);
+                    if data.len() == len {
+                        debug!("zigbee demodulator: received frame ({} bytes)", len);
+                        self.n_received += 1;
+                        frames.push(data);
+                        State::Search
+                    } else {
+                        State::Payload {
+                            len,
+                            low: None,
+                            data,
+                        }
+                    }
+                }
+                State::Search => unreachable!(),
+            };
+        }
+
+        self.buf.drain(0..self.pos);
+        self.pos = 0;
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Demodulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex<f32>>();
+        self.buf.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        let mut frames = Vec::new();
+        self.process(&mut frames);
+
+        for f in frames.drain(..) {
+            mio.post(0, Pmt::Blob(f)).await;
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("zigbee demodulator: n_received {}", self.n_received);
+        Ok(())
+    }
+}
+
+pub struct DemodulatorBuilder {
+    threshold: f32,
+}
+
+impl DemodulatorBuilder {
+    pub fn new() -> DemodulatorBuilder {
+        DemodulatorBuilder { threshold: 0.5 }
+    }
+
+    pub fn threshold(mut self, threshold: f32) -> DemodulatorBuilder {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Demodulator::new(self.threshold)
+    }
+}
+
+impl Default for DemodulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,233 @@
+use anyhow::Result;
+use futures::FutureExt;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+
+// CRC-16 ITU-T as used for the 802.15.4 FCS (reflected, init 0, no final xor)
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0u16;
+    for b in data {
+        crc ^= *b as u16;
+        for _ in 0..8 {
+            if crc & 1 == 1 {
+                crc = (crc >> 1) ^ 0x8408;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    crc
+}
+
+fn address_len(mode: u16) -> usize {
+    match mode {
+        0b10 => 2,
+        0b11 => 8,
+        _ => 0,
+    }
+}
+
+// length of the MAC header, derived from the frame control field
+fn header_len(frame: &[u8]) -> usize {
+    let fcf = u16::from_le_bytes([frame[0], frame[1]]);
+    let pan_id_compression = fcf & (1 << 6) != 0;
+    let dst = address_len((fcf >> 10) & 0b11);
+    let src = address_len((fcf >> 14) & 0b11);
+
+    let mut len = 3;
+    if dst > 0 {
+        len += 2 + dst;
+    }
+    if src > 0 {
+        len += src;
+        if !pan_id_compression || dst == 0 {
+            len += 2;
+        }
+    }
+    len
+}
+
+pub struct Mac {
+    pan_id: u16,
+    src_address: u16,
+    dst_address: u16,
+    sequence_number: u8,
+    n_sent: u64,
+    n_received: u64,
+    n_crc_errors: u64,
+}
+
+impl Mac {
+    pub fn new(pan_id: u16, src_address: u16, dst_address: u16) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Mac").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_output("tx")
+                .add_output("rx")
+                .add_async_input("tx", Mac::transmit)
+                .add_async_input("rx", Mac::receive)
+                .build(),
+            Mac {
+                pan_id,
+                src_address,
+                dst_address,
+                sequence_number: 0,
+                n_sent: 0,
+                n_received: 0,
+                n_crc_errors: 0,
+            },
+        )
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.n_sent
+    }
+
+    pub fn received(&self) -> u64 {
+        self.n_received
+    }
+
+    pub fn crc_errors(&self) -> u64 {
+        self.n_crc_errors
+    }
+
+    // data frame, short addresses, pan id compression
+    fn frame(&mut self, payload: &[u8]) -> Vec<u8> {
+        let mut frame = Vec::with_capacity(payload.len() + 11);
+        frame.extend_from_slice(&0x8841u16.to_le_bytes());
+        frame.push(self.sequence_number);
+        frame.extend_from_slice(&self.pan_id.to_le_bytes());
+        frame.extend_from_slice(&self.dst_address.to_le_bytes());
+        frame.extend_from_slice(&self.src_address.to_le_bytes());
+        frame.extend_from_slice(payload);
+        let crc = crc16(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes());
+
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        frame
+    }
+
+    fn transmit<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            match p {
+                Pmt::Blob(payload) => {
+                    let frame = self.frame(&payload);
+                    self.n_sent += 1;
+                    mio.post(0, Pmt::Blob(frame)).await;
+                }
+                Pmt::String(s) => {
+                    let frame = self.frame(s.as_bytes());
+                    self.n_sent += 1;
+                    mio.post(0, Pmt::Blob(frame)).await;
+                }
+                p => warn!("zigbee mac: cannot transmit {:?}", p),
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+
+    fn receive<'a>(
+        &'a mut self,
+        mio: &'a mut MessageIo<Self>,
+        _meta: &'a mut BlockMeta,
+        p: Pmt,
+    ) -> Pin<Box<dyn Future<Output = Result<Pmt>> + Send + 'a>> {
+        async move {
+            if let Pmt::Blob(frame) = p {
+                if frame.len() < 5 || crc16(&frame) != 0 {
+                    debug!("zigbee mac: crc error");
+                    self.n_crc_errors += 1;
+                    return Ok(Pmt::Null);
+                }
+
+                let header = header_len(&frame);
+                if header + 2 > frame.len() {
+                    debug!("zigbee mac: frame too short");
+                    return Ok(Pmt::Null);
+                }
+
+                self.n_received += 1;
+                mio.post(1, Pmt::Blob(frame[header..frame.len() - 2].to_vec()))
+                    .await;
+            } else {
+                warn!("zigbee mac: expected Pmt::Blob, got {:?}", p);
+            }
+            Ok(Pmt::Null)
+        }
+        .boxed()
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Mac {
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!(
+            "zigbee mac: n_sent {}, n_received {}, n_crc_errors {}",
+            self.n_sent, self.n_received, self.n_crc_errors
+        );
+        Ok(())
+    }
+}
+
+pub struct MacBuilder {
+    pan_id: u16,
+    src_address: u16,
+    dst_address: u16,
+}
+
+impl MacBuilder {
+    pub fn new() -> MacBuilder {
+        MacBuilder {
+            pan_id: 0x1aaa,
+            src_address: 0x3344,
+            dst_address: 0xffff,
+        }
+    }
+
+    pub fn pan_id(mut self, pan_id: u16) -> MacBuilder {
+        self.pan_id = pan_id;
+        self
+    }
+
+    pub fn src_address(mut self, address: u16) -> MacBuilder {
+        self.src_address = address;
+        self
+    }
+
+    pub fn dst_address(mut self, address: u16) -> MacBuilder {
+        self.dst_address = address;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        Mac::new(self.pan_id, self.src_address, self.dst_address)
+    }
+}
+
+impl Default for MacBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
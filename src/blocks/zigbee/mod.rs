@@ -0,0 +1,6 @@
+mod demodulator;
+pub use demodulator::{Demodulator, DemodulatorBuilder};
+mod mac;
+pub use mac::{crc16, Mac, MacBuilder};
+mod modulator;
+pub use modulator::{modulate, Modulator, ModulatorBuilder, CHIP_MAP, SAMPLES_PER_CHIP};
@@ -0,0 +1,183 @@
+use anyhow::{bail, Result};
+use num_complex::Complex;
+use std::cmp;
+use std::collections::VecDeque;
+use std::f32::consts::PI;
+use std::mem::size_of;
+
+use crate::runtime::AsyncKernel;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+pub const SAMPLES_PER_CHIP: usize = 2;
+
+// chip sequences of the 16 data symbols (802.15.4, 2.4 GHz O-QPSK), c0 is the MSB
+pub const CHIP_MAP: [u32; 16] = [
+    0xd9c3522e, 0xed9c3522, 0x2ed9c352, 0x22ed9c35, 0x522ed9c3, 0x3522ed9c, 0xc3522ed9, 0x9c3522ed,
+    0x8c96077b, 0xb8c96077, 0x7b8c9607, 0x77b8c960, 0x077b8c96, 0x6077b8c9, 0x96077b8c, 0xc96077b8,
+];
+
+const PREAMBLE: [u8; 4] = [0x00; 4];
+const SFD: u8 = 0xa7;
+const MAX_PSDU_SIZE: usize = 127;
+
+// O-QPSK with half-sine pulse shaping: even chips on I, odd chips on Q, each
+// pulse spans two chips and Q lags by one chip
+pub(super) fn waveform(symbols: &[u8]) -> Vec<Complex<f32>> {
+    let pulse_len = 2 * SAMPLES_PER_CHIP;
+    let mut out = vec![Complex::new(0.0, 0.0); (symbols.len() * 32 + 1) * SAMPLES_PER_CHIP];
+
+    for (i, s) in symbols.iter().enumerate() {
+        let chips = CHIP_MAP[*s as usize & 0x0f];
+        for c in 0..32 {
+            let v = if (chips >> (31 - c)) & 1 == 1 {
+                1.0
+            } else {
+                -1.0
+            };
+            let start = (i * 32 + c) * SAMPLES_PER_CHIP;
+            for k in 0..pulse_len {
+                let p = v * (PI * k as f32 / pulse_len as f32).sin();
+                if c % 2 == 0 {
+                    out[start + k].re += p;
+                } else {
+                    out[start + k].im += p;
+                }
+            }
+        }
+    }
+
+    out
+}
+
+pub fn modulate(psdu: &[u8]) -> Result<Vec<Complex<f32>>> {
+    if psdu.is_empty() || psdu.len() > MAX_PSDU_SIZE {
+        bail!("invalid psdu size ({} bytes)", psdu.len());
+    }
+
+    let mut ppdu = Vec::with_capacity(PREAMBLE.len() + 2 + psdu.len());
+    ppdu.extend_from_slice(&PREAMBLE);
+    ppdu.push(SFD);
+    ppdu.push(psdu.len() as u8);
+    ppdu.extend_from_slice(psdu);
+
+    // least significant nibble first
+    let symbols: Vec<u8> = ppdu.iter().flat_map(|b| [b & 0x0f, b >> 4]).collect();
+    Ok(waveform(&symbols))
+}
+
+pub struct Modulator {
+    samples: VecDeque<Complex<f32>>,
+    terminated: bool,
+    n_frames: u64,
+}
+
+impl Modulator {
+    pub fn new() -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Modulator")
+                .drain_on_terminate()
+                .build(),
+            StreamIoBuilder::new()
+                .add_output("out", size_of::<Complex<f32>>())
+                .build(),
+            MessageIoBuilder::new()
+                .add_sync_input("in", Self::handler)
+                .build(),
+            Modulator {
+                samples: VecDeque::new(),
+                terminated: false,
+                n_frames: 0,
+            },
+        )
+    }
+
+    fn handler(
+        &mut self,
+        _mio: &mut MessageIo<Modulator>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Blob(psdu) => match modulate(&psdu) {
+                Ok(s) => {
+                    self.samples.extend(s);
+                    self.n_frames += 1;
+                }
+                Err(e) => warn!("zigbee modulator: dropping frame, {}", e),
+            },
+            p => warn!("zigbee modulator: expected Pmt::Blob, got {:?}", p),
+        }
+        Ok(Pmt::Null)
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.n_frames
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Modulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        // upstream terminated, send out what is queued before finishing
+        if io.finished {
+            self.terminated = true;
+            io.finished = false;
+        }
+
+        let o = sio.output(0).slice::<Complex<f32>>();
+        let n = cmp::min(o.len(), self.samples.len());
+
+        for (v, s) in o.iter_mut().zip(self.samples.drain(0..n)) {
+            *v = s;
+        }
+        sio.output(0).produce(n);
+
+        if self.terminated && self.samples.is_empty() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        debug!("zigbee modulator: n_frames {}", self.n_frames);
+        Ok(())
+    }
+}
+
+pub struct ModulatorBuilder {}
+
+impl ModulatorBuilder {
+    pub fn new() -> ModulatorBuilder {
+        ModulatorBuilder {}
+    }
+
+    pub fn build(self) -> Block {
+        Modulator::new()
+    }
+}
+
+impl Default for ModulatorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
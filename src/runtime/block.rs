@@ -3,12 +3,17 @@ use std::any::Any;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
 
 use crate::runtime::BlockMeta;
 use crate::runtime::MessageInput;
+use crate::runtime::MessageInputMetrics;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageOutput;
+use crate::runtime::MessagePriority;
+use crate::runtime::MessageQueue;
 use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
 use crate::runtime::StreamInput;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamOutput;
@@ -103,6 +108,7 @@ pub trait AsyncBlockT: Send + Any {
     fn set_instance_name(&mut self, name: &str);
     fn type_name(&self) -> &str;
     fn is_blocking(&self) -> bool;
+    fn drains_on_terminate(&self) -> bool;
 
     // ##### KERNEL
     async fn work(&mut self, io: &mut WorkIo) -> Result<()>;
@@ -124,6 +130,13 @@ pub trait AsyncBlockT: Send + Any {
     // ##### MESSAGE IO
     fn message_input_is_async(&self, id: usize) -> bool;
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
+    fn message_input_names(&self) -> Vec<String>;
+    fn message_input_queue(&self, id: usize) -> Arc<MessageQueue>;
+    fn message_input_metrics(&self) -> Vec<MessageInputMetrics>;
+    fn message_input_is_batch(&self, id: usize) -> bool;
+    fn message_input_priority(&self, id: usize) -> MessagePriority;
+    fn message_input_kinds(&self, id: usize) -> Vec<PmtKind>;
+    fn max_messages_per_work(&self) -> Option<usize>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_outputs_mut(&mut self) -> &mut Vec<MessageOutput>;
     fn message_output(&self, id: usize) -> &MessageOutput;
@@ -131,6 +144,7 @@ pub trait AsyncBlockT: Send + Any {
     fn message_output_name_to_id(&self, name: &str) -> Option<usize>;
 
     fn call_sync_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt>;
+    fn call_batch_handler(&mut self, id: usize, p: Vec<Pmt>) -> Result<Pmt>;
     async fn call_async_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt>;
     async fn post(&mut self, id: usize, p: Pmt);
 }
@@ -146,6 +160,7 @@ pub trait SyncBlockT: Send + Any {
     fn set_instance_name(&mut self, name: &str);
     fn type_name(&self) -> &str;
     fn is_blocking(&self) -> bool;
+    fn drains_on_terminate(&self) -> bool;
 
     // ##### KERNEL
     fn work(&mut self, io: &mut WorkIo) -> Result<()>;
@@ -167,6 +182,13 @@ pub trait SyncBlockT: Send + Any {
     // ##### MESSAGE IO
     fn message_input_is_async(&self, id: usize) -> bool;
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
+    fn message_input_names(&self) -> Vec<String>;
+    fn message_input_queue(&self, id: usize) -> Arc<MessageQueue>;
+    fn message_input_metrics(&self) -> Vec<MessageInputMetrics>;
+    fn message_input_is_batch(&self, id: usize) -> bool;
+    fn message_input_priority(&self, id: usize) -> MessagePriority;
+    fn message_input_kinds(&self, id: usize) -> Vec<PmtKind>;
+    fn max_messages_per_work(&self) -> Option<usize>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_outputs_mut(&mut self) -> &mut Vec<MessageOutput>;
     fn message_output(&self, id: usize) -> &MessageOutput;
@@ -174,6 +196,7 @@ pub trait SyncBlockT: Send + Any {
     fn message_output_name_to_id(&self, name: &str) -> Option<usize>;
 
     fn call_sync_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt>;
+    fn call_batch_handler(&mut self, id: usize, p: Vec<Pmt>) -> Result<Pmt>;
     async fn call_async_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt>;
     async fn post(&mut self, id: usize, p: Pmt);
 }
@@ -208,6 +231,9 @@ impl<T: AsyncKernel + Send> AsyncBlockT for AsyncBlock<T> {
     fn is_blocking(&self) -> bool {
         self.meta.is_blocking()
     }
+    fn drains_on_terminate(&self) -> bool {
+        self.meta.drains_on_terminate()
+    }
 
     // ##### KERNEL
     async fn work(&mut self, io: &mut WorkIo) -> Result<()> {
@@ -265,6 +291,27 @@ impl<T: AsyncKernel + Send> AsyncBlockT for AsyncBlock<T> {
     fn message_input_name_to_id(&self, name: &str) -> Option<usize> {
         self.mio.input_name_to_id(name)
     }
+    fn message_input_names(&self) -> Vec<String> {
+        self.mio.input_names()
+    }
+    fn message_input_queue(&self, id: usize) -> Arc<MessageQueue> {
+        self.mio.input_queue(id).clone()
+    }
+    fn message_input_metrics(&self) -> Vec<MessageInputMetrics> {
+        self.mio.input_metrics()
+    }
+    fn message_input_is_batch(&self, id: usize) -> bool {
+        self.mio.input_is_batch(id)
+    }
+    fn message_input_priority(&self, id: usize) -> MessagePriority {
+        self.mio.input_priority(id)
+    }
+    fn message_input_kinds(&self, id: usize) -> Vec<PmtKind> {
+        self.mio.input_kinds(id).to_vec()
+    }
+    fn max_messages_per_work(&self) -> Option<usize> {
+        self.mio.max_messages_per_work()
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.mio.outputs()
     }
@@ -291,10 +338,18 @@ impl<T: AsyncKernel + Send> AsyncBlockT for AsyncBlock<T> {
     fn call_sync_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt> {
         let h = match self.mio.input(id) {
             MessageInput::Sync(t) => t.get_handler(),
+            MessageInput::Batch(_) => return self.call_batch_handler(id, vec![p]),
             _ => panic!("message handler is not sync!"),
         };
         (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p)
     }
+    fn call_batch_handler(&mut self, id: usize, p: Vec<Pmt>) -> Result<Pmt> {
+        let h = match self.mio.input(id) {
+            MessageInput::Batch(t) => t.get_handler(),
+            _ => panic!("message handler is not batch!"),
+        };
+        (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p)
+    }
     async fn post(&mut self, id: usize, p: Pmt) {
         self.mio.post(id, p).await;
     }
@@ -330,6 +385,9 @@ impl<T: SyncKernel + Send> SyncBlockT for SyncBlock<T> {
     fn is_blocking(&self) -> bool {
         self.meta.is_blocking()
     }
+    fn drains_on_terminate(&self) -> bool {
+        self.meta.drains_on_terminate()
+    }
 
     // ##### KERNEL
     fn work(&mut self, io: &mut WorkIo) -> Result<()> {
@@ -386,6 +444,27 @@ impl<T: SyncKernel + Send> SyncBlockT for SyncBlock<T> {
     fn message_input_name_to_id(&self, name: &str) -> Option<usize> {
         self.mio.input_name_to_id(name)
     }
+    fn message_input_names(&self) -> Vec<String> {
+        self.mio.input_names()
+    }
+    fn message_input_queue(&self, id: usize) -> Arc<MessageQueue> {
+        self.mio.input_queue(id).clone()
+    }
+    fn message_input_metrics(&self) -> Vec<MessageInputMetrics> {
+        self.mio.input_metrics()
+    }
+    fn message_input_is_batch(&self, id: usize) -> bool {
+        self.mio.input_is_batch(id)
+    }
+    fn message_input_priority(&self, id: usize) -> MessagePriority {
+        self.mio.input_priority(id)
+    }
+    fn message_input_kinds(&self, id: usize) -> Vec<PmtKind> {
+        self.mio.input_kinds(id).to_vec()
+    }
+    fn max_messages_per_work(&self) -> Option<usize> {
+        self.mio.max_messages_per_work()
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.mio.outputs()
     }
@@ -412,10 +491,18 @@ impl<T: SyncKernel + Send> SyncBlockT for SyncBlock<T> {
     fn call_sync_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt> {
         let h = match self.mio.input(id) {
             MessageInput::Sync(t) => t.get_handler(),
+            MessageInput::Batch(_) => return self.call_batch_handler(id, vec![p]),
             _ => panic!("message handler is not sync!"),
         };
         (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p)
     }
+    fn call_batch_handler(&mut self, id: usize, p: Vec<Pmt>) -> Result<Pmt> {
+        let h = match self.mio.input(id) {
+            MessageInput::Batch(t) => t.get_handler(),
+            _ => panic!("message handler is not batch!"),
+        };
+        (h)(&mut self.kernel, &mut self.mio, &mut self.meta, p)
+    }
     async fn post(&mut self, id: usize, p: Pmt) {
         self.mio.post(id, p).await;
     }
@@ -523,6 +610,12 @@ impl Block {
             Block::Async(b) => b.is_blocking(),
         }
     }
+    pub fn drains_on_terminate(&self) -> bool {
+        match self {
+            Block::Sync(b) => b.drains_on_terminate(),
+            Block::Async(b) => b.drains_on_terminate(),
+        }
+    }
 
     // ##### KERNEL
     pub async fn init(&mut self) -> Result<()> {
@@ -613,6 +706,48 @@ impl Block {
             Block::Async(b) => b.message_input_name_to_id(name),
         }
     }
+    pub fn message_input_names(&self) -> Vec<String> {
+        match self {
+            Block::Sync(b) => b.message_input_names(),
+            Block::Async(b) => b.message_input_names(),
+        }
+    }
+    pub fn message_input_queue(&self, id: usize) -> Arc<MessageQueue> {
+        match self {
+            Block::Sync(b) => b.message_input_queue(id),
+            Block::Async(b) => b.message_input_queue(id),
+        }
+    }
+    pub fn message_input_metrics(&self) -> Vec<MessageInputMetrics> {
+        match self {
+            Block::Sync(b) => b.message_input_metrics(),
+            Block::Async(b) => b.message_input_metrics(),
+        }
+    }
+    pub fn message_input_is_batch(&self, id: usize) -> bool {
+        match self {
+            Block::Sync(b) => b.message_input_is_batch(id),
+            Block::Async(b) => b.message_input_is_batch(id),
+        }
+    }
+    pub fn message_input_priority(&self, id: usize) -> MessagePriority {
+        match self {
+            Block::Sync(b) => b.message_input_priority(id),
+            Block::Async(b) => b.message_input_priority(id),
+        }
+    }
+    pub fn message_input_kinds(&self, id: usize) -> Vec<PmtKind> {
+        match self {
+            Block::Sync(b) => b.message_input_kinds(id),
+            Block::Async(b) => b.message_input_kinds(id),
+        }
+    }
+    pub fn max_messages_per_work(&self) -> Option<usize> {
+        match self {
+            Block::Sync(b) => b.max_messages_per_work(),
+            Block::Async(b) => b.max_messages_per_work(),
+        }
+    }
     pub fn message_outputs(&self) -> &Vec<MessageOutput> {
         match self {
             Block::Sync(b) => b.message_outputs(),
@@ -649,6 +784,12 @@ impl Block {
             Block::Async(b) => b.call_sync_handler(id, p),
         }
     }
+    pub fn call_batch_handler(&mut self, id: usize, p: Vec<Pmt>) -> Result<Pmt> {
+        match self {
+            Block::Sync(b) => b.call_batch_handler(id, p),
+            Block::Async(b) => b.call_batch_handler(id, p),
+        }
+    }
     pub async fn call_async_handler(&mut self, id: usize, p: Pmt) -> Result<Pmt> {
         match self {
             Block::Sync(b) => b.call_async_handler(id, p).await,
@@ -2,14 +2,16 @@ pub struct BlockMeta {
     type_name: String,
     instance_name: Option<String>,
     blocking: bool,
+    drain: bool,
 }
 
 impl BlockMeta {
-    fn new(type_name: String, blocking: bool) -> BlockMeta {
+    fn new(type_name: String, blocking: bool, drain: bool) -> BlockMeta {
         BlockMeta {
             type_name,
             instance_name: None,
             blocking,
+            drain,
         }
     }
 
@@ -25,6 +27,10 @@ impl BlockMeta {
         self.blocking
     }
 
+    pub fn drains_on_terminate(&self) -> bool {
+        self.drain
+    }
+
     pub fn set_instance_name(&mut self, name: &str) {
         self.instance_name = Some(name.to_string());
     }
@@ -33,6 +39,7 @@ impl BlockMeta {
 pub struct BlockMetaBuilder {
     name: String,
     blocking: bool,
+    drain: bool,
 }
 
 impl BlockMetaBuilder {
@@ -40,6 +47,7 @@ impl BlockMetaBuilder {
         BlockMetaBuilder {
             name: name.to_string(),
             blocking: false,
+            drain: false,
         }
     }
 
@@ -48,12 +56,21 @@ impl BlockMetaBuilder {
         self
     }
 
+    // When terminated by an upstream message output, the block gets one more
+    // call to work with io.finished set, e.g., to send out what its handlers
+    // queued. It keeps running if it resets io.finished and stops once it sets
+    // it again.
+    pub fn drain_on_terminate(mut self) -> Self {
+        self.drain = true;
+        self
+    }
+
     pub fn name(mut self, name: &str) -> Self {
         self.name = name.to_string();
         self
     }
 
     pub fn build(self) -> BlockMeta {
-        BlockMeta::new(self.name, self.blocking)
+        BlockMeta::new(self.name, self.blocking, self.drain)
     }
 }
@@ -1,18 +1,30 @@
 use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::prelude::*;
+use futuresdr_pmt::PmtConversionError;
 use rocket::fs::{relative, FileServer};
-use rocket::serde::json::Json;
+use rocket::http::Status;
+use rocket::response::status::Custom;
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::{self, Json};
 use rocket::{config::Shutdown, get, post, routes};
+use serde::Serialize;
 use slab::Slab;
+use std::fmt;
 use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::runtime::config;
+use crate::runtime::events::EventBus;
 use crate::runtime::AsyncMessage;
+use crate::runtime::BlockDescription;
+use crate::runtime::EventSubscription;
+use crate::runtime::FlowgraphDescription;
 use crate::runtime::Pmt;
 
 fn routes() -> Vec<rocket::Route> {
-    routes![index, handler_id, handler_id_post]
+    routes![index, flowgraph, block, events, handler_id, handler_id_post]
 }
 
 #[get("/")]
@@ -20,60 +32,175 @@ fn index(boxes: &rocket::State<Slab<Option<mpsc::Sender<AsyncMessage>>>>) -> Str
     format!("number of Blocks {:?}", boxes.len())
 }
 
-#[get("/block/<blk>/call/<handler>")]
-async fn handler_id(
-    blk: usize,
-    handler: usize,
-    boxes: &rocket::State<Slab<Option<mpsc::Sender<AsyncMessage>>>>,
-) -> String {
-    let mut b = match boxes.get(blk) {
-        Some(Some(s)) => s.clone(),
-        _ => return "block not found".to_string(),
-    };
+#[derive(Debug, Serialize)]
+struct ErrorResponse {
+    error: String,
+}
 
-    let (tx, rx) = oneshot::channel::<Pmt>();
+type Response<T = Pmt> = Result<Json<T>, Custom<Json<ErrorResponse>>>;
 
-    b.send(AsyncMessage::Callback {
-        port_id: handler,
-        data: Pmt::Null,
-        tx,
-    })
-    .await
-    .unwrap();
+fn error(status: Status, e: impl fmt::Display) -> Custom<Json<ErrorResponse>> {
+    Custom(
+        status,
+        Json(ErrorResponse {
+            error: e.to_string(),
+        }),
+    )
+}
 
-    let ret = rx.await.unwrap();
+// blocks with their ports and handlers, and the stream and message edges
+#[get("/fg")]
+fn flowgraph(description: &rocket::State<FlowgraphDescription>) -> Json<FlowgraphDescription> {
+    Json(description.inner().clone())
+}
 
-    format!("{:?}", ret)
+#[get("/block/<blk>")]
+fn block(
+    blk: &str,
+    description: &rocket::State<FlowgraphDescription>,
+) -> Response<BlockDescription> {
+    description
+        .block(blk)
+        .map(|b| Json(b.clone()))
+        .map_err(|e| error(Status::NotFound, e))
+}
+
+// Server-sent events with block lifecycle events, messages posted to the given
+// message outputs (comma-separated block:output pairs, given by id or name),
+// and metrics every given number of milliseconds. Subscribe, e.g., with
+// /api/events?messages=MessageSource_0:out&metrics=1000
+#[get("/events?<messages>&<metrics>")]
+fn events(
+    messages: Option<&str>,
+    metrics: Option<u64>,
+    description: &rocket::State<FlowgraphDescription>,
+    bus: &rocket::State<Arc<EventBus>>,
+) -> Result<EventStream<impl Stream<Item = Event>>, Custom<Json<ErrorResponse>>> {
+    let mut subscription = EventSubscription::new();
+    for m in messages
+        .unwrap_or_default()
+        .split(',')
+        .filter(|m| !m.is_empty())
+    {
+        let (blk, output) = m.rsplit_once(':').ok_or_else(|| {
+            error(
+                Status::BadRequest,
+                format!("invalid message output {}, expected block:output", m),
+            )
+        })?;
+        let (blk, output) = description
+            .resolve_output(blk, output)
+            .map_err(|e| error(Status::NotFound, e))?;
+        subscription = subscription.messages(blk, output);
+    }
+    match metrics {
+        Some(0) => {
+            return Err(error(
+                Status::BadRequest,
+                "metrics interval must be positive",
+            ))
+        }
+        Some(ms) => subscription = subscription.metrics(Duration::from_millis(ms)),
+        None => {}
+    }
+
+    let events = bus.subscribe(subscription).map(|e| Event::json(&e));
+    Ok(EventStream::from(events))
+}
+
+// blocks and handlers are given by id or name, see FlowgraphHandle::call_by_name
+#[get("/block/<blk>/call/<handler>")]
+async fn handler_id(
+    blk: &str,
+    handler: &str,
+    boxes: &rocket::State<Slab<Option<mpsc::Sender<AsyncMessage>>>>,
+    description: &rocket::State<FlowgraphDescription>,
+) -> Response {
+    call(blk, handler, Pmt::Null, boxes, description).await
 }
 
 #[post("/block/<blk>/call/<handler>", data = "<pmt>")]
 async fn handler_id_post(
-    blk: usize,
-    handler: usize,
-    pmt: Json<Pmt>,
+    blk: &str,
+    handler: &str,
+    pmt: Result<Json<Pmt>, json::Error<'_>>,
     boxes: &rocket::State<Slab<Option<mpsc::Sender<AsyncMessage>>>>,
-) -> String {
+    description: &rocket::State<FlowgraphDescription>,
+) -> Response {
+    let pmt = pmt.map_err(|e| error(Status::BadRequest, format!("invalid Pmt: {}", e)))?;
+    call(blk, handler, pmt.into_inner(), boxes, description).await
+}
+
+async fn call(
+    blk: &str,
+    handler: &str,
+    data: Pmt,
+    boxes: &Slab<Option<mpsc::Sender<AsyncMessage>>>,
+    description: &FlowgraphDescription,
+) -> Response {
+    let (blk, handler) = description
+        .resolve(blk, handler)
+        .map_err(|e| error(Status::NotFound, e))?;
+
+    // Null queries the handler, other Pmts have to be of a declared kind
+    let h = &description
+        .block(&blk.to_string())
+        .map_err(|e| error(Status::NotFound, e))?
+        .message_inputs[handler];
+    if let Some(kind) = data.kind() {
+        if !h.kinds.is_empty() && !h.kinds.contains(&kind) {
+            return Err(error(
+                Status::BadRequest,
+                format!("handler {} expects {:?}, got {:?}", h.name, h.kinds, kind),
+            ));
+        }
+    }
+
+    let terminated = || error(Status::ServiceUnavailable, "flowgraph terminated");
+
     let mut b = match boxes.get(blk) {
         Some(Some(s)) => s.clone(),
-        _ => return "block not found".to_string(),
+        _ => return Err(terminated()),
     };
 
-    let (tx, rx) = oneshot::channel::<Pmt>();
+    let (tx, rx) = oneshot::channel::<anyhow::Result<Pmt>>();
 
     b.send(AsyncMessage::Callback {
         port_id: handler,
-        data: pmt.into_inner(),
+        data,
         tx,
     })
     .await
-    .unwrap();
+    .map_err(|_| terminated())?;
 
-    let ret = rx.await.unwrap();
+    match rx.await {
+        Ok(Ok(p)) => Ok(Json(p)),
+        Ok(Err(e)) if e.chain().any(|c| c.is::<PmtConversionError>()) => {
+            Err(error(Status::BadRequest, format!("{:#}", e)))
+        }
+        Ok(Err(e)) => Err(error(Status::InternalServerError, format!("{:#}", e))),
+        Err(_) => Err(terminated()),
+    }
+}
 
-    format!("{:?}", ret)
+fn server(
+    config: rocket::Config,
+    inboxes: Slab<Option<mpsc::Sender<AsyncMessage>>>,
+    description: FlowgraphDescription,
+    events: Arc<EventBus>,
+) -> rocket::Rocket<rocket::Build> {
+    rocket::custom(config)
+        .manage(inboxes)
+        .manage(description)
+        .manage(events)
+        .mount("/api/", routes())
 }
 
-pub fn start_control_port(inboxes: Slab<Option<mpsc::Sender<AsyncMessage>>>) {
+pub(crate) fn start_control_port(
+    inboxes: Slab<Option<mpsc::Sender<AsyncMessage>>>,
+    description: FlowgraphDescription,
+    events: Arc<EventBus>,
+) {
     if !config::config().ctrlport_enable {
         return;
     }
@@ -99,10 +226,7 @@ pub fn start_control_port(inboxes: Slab<Option<mpsc::Sender<AsyncMessage>>>) {
             // You can also deserialize this
             let cors = rocket_cors::CorsOptions::default().to_cors().unwrap();
 
-            let mut r = rocket::custom(config)
-                .manage(inboxes)
-                .mount("/api/", routes())
-                .attach(cors);
+            let mut r = server(config, inboxes, description, events).attach(cors);
 
             if let Some(ref p) = config::config().frontend_path {
                 r = r.mount("/", FileServer::from(p));
@@ -117,3 +241,99 @@ pub fn start_control_port(inboxes: Slab<Option<mpsc::Sender<AsyncMessage>>>) {
         });
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::HandlerDescription;
+    use crate::runtime::PmtKind;
+    use rocket::local::blocking::Client;
+
+    // a block with a "freq" handler that takes U32 and a "fail" handler that
+    // returns an error, or a conversion error for strings
+    fn client() -> Client {
+        let description = FlowgraphDescription {
+            blocks: vec![BlockDescription {
+                id: 0,
+                type_name: "Dummy".to_string(),
+                instance_name: "Dummy_0".to_string(),
+                stream_inputs: Vec::new(),
+                stream_outputs: Vec::new(),
+                message_inputs: vec![
+                    HandlerDescription {
+                        name: "freq".to_string(),
+                        kinds: vec![PmtKind::U32],
+                    },
+                    HandlerDescription {
+                        name: "fail".to_string(),
+                        kinds: Vec::new(),
+                    },
+                ],
+                message_outputs: Vec::new(),
+            }],
+            ..Default::default()
+        };
+
+        let (tx, mut rx) = mpsc::channel(1);
+        std::thread::spawn(move || {
+            async_io::block_on(async move {
+                while let Some(m) = rx.next().await {
+                    if let AsyncMessage::Callback { port_id, data, tx } = m {
+                        let res = match (port_id, data) {
+                            (0, Pmt::Null) => Ok(Pmt::U32(1)),
+                            (0, p) => Ok(p),
+                            (_, Pmt::String(_)) => Err(PmtConversionError.into()),
+                            _ => Err(anyhow::anyhow!("failed")),
+                        };
+                        let _ = tx.send(res);
+                    }
+                }
+            })
+        });
+
+        let mut inboxes = Slab::new();
+        inboxes.insert(Some(tx));
+        let config = rocket::Config::debug_default();
+        Client::tracked(server(config, inboxes, description, EventBus::attach(None))).unwrap()
+    }
+
+    #[test]
+    fn ctrl_port_status() {
+        let client = client();
+
+        let r = client.get("/api/block/Dummy_0/call/freq").dispatch();
+        assert_eq!(r.status(), Status::Ok);
+        assert_eq!(r.into_json::<Pmt>(), Some(Pmt::U32(1)));
+        let r = client
+            .post("/api/block/Dummy/call/0")
+            .body(r#"{"U32": 5}"#)
+            .dispatch();
+        assert_eq!(r.status(), Status::Ok);
+        assert_eq!(r.into_json::<Pmt>(), Some(Pmt::U32(5)));
+
+        // not a declared kind
+        let r = client
+            .post("/api/block/Dummy_0/call/freq")
+            .body(r#"{"Double": 5.0}"#)
+            .dispatch();
+        assert_eq!(r.status(), Status::BadRequest);
+        let r = client
+            .post("/api/block/Dummy_0/call/freq")
+            .body("5")
+            .dispatch();
+        assert_eq!(r.status(), Status::BadRequest);
+        let r = client
+            .post("/api/block/Dummy_0/call/fail")
+            .body(r#"{"String": "foo"}"#)
+            .dispatch();
+        assert_eq!(r.status(), Status::BadRequest);
+
+        let r = client.get("/api/block/Dummy_1/call/freq").dispatch();
+        assert_eq!(r.status(), Status::NotFound);
+        let r = client.get("/api/block/Dummy_0/call/gain").dispatch();
+        assert_eq!(r.status(), Status::NotFound);
+
+        let r = client.get("/api/block/Dummy_0/call/fail").dispatch();
+        assert_eq!(r.status(), Status::InternalServerError);
+    }
+}
@@ -0,0 +1,296 @@
+use futures::channel::mpsc;
+use futures::prelude::*;
+use serde::Serialize;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::runtime::config;
+use crate::runtime::MessageInputMetrics;
+use crate::runtime::MessageQueue;
+use crate::runtime::Pmt;
+use crate::runtime::Topology;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockMetrics {
+    pub block: usize,
+    pub message_inputs: Vec<MessageInputMetrics>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum FlowgraphEvent {
+    Initialized {
+        block: usize,
+    },
+    Finished {
+        block: usize,
+    },
+    // the block stopped, since init, work, deinit, or a message handler failed
+    Error {
+        block: usize,
+        error: String,
+    },
+    // a message posted to a subscribed message output
+    Message {
+        block: usize,
+        port: usize,
+        data: Pmt,
+    },
+    Metrics {
+        blocks: Vec<BlockMetrics>,
+    },
+}
+
+impl FlowgraphEvent {
+    pub fn block(&self) -> Option<usize> {
+        match self {
+            FlowgraphEvent::Initialized { block }
+            | FlowgraphEvent::Finished { block }
+            | FlowgraphEvent::Error { block, .. }
+            | FlowgraphEvent::Message { block, .. } => Some(*block),
+            FlowgraphEvent::Metrics { .. } => None,
+        }
+    }
+}
+
+// Lifecycle events are always sent. Messages are only copied for the message
+// outputs that are subscribed, metrics are only sent if an interval is set.
+#[derive(Debug, Clone, Default)]
+pub struct EventSubscription {
+    messages: Vec<(usize, usize)>,
+    metrics: Option<Duration>,
+}
+
+impl EventSubscription {
+    pub fn new() -> EventSubscription {
+        EventSubscription::default()
+    }
+
+    pub fn messages(mut self, block_id: usize, port_id: usize) -> EventSubscription {
+        self.messages.push((block_id, port_id));
+        self
+    }
+
+    pub(crate) fn message_outputs(&self) -> &[(usize, usize)] {
+        &self.messages
+    }
+
+    pub fn metrics(mut self, interval: Duration) -> EventSubscription {
+        assert!(
+            interval > Duration::from_secs(0),
+            "event subscription: metrics interval must be positive"
+        );
+        self.metrics = Some(interval);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct InputQueues {
+    block: usize,
+    inputs: Vec<(String, Arc<MessageQueue>)>,
+}
+
+#[derive(Debug)]
+struct Subscriber {
+    tx: mpsc::Sender<FlowgraphEvent>,
+    messages: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Default)]
+struct EventBusState {
+    subscribers: Vec<Subscriber>,
+    // last lifecycle event of each block, replayed to new subscribers
+    lifecycle: Vec<FlowgraphEvent>,
+    closed: bool,
+}
+
+// Distributes flowgraph events to subscribers. Events are dropped for
+// subscribers that do not keep up, so that they cannot stall the flowgraph.
+#[derive(Debug, Default)]
+pub(crate) struct EventBus {
+    state: Mutex<EventBusState>,
+    // number of message output subscriptions, to skip the lock on post if there are none
+    n_message_taps: AtomicUsize,
+    queues: Vec<InputQueues>,
+}
+
+impl EventBus {
+    // Connects the message outputs of the blocks to the bus and gets the message
+    // input queues, which are shared with the blocks, for the metrics. This has
+    // to happen before the blocks are spawned.
+    pub(crate) fn attach(topology: Option<&mut Topology>) -> Arc<EventBus> {
+        let topology = match topology {
+            Some(t) => t,
+            None => return Arc::new(EventBus::default()),
+        };
+
+        let queues = topology
+            .blocks
+            .iter()
+            .filter_map(|(id, b)| {
+                let b = b.as_ref()?;
+                let inputs = b
+                    .message_input_names()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, name)| (name, b.message_input_queue(i)))
+                    .collect();
+                Some(InputQueues { block: id, inputs })
+            })
+            .collect();
+        let bus = Arc::new(EventBus {
+            queues,
+            ..Default::default()
+        });
+
+        for (id, b) in topology.blocks.iter_mut() {
+            if let Some(b) = b {
+                for (port, o) in b.message_outputs_mut().iter_mut().enumerate() {
+                    o.set_events(bus.clone(), id, port);
+                }
+            }
+        }
+
+        bus
+    }
+
+    pub(crate) fn subscribe(self: &Arc<Self>, subscription: EventSubscription) -> Events {
+        let (mut tx, rx) = mpsc::channel(config::config().queue_size);
+        let mut state = self.state.lock().unwrap();
+        for e in state.lifecycle.iter() {
+            if tx.try_send(e.clone()).is_err() {
+                debug!("event subscriber cannot keep up, dropping event");
+            }
+        }
+        if !state.closed {
+            self.n_message_taps
+                .fetch_add(subscription.messages.len(), Ordering::SeqCst);
+            state.subscribers.push(Subscriber {
+                tx,
+                messages: subscription.messages,
+            });
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let metrics = subscription.metrics.map(async_io::Timer::interval);
+        #[cfg(target_arch = "wasm32")]
+        if subscription.metrics.is_some() {
+            warn!("event subscription: metrics are not supported on wasm");
+        }
+
+        Events {
+            rx,
+            #[cfg(not(target_arch = "wasm32"))]
+            bus: self.clone(),
+            #[cfg(not(target_arch = "wasm32"))]
+            metrics,
+        }
+    }
+
+    pub(crate) fn publish(&self, event: FlowgraphEvent) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(block) = event.block() {
+            if !matches!(event, FlowgraphEvent::Message { .. }) {
+                state.lifecycle.retain(|e| e.block() != Some(block));
+                state.lifecycle.push(event.clone());
+            }
+        }
+        self.send(&mut state, |_| true, event);
+    }
+
+    pub(crate) fn message_tapped(&self) -> bool {
+        self.n_message_taps.load(Ordering::Relaxed) > 0
+    }
+
+    pub(crate) fn publish_message(&self, block: usize, port: usize, data: &Pmt) {
+        let mut state = self.state.lock().unwrap();
+        let event = FlowgraphEvent::Message {
+            block,
+            port,
+            data: data.clone(),
+        };
+        self.send(&mut state, |s| s.messages.contains(&(block, port)), event);
+    }
+
+    fn send(
+        &self,
+        state: &mut EventBusState,
+        filter: impl Fn(&Subscriber) -> bool,
+        event: FlowgraphEvent,
+    ) {
+        // try_send needs the sender mutably, so no retain
+        let mut i = 0;
+        while i < state.subscribers.len() {
+            let s = &mut state.subscribers[i];
+            if filter(s) {
+                match s.tx.try_send(event.clone()) {
+                    Ok(()) => {}
+                    Err(e) if e.is_full() => {
+                        debug!("event subscriber cannot keep up, dropping event");
+                    }
+                    Err(_) => {
+                        let s = state.subscribers.remove(i);
+                        self.n_message_taps
+                            .fetch_sub(s.messages.len(), Ordering::SeqCst);
+                        continue;
+                    }
+                }
+            }
+            i += 1;
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> FlowgraphEvent {
+        FlowgraphEvent::Metrics {
+            blocks: self
+                .queues
+                .iter()
+                .map(|b| BlockMetrics {
+                    block: b.block,
+                    message_inputs: b.inputs.iter().map(|(name, q)| q.metrics(name)).collect(),
+                })
+                .collect(),
+        }
+    }
+
+    // ends the event streams of all subscribers
+    pub(crate) fn close(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.closed = true;
+        state.subscribers.clear();
+        self.n_message_taps.store(0, Ordering::SeqCst);
+    }
+}
+
+// Stream of flowgraph events that ends when the flowgraph terminates. It
+// starts with the last lifecycle event of each block.
+pub struct Events {
+    rx: mpsc::Receiver<FlowgraphEvent>,
+    #[cfg(not(target_arch = "wasm32"))]
+    bus: Arc<EventBus>,
+    #[cfg(not(target_arch = "wasm32"))]
+    metrics: Option<async_io::Timer>,
+}
+
+impl Stream for Events {
+    type Item = FlowgraphEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<FlowgraphEvent>> {
+        if let Poll::Ready(e) = self.rx.poll_next_unpin(cx) {
+            return Poll::Ready(e);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(t) = self.metrics.as_mut() {
+            if let Poll::Ready(Some(_)) = t.poll_next_unpin(cx) {
+                return Poll::Ready(Some(self.bus.metrics()));
+            }
+        }
+
+        Poll::Pending
+    }
+}
@@ -5,6 +5,7 @@ use futures::SinkExt;
 use std::cmp::{Eq, PartialEq};
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::sync::Arc;
 
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::buffer::circular::Circular;
@@ -12,9 +13,15 @@ use crate::runtime::buffer::circular::Circular;
 use crate::runtime::buffer::slab::Slab;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::events::EventBus;
 use crate::runtime::AsyncKernel;
 use crate::runtime::AsyncMessage;
 use crate::runtime::Block;
+use crate::runtime::EventSubscription;
+use crate::runtime::Events;
+use crate::runtime::FlowgraphDescription;
+use crate::runtime::MessageInputMetrics;
+use crate::runtime::MessageTime;
 use crate::runtime::Pmt;
 use crate::runtime::SyncKernel;
 use crate::runtime::Topology;
@@ -77,6 +84,17 @@ impl Flowgraph {
             .connect_message(src_block, src_port, dst_block, dst_port)
     }
 
+    pub fn message_input_metrics(&self, id: usize) -> Option<Vec<MessageInputMetrics>> {
+        self.topology
+            .as_ref()
+            .and_then(|t| t.block_ref(id))
+            .map(|b| b.message_input_metrics())
+    }
+
+    pub fn description(&self) -> Option<FlowgraphDescription> {
+        self.topology.as_ref().map(|t| t.description())
+    }
+
     pub fn block_async<T: AsyncKernel + 'static>(&self, id: usize) -> Option<&T> {
         self.topology
             .as_ref()
@@ -114,14 +132,36 @@ impl Default for Flowgraph {
 
 pub struct FlowgraphHandle {
     inbox: Sender<AsyncMessage>,
+    description: Arc<FlowgraphDescription>,
+    events: Arc<EventBus>,
 }
 
 impl FlowgraphHandle {
-    pub(crate) fn new(inbox: Sender<AsyncMessage>) -> FlowgraphHandle {
-        FlowgraphHandle { inbox }
+    pub(crate) fn new(
+        inbox: Sender<AsyncMessage>,
+        description: Arc<FlowgraphDescription>,
+        events: Arc<EventBus>,
+    ) -> FlowgraphHandle {
+        FlowgraphHandle {
+            inbox,
+            description,
+            events,
+        }
+    }
+
+    pub fn subscribe(&self, subscription: EventSubscription) -> Result<Events> {
+        for (block_id, port_id) in subscription.message_outputs() {
+            self.description.check_output(*block_id, *port_id)?;
+        }
+        Ok(self.events.subscribe(subscription))
+    }
+
+    pub fn description(&self) -> &FlowgraphDescription {
+        &self.description
     }
 
     pub async fn call(&mut self, block_id: usize, port_id: usize, data: Pmt) -> Result<()> {
+        self.description.check(block_id, port_id)?;
         self.inbox
             .send(AsyncMessage::BlockCall {
                 block_id,
@@ -133,7 +173,8 @@ impl FlowgraphHandle {
     }
 
     pub async fn callback(&mut self, block_id: usize, port_id: usize, data: Pmt) -> Result<Pmt> {
-        let (tx, rx) = oneshot::channel::<Pmt>();
+        self.description.check(block_id, port_id)?;
+        let (tx, rx) = oneshot::channel::<Result<Pmt>>();
         self.inbox
             .send(AsyncMessage::BlockCallback {
                 block_id,
@@ -142,8 +183,51 @@ impl FlowgraphHandle {
                 tx,
             })
             .await?;
-        let p = rx.await?;
-        Ok(p)
+        rx.await?
+    }
+
+    // the message is held by the block and handed to the handler once the time is reached
+    pub async fn call_at(
+        &mut self,
+        block_id: usize,
+        port_id: usize,
+        data: Pmt,
+        time: MessageTime,
+    ) -> Result<()> {
+        self.description.check(block_id, port_id)?;
+        self.inbox
+            .send(AsyncMessage::BlockCallAt {
+                block_id,
+                port_id,
+                data,
+                time,
+            })
+            .await?;
+        Ok(())
+    }
+
+    // The block is given by its instance name (e.g., "FileSource_0") or, if
+    // unique in the flowgraph, its type name (e.g., "FileSource"); the handler by
+    // its name. Ids (e.g., "2") are accepted as well.
+    pub async fn call_by_name(&mut self, block: &str, handler: &str, data: Pmt) -> Result<()> {
+        let (block_id, port_id) = self.description.resolve(block, handler)?;
+        self.call(block_id, port_id, data).await
+    }
+
+    pub async fn callback_by_name(&mut self, block: &str, handler: &str, data: Pmt) -> Result<Pmt> {
+        let (block_id, port_id) = self.description.resolve(block, handler)?;
+        self.callback(block_id, port_id, data).await
+    }
+
+    pub async fn call_at_by_name(
+        &mut self,
+        block: &str,
+        handler: &str,
+        data: Pmt,
+        time: MessageTime,
+    ) -> Result<()> {
+        let (block_id, port_id) = self.description.resolve(block, handler)?;
+        self.call_at(block_id, port_id, data, time).await
     }
 }
 
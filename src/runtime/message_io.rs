@@ -1,17 +1,27 @@
 use anyhow::Result;
 use futures::channel::mpsc::Sender;
 use futures::prelude::*;
+use serde::Serialize;
+use std::collections::VecDeque;
 use std::future::Future;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::{Poll, Waker};
+use std::time::SystemTime;
 
+use crate::runtime::config;
+use crate::runtime::events::EventBus;
 use crate::runtime::AsyncMessage;
 use crate::runtime::BlockMeta;
 use crate::runtime::Pmt;
+use crate::runtime::PmtKind;
 
 pub enum MessageInput<T: Send + ?Sized> {
     Sync(SyncMessageInput<T>),
     Async(AsyncMessageInput<T>),
+    Batch(BatchMessageInput<T>),
 }
 
 impl<T: Send + ?Sized> MessageInput<T> {
@@ -19,6 +29,7 @@ impl<T: Send + ?Sized> MessageInput<T> {
         match self {
             MessageInput::Sync(i) => i.name(),
             MessageInput::Async(i) => i.name(),
+            MessageInput::Batch(i) => i.name(),
         }
     }
 }
@@ -123,10 +134,243 @@ impl<T: Send + ?Sized> AsyncMessageInput<T> {
     }
 }
 
+// What happens to a message that is posted to a full input queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum OverflowPolicy {
+    // the sender waits until there is space (back pressure)
+    Block,
+    // the new message is discarded
+    DropNewest,
+    // the oldest queued message is discarded to make room
+    DropOldest,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MessageInputMetrics {
+    pub name: String,
+    pub depth: usize,
+    pub policy: OverflowPolicy,
+    pub queued: usize,
+    pub received: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    // with the time of timed messages
+    items: VecDeque<(Pmt, Option<MessageTime>)>,
+    waiting: Vec<Waker>,
+    closed: bool,
+}
+
+// Messages posted to a message input are queued here by the sending block and
+// handled by the receiving block in its main loop.
+#[derive(Debug)]
+pub struct MessageQueue {
+    depth: usize,
+    policy: OverflowPolicy,
+    state: Mutex<QueueState>,
+    received: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl MessageQueue {
+    pub fn new(depth: usize, policy: OverflowPolicy) -> MessageQueue {
+        assert!(depth > 0, "message queue: depth must be positive");
+        MessageQueue {
+            depth,
+            policy,
+            state: Mutex::new(QueueState {
+                items: VecDeque::new(),
+                waiting: Vec::new(),
+                closed: false,
+            }),
+            received: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn policy(&self) -> OverflowPolicy {
+        self.policy
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn received(&self) -> u64 {
+        self.received.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn metrics(&self, name: &str) -> MessageInputMetrics {
+        MessageInputMetrics {
+            name: name.to_string(),
+            depth: self.depth(),
+            policy: self.policy(),
+            queued: self.len(),
+            received: self.received(),
+            dropped: self.dropped(),
+        }
+    }
+
+    // Returns false if the receiver is gone.
+    pub async fn push(&self, p: Pmt) -> bool {
+        self.push_entry(p, None).await
+    }
+
+    // timed messages take the same queue, so that they are subject to its depth
+    // and overflow policy; the receiver holds them once they are handled
+    pub async fn push_at(&self, p: Pmt, time: MessageTime) -> bool {
+        self.push_entry(p, Some(time)).await
+    }
+
+    async fn push_entry(&self, p: Pmt, time: Option<MessageTime>) -> bool {
+        let mut p = Some((p, time));
+        future::poll_fn(|cx| {
+            let mut s = self.state.lock().unwrap();
+            if s.closed {
+                return Poll::Ready(false);
+            }
+            if s.items.len() >= self.depth {
+                match self.policy {
+                    OverflowPolicy::Block => {
+                        s.waiting.push(cx.waker().clone());
+                        return Poll::Pending;
+                    }
+                    OverflowPolicy::DropNewest => {
+                        self.received.fetch_add(1, Ordering::Relaxed);
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return Poll::Ready(true);
+                    }
+                    OverflowPolicy::DropOldest => {
+                        s.items.pop_front();
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }
+            s.items.push_back(p.take().unwrap());
+            self.received.fetch_add(1, Ordering::Relaxed);
+            Poll::Ready(true)
+        })
+        .await
+    }
+
+    pub fn pop(&self) -> Option<Pmt> {
+        self.pop_entry().map(|(p, _)| p)
+    }
+
+    pub(crate) fn pop_entry(&self) -> Option<(Pmt, Option<MessageTime>)> {
+        let mut s = self.state.lock().unwrap();
+        let p = s.items.pop_front();
+        if p.is_some() {
+            s.waiting.drain(..).for_each(|w| w.wake());
+        }
+        p
+    }
+
+    // Called by the receiver when it terminates. Queued messages are dropped and
+    // waiting senders are released.
+    pub fn close(&self) {
+        let mut s = self.state.lock().unwrap();
+        s.closed = true;
+        self.dropped
+            .fetch_add(s.items.len() as u64, Ordering::Relaxed);
+        s.items.clear();
+        s.waiting.drain(..).for_each(|w| w.wake());
+    }
+}
+
+#[derive(Debug)]
+struct MessageConnection {
+    port_id: usize,
+    inbox: Sender<AsyncMessage>,
+    queue: Arc<MessageQueue>,
+}
+
+pub struct BatchMessageInput<T: Send + ?Sized> {
+    name: String,
+    #[allow(clippy::type_complexity)]
+    handler: Arc<
+        dyn for<'a> Fn(&'a mut T, &'a mut MessageIo<T>, &'a mut BlockMeta, Vec<Pmt>) -> Result<Pmt>
+            + Send
+            + Sync,
+    >,
+}
+
+impl<T: Send + ?Sized> BatchMessageInput<T> {
+    #[allow(clippy::type_complexity)]
+    pub fn new(
+        name: &str,
+        handler: Arc<
+            dyn for<'a> Fn(
+                    &'a mut T,
+                    &'a mut MessageIo<T>,
+                    &'a mut BlockMeta,
+                    Vec<Pmt>,
+                ) -> Result<Pmt>
+                + Send
+                + Sync,
+        >,
+    ) -> BatchMessageInput<T> {
+        BatchMessageInput {
+            name: name.to_string(),
+            handler,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    pub fn get_handler(
+        &self,
+    ) -> Arc<
+        dyn for<'a> Fn(&'a mut T, &'a mut MessageIo<T>, &'a mut BlockMeta, Vec<Pmt>) -> Result<Pmt>
+            + Send
+            + Sync,
+    > {
+        self.handler.clone()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+// When a timed message is delivered to the handler of the receiving block. Item
+// offsets count the items consumed from a stream input or produced to a stream
+// output of the receiving block; its work is cut at the offset, so that the
+// handler runs exactly between the items offset - 1 and offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageTime {
+    At(SystemTime),
+    InputItem { port: usize, item: u64 },
+    OutputItem { port: usize, item: u64 },
+}
+
+// Order in which queued messages are handled. High priority inputs are served
+// first, e.g., for control messages that should not wait behind bulk data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MessagePriority {
+    High,
+    Normal,
+}
+
 #[derive(Debug)]
 pub struct MessageOutput {
     name: String,
-    handlers: Vec<(usize, Sender<AsyncMessage>)>,
+    handlers: Vec<MessageConnection>,
+    // event bus, block id, port id
+    events: Option<(Arc<EventBus>, usize, usize)>,
 }
 
 impl MessageOutput {
@@ -134,6 +378,19 @@ impl MessageOutput {
         MessageOutput {
             name: name.to_string(),
             handlers: Vec::new(),
+            events: None,
+        }
+    }
+
+    pub(crate) fn set_events(&mut self, bus: Arc<EventBus>, block_id: usize, port_id: usize) {
+        self.events = Some((bus, block_id, port_id));
+    }
+
+    fn publish(&self, p: &Pmt) {
+        if let Some((bus, block_id, port_id)) = &self.events {
+            if bus.message_tapped() {
+                bus.publish_message(*block_id, *port_id, p);
+            }
         }
     }
 
@@ -141,46 +398,112 @@ impl MessageOutput {
         &self.name
     }
 
-    pub fn connect(&mut self, port: usize, sender: Sender<AsyncMessage>) {
-        self.handlers.push((port, sender));
+    pub fn connect(&mut self, port: usize, sender: Sender<AsyncMessage>, queue: Arc<MessageQueue>) {
+        self.handlers.push(MessageConnection {
+            port_id: port,
+            inbox: sender,
+            queue,
+        });
     }
 
     pub async fn notify_finished(&mut self) {
-        for (_, sender) in self.handlers.iter_mut() {
-            sender.send(AsyncMessage::Terminate).await.unwrap();
+        for c in self.handlers.iter_mut() {
+            if c.inbox.send(AsyncMessage::Terminate).await.is_err() {
+                debug!("message output {}: receiver already terminated", self.name);
+            }
         }
     }
 
     pub async fn post(&mut self, p: Pmt) {
-        for (port_id, sender) in self.handlers.iter_mut() {
-            sender
-                .send(AsyncMessage::Call {
-                    port_id: *port_id,
-                    data: p.clone(),
-                })
-                .await
-                .unwrap();
+        self.send(p, None).await;
+    }
+
+    // the receiver holds timed messages until they are due
+    pub async fn post_at(&mut self, p: Pmt, time: MessageTime) {
+        self.send(p, Some(time)).await;
+    }
+
+    async fn send(&mut self, p: Pmt, time: Option<MessageTime>) {
+        self.publish(&p);
+        let name = &self.name;
+        let mut i = 0;
+        while i < self.handlers.len() {
+            let c = &mut self.handlers[i];
+            let mut connected = match time {
+                Some(t) => c.queue.push_at(p.clone(), t).await,
+                None => c.queue.push(p.clone()).await,
+            };
+            if connected {
+                // a full inbox already has a pending wake up for the receiver
+                if let Err(e) = c.inbox.try_send(AsyncMessage::Notify) {
+                    connected = !e.is_disconnected();
+                }
+            }
+            if connected {
+                i += 1;
+            } else {
+                debug!(
+                    "message output {}: port {} disconnected, dropping it",
+                    name, c.port_id
+                );
+                self.handlers.remove(i);
+            }
         }
     }
 }
 
 pub struct MessageIo<T: Send + ?Sized> {
     inputs: Vec<MessageInput<T>>,
+    queues: Vec<Arc<MessageQueue>>,
+    priorities: Vec<MessagePriority>,
+    kinds: Vec<Vec<PmtKind>>,
+    max_messages_per_work: Option<usize>,
     outputs: Vec<MessageOutput>,
 }
 
 impl<T: Send> MessageIo<T> {
-    fn new(inputs: Vec<MessageInput<T>>, outputs: Vec<MessageOutput>) -> Self {
-        MessageIo { inputs, outputs }
+    fn new(
+        inputs: Vec<MessageInput<T>>,
+        queues: Vec<Arc<MessageQueue>>,
+        priorities: Vec<MessagePriority>,
+        kinds: Vec<Vec<PmtKind>>,
+        max_messages_per_work: Option<usize>,
+        outputs: Vec<MessageOutput>,
+    ) -> Self {
+        MessageIo {
+            inputs,
+            queues,
+            priorities,
+            kinds,
+            max_messages_per_work,
+            outputs,
+        }
     }
 
     pub fn input_is_async(&self, id: usize) -> bool {
         match self.inputs.get(id).unwrap() {
-            MessageInput::Sync(_) => false,
+            MessageInput::Sync(_) | MessageInput::Batch(_) => false,
             MessageInput::Async(_) => true,
         }
     }
 
+    pub fn input_is_batch(&self, id: usize) -> bool {
+        matches!(self.inputs.get(id).unwrap(), MessageInput::Batch(_))
+    }
+
+    pub fn input_priority(&self, id: usize) -> MessagePriority {
+        self.priorities[id]
+    }
+
+    // empty if the kinds were not declared
+    pub fn input_kinds(&self, id: usize) -> &[PmtKind] {
+        &self.kinds[id]
+    }
+
+    pub fn max_messages_per_work(&self) -> Option<usize> {
+        self.max_messages_per_work
+    }
+
     pub fn input_name_to_id(&self, name: &str) -> Option<usize> {
         self.inputs
             .iter()
@@ -189,10 +512,26 @@ impl<T: Send> MessageIo<T> {
             .map(|(i, _)| i)
     }
 
+    pub fn input_names(&self) -> Vec<String> {
+        self.inputs.iter().map(|i| i.name().to_string()).collect()
+    }
+
     pub fn input(&self, id: usize) -> &MessageInput<T> {
         &self.inputs[id]
     }
 
+    pub fn input_queue(&self, id: usize) -> &Arc<MessageQueue> {
+        &self.queues[id]
+    }
+
+    pub fn input_metrics(&self) -> Vec<MessageInputMetrics> {
+        self.inputs
+            .iter()
+            .zip(self.queues.iter())
+            .map(|(i, q)| q.metrics(i.name()))
+            .collect()
+    }
+
     pub fn outputs(&self) -> &Vec<MessageOutput> {
         &self.outputs
     }
@@ -220,10 +559,18 @@ impl<T: Send> MessageIo<T> {
     pub async fn post(&mut self, id: usize, p: Pmt) {
         self.output_mut(id).post(p).await;
     }
+
+    pub async fn post_at(&mut self, id: usize, p: Pmt, time: MessageTime) {
+        self.output_mut(id).post_at(p, time).await;
+    }
 }
 
 pub struct MessageIoBuilder<T: Send> {
     inputs: Vec<MessageInput<T>>,
+    queues: Vec<Option<(usize, OverflowPolicy)>>,
+    priorities: Vec<MessagePriority>,
+    kinds: Vec<Vec<PmtKind>>,
+    max_messages_per_work: Option<usize>,
     outputs: Vec<MessageOutput>,
 }
 
@@ -231,10 +578,21 @@ impl<T: Send> MessageIoBuilder<T> {
     pub fn new() -> MessageIoBuilder<T> {
         MessageIoBuilder {
             inputs: Vec::new(),
+            queues: Vec::new(),
+            priorities: Vec::new(),
+            kinds: Vec::new(),
+            max_messages_per_work: None,
             outputs: Vec::new(),
         }
     }
 
+    fn input_id(&self, name: &str) -> usize {
+        self.inputs
+            .iter()
+            .position(|i| i.name() == name)
+            .unwrap_or_else(|| panic!("no message input {}", name))
+    }
+
     // adding inputs can only be done here
     pub fn add_async_input(
         mut self,
@@ -253,6 +611,9 @@ impl<T: Send> MessageIoBuilder<T> {
             name,
             Arc::new(c),
         )));
+        self.queues.push(None);
+        self.priorities.push(MessagePriority::Normal);
+        self.kinds.push(Vec::new());
         self
     }
 
@@ -266,6 +627,66 @@ impl<T: Send> MessageIoBuilder<T> {
     ) -> MessageIoBuilder<T> {
         self.inputs
             .push(MessageInput::Sync(SyncMessageInput::new(name, Arc::new(c))));
+        self.queues.push(None);
+        self.priorities.push(MessagePriority::Normal);
+        self.kinds.push(Vec::new());
+        self
+    }
+
+    // inputs default to a queue of config().queue_size messages that blocks the sender when full
+    pub fn input_queue(
+        mut self,
+        name: &str,
+        depth: usize,
+        policy: OverflowPolicy,
+    ) -> MessageIoBuilder<T> {
+        assert!(
+            depth > 0,
+            "message input {}: queue depth must be positive",
+            name
+        );
+        let id = self.input_id(name);
+        self.queues[id] = Some((depth, policy));
+        self
+    }
+
+    // the handler gets all messages that are queued (up to max_messages_per_work) in one call
+    pub fn add_batch_input(
+        mut self,
+        name: &str,
+        c: impl for<'a> Fn(&'a mut T, &'a mut MessageIo<T>, &'a mut BlockMeta, Vec<Pmt>) -> Result<Pmt>
+            + Send
+            + Sync
+            + 'static,
+    ) -> MessageIoBuilder<T> {
+        self.inputs.push(MessageInput::Batch(BatchMessageInput::new(
+            name,
+            Arc::new(c),
+        )));
+        self.queues.push(None);
+        self.priorities.push(MessagePriority::Normal);
+        self.kinds.push(Vec::new());
+        self
+    }
+
+    pub fn input_priority(mut self, name: &str, priority: MessagePriority) -> MessageIoBuilder<T> {
+        let id = self.input_id(name);
+        self.priorities[id] = priority;
+        self
+    }
+
+    // Pmt kinds the handler accepts, to describe it to frontends (e.g., through
+    // the control port); messages are passed to the handler regardless
+    pub fn input_kinds(mut self, name: &str, kinds: &[PmtKind]) -> MessageIoBuilder<T> {
+        let id = self.input_id(name);
+        self.kinds[id] = kinds.to_vec();
+        self
+    }
+
+    // limit the number of queued messages handled between two calls to work
+    pub fn max_messages_per_work(mut self, n: usize) -> MessageIoBuilder<T> {
+        assert!(n > 0, "max messages per work must be positive");
+        self.max_messages_per_work = Some(n);
         self
     }
 
@@ -276,7 +697,23 @@ impl<T: Send> MessageIoBuilder<T> {
     }
 
     pub fn build(self) -> MessageIo<T> {
-        MessageIo::new(self.inputs, self.outputs)
+        let queues = self
+            .queues
+            .into_iter()
+            .map(|q| {
+                let (depth, policy) =
+                    q.unwrap_or((config::config().queue_size, OverflowPolicy::Block));
+                Arc::new(MessageQueue::new(depth, policy))
+            })
+            .collect();
+        MessageIo::new(
+            self.inputs,
+            queues,
+            self.priorities,
+            self.kinds,
+            self.max_messages_per_work,
+            self.outputs,
+        )
     }
 }
 
@@ -1,5 +1,7 @@
+use anyhow::Result;
 use futures::channel::mpsc;
 use futures::channel::oneshot;
+use std::sync::Arc;
 
 mod block;
 #[cfg(feature = "block_builder")]
@@ -20,6 +22,7 @@ mod logging;
 #[path = "logging_wasm.rs"]
 mod logging;
 
+mod events;
 mod flowgraph;
 mod message_io;
 #[allow(clippy::module_inception)]
@@ -38,13 +41,23 @@ pub use block::WorkIo;
 pub use block_builder::BlockBuilder;
 pub use block_meta::BlockMeta;
 pub use block_meta::BlockMetaBuilder;
+pub use events::BlockMetrics;
+pub use events::EventSubscription;
+pub use events::Events;
+pub use events::FlowgraphEvent;
 pub use flowgraph::Flowgraph;
 pub use flowgraph::FlowgraphHandle;
 pub use futuresdr_pmt::Pmt;
+pub use futuresdr_pmt::PmtKind;
 pub use message_io::MessageInput;
+pub use message_io::MessageInputMetrics;
 pub use message_io::MessageIo;
 pub use message_io::MessageIoBuilder;
 pub use message_io::MessageOutput;
+pub use message_io::MessagePriority;
+pub use message_io::MessageQueue;
+pub use message_io::MessageTime;
+pub use message_io::OverflowPolicy;
 pub(crate) use runtime::run_block;
 pub use runtime::Runtime;
 pub use runtime::RuntimeBuilder;
@@ -52,6 +65,11 @@ pub use stream_io::StreamInput;
 pub use stream_io::StreamIo;
 pub use stream_io::StreamIoBuilder;
 pub use stream_io::StreamOutput;
+pub use topology::BlockDescription;
+pub use topology::EdgeDescription;
+pub use topology::FlowgraphDescription;
+pub use topology::HandlerDescription;
+pub use topology::StreamPortDescription;
 pub use topology::Topology;
 
 use crate::runtime::buffer::BufferReader;
@@ -64,13 +82,19 @@ pub fn init() {
 #[derive(Debug)]
 pub enum AsyncMessage {
     Initialize,
-    Initialized,
+    Initialized {
+        id: usize,
+    },
     Notify,
     Terminate,
     BlockDone {
         id: usize,
         block: Block,
     },
+    BlockError {
+        id: usize,
+        error: String,
+    },
     StreamOutputInit {
         src_port: usize,
         writer: BufferWriter,
@@ -89,6 +113,7 @@ pub enum AsyncMessage {
         src_port: usize,
         dst_port: usize,
         dst_inbox: mpsc::Sender<AsyncMessage>,
+        dst_queue: Arc<MessageQueue>,
     },
     Call {
         port_id: usize,
@@ -97,7 +122,12 @@ pub enum AsyncMessage {
     Callback {
         port_id: usize,
         data: Pmt,
-        tx: oneshot::Sender<Pmt>,
+        tx: oneshot::Sender<Result<Pmt>>,
+    },
+    CallAt {
+        port_id: usize,
+        data: Pmt,
+        time: MessageTime,
     },
     BlockCall {
         block_id: usize,
@@ -108,6 +138,12 @@ pub enum AsyncMessage {
         block_id: usize,
         port_id: usize,
         data: Pmt,
-        tx: oneshot::Sender<Pmt>,
+        tx: oneshot::Sender<Result<Pmt>>,
+    },
+    BlockCallAt {
+        block_id: usize,
+        port_id: usize,
+        data: Pmt,
+        time: MessageTime,
     },
 }
@@ -2,12 +2,19 @@ use anyhow::{Context, Result};
 #[cfg(not(target_arch = "wasm32"))]
 use async_io::block_on;
 #[cfg(not(target_arch = "wasm32"))]
+use async_io::Timer;
+#[cfg(not(target_arch = "wasm32"))]
 use async_task::Task;
 use futures::channel::mpsc::{channel, Receiver, Sender};
 use futures::future::join_all;
 use futures::future::Either;
 use futures::prelude::*;
 use futures::FutureExt;
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Duration;
+use std::time::SystemTime;
 #[cfg(target_arch = "wasm32")]
 use wasm_rs_async_executor::single_threaded;
 #[cfg(target_arch = "wasm32")]
@@ -18,6 +25,7 @@ type Task<T> = single_threaded::TaskHandle<T>;
 use crate::runtime::config;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::ctrl_port;
+use crate::runtime::events::EventBus;
 use crate::runtime::scheduler::Scheduler;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::scheduler::SmolScheduler;
@@ -26,7 +34,12 @@ use crate::runtime::scheduler::WasmScheduler;
 use crate::runtime::AsyncMessage;
 use crate::runtime::Block;
 use crate::runtime::Flowgraph;
+use crate::runtime::FlowgraphEvent;
 use crate::runtime::FlowgraphHandle;
+use crate::runtime::MessagePriority;
+use crate::runtime::MessageQueue;
+use crate::runtime::MessageTime;
+use crate::runtime::Pmt;
 use crate::runtime::WorkIo;
 
 pub struct Runtime<S: Scheduler> {
@@ -102,17 +115,27 @@ impl<S: Scheduler> Runtime<S> {
         self.scheduler.spawn_blocking(future).detach();
     }
 
-    pub fn start(&self, fg: Flowgraph) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
+    pub fn start(&self, mut fg: Flowgraph) -> (Task<Result<Flowgraph>>, FlowgraphHandle) {
         let queue_size = config::config().queue_size;
         let (fg_inbox, fg_inbox_rx) = channel::<AsyncMessage>(queue_size);
+        let description = Arc::new(fg.description().unwrap_or_default());
+        let events = EventBus::attach(fg.topology.as_mut());
 
-        let task = self.scheduler.spawn(run_flowgraph(
+        let run = run_flowgraph(
             fg,
             self.scheduler.clone(),
             fg_inbox.clone(),
             fg_inbox_rx,
-        ));
-        (task, FlowgraphHandle::new(fg_inbox))
+            events.clone(),
+        );
+        let bus = events.clone();
+        let task = self.scheduler.spawn(async move {
+            let res = run.await;
+            // end the event streams
+            bus.close();
+            res
+        });
+        (task, FlowgraphHandle::new(fg_inbox, description, events))
     }
 
     #[cfg(not(target_arch = "wasm32"))]
@@ -146,10 +169,24 @@ async fn run_flowgraph<S: Scheduler>(
     scheduler: S,
     mut main_channel: Sender<AsyncMessage>,
     mut main_rx: Receiver<AsyncMessage>,
+    events: Arc<EventBus>,
 ) -> Result<Flowgraph> {
     debug!("in run_flowgraph");
     let mut topology = fg.topology.take().context("flowgraph not initialized")?;
     topology.validate()?;
+    #[cfg(not(target_arch = "wasm32"))]
+    let description = topology.description();
+    // message queues belong to the receiving blocks, so get them before the blocks are spawned
+    let message_queues: Vec<Arc<MessageQueue>> = topology
+        .message_edges
+        .iter()
+        .map(|(_, _, dst, dst_port)| {
+            topology
+                .block_ref(*dst)
+                .unwrap()
+                .message_input_queue(*dst_port)
+        })
+        .collect();
 
     let mut inboxes = scheduler.run_topology(&mut topology, &main_channel);
 
@@ -188,7 +225,9 @@ async fn run_flowgraph<S: Scheduler>(
 
     debug!("connect message io");
     // connect message IO
-    for (src, src_port, dst, dst_port) in topology.message_edges.iter() {
+    for ((src, src_port, dst, dst_port), dst_queue) in
+        topology.message_edges.iter().zip(message_queues)
+    {
         let dst_box = inboxes[*dst].as_ref().unwrap().clone();
         inboxes[*src]
             .as_mut()
@@ -197,6 +236,7 @@ async fn run_flowgraph<S: Scheduler>(
                 src_port: *src_port,
                 dst_port: *dst_port,
                 dst_inbox: dst_box,
+                dst_queue,
             })
             .await
             .unwrap();
@@ -223,7 +263,10 @@ async fn run_flowgraph<S: Scheduler>(
 
         let m = main_rx.next().await.context("no msg")?;
         match m {
-            AsyncMessage::Initialized => i -= 1,
+            AsyncMessage::Initialized { id } => {
+                events.publish(FlowgraphEvent::Initialized { block: id });
+                i -= 1;
+            }
             x => {
                 debug!(
                     "queueing unhandled message received during initialization {:?}",
@@ -251,7 +294,7 @@ async fn run_flowgraph<S: Scheduler>(
 
     // Start Control Port
     #[cfg(not(target_arch = "wasm32"))]
-    ctrl_port::start_control_port(inboxes.clone());
+    ctrl_port::start_control_port(inboxes.clone(), description, events.clone());
 
     // main loop
     loop {
@@ -266,12 +309,15 @@ async fn run_flowgraph<S: Scheduler>(
                 port_id,
                 data,
             } => {
-                inboxes[block_id]
+                if inboxes[block_id]
                     .as_mut()
                     .unwrap()
                     .send(AsyncMessage::Call { port_id, data })
                     .await
-                    .unwrap();
+                    .is_err()
+                {
+                    debug!("runtime wanted to call block that already terminated");
+                }
             }
             AsyncMessage::BlockCallback {
                 block_id,
@@ -279,18 +325,50 @@ async fn run_flowgraph<S: Scheduler>(
                 data,
                 tx,
             } => {
-                inboxes[block_id]
+                if inboxes[block_id]
                     .as_mut()
                     .unwrap()
                     .send(AsyncMessage::Callback { port_id, data, tx })
                     .await
-                    .unwrap();
+                    .is_err()
+                {
+                    debug!("runtime wanted to call block that already terminated");
+                }
+            }
+            AsyncMessage::BlockCallAt {
+                block_id,
+                port_id,
+                data,
+                time,
+            } => {
+                if inboxes[block_id]
+                    .as_mut()
+                    .unwrap()
+                    .send(AsyncMessage::CallAt {
+                        port_id,
+                        data,
+                        time,
+                    })
+                    .await
+                    .is_err()
+                {
+                    debug!("runtime wanted to call block that already terminated");
+                }
             }
             AsyncMessage::BlockDone { id, block } => {
                 *topology.blocks.get_mut(id).unwrap() = Some(block);
+                events.publish(FlowgraphEvent::Finished { block: id });
 
                 active_blocks -= 1;
             }
+            AsyncMessage::BlockError { id, error } => {
+                warn!(
+                    "{} failed: {}",
+                    topology.block_name(id).unwrap_or("block"),
+                    error
+                );
+                events.publish(FlowgraphEvent::Error { block: id, error });
+            }
             _ => warn!("main loop received unhandled message"),
         }
     }
@@ -300,6 +378,25 @@ async fn run_flowgraph<S: Scheduler>(
 }
 
 pub(crate) async fn run_block(
+    block: Block,
+    block_id: usize,
+    mut main_inbox: Sender<AsyncMessage>,
+    inbox: Receiver<AsyncMessage>,
+) -> Result<()> {
+    let res = run_block_inner(block, block_id, main_inbox.clone(), inbox).await;
+    if let Err(ref e) = res {
+        let m = AsyncMessage::BlockError {
+            id: block_id,
+            error: format!("{:#}", e),
+        };
+        if main_inbox.send(m).await.is_err() {
+            debug!("block {} failed after the flowgraph terminated", block_id);
+        }
+    }
+    res
+}
+
+async fn run_block_inner(
     mut block: Block,
     block_id: usize,
     mut main_inbox: Sender<AsyncMessage>,
@@ -317,7 +414,9 @@ pub(crate) async fn run_block(
         match inbox.next().await.context("no msg")? {
             AsyncMessage::Initialize => {
                 block.init().await?;
-                main_inbox.send(AsyncMessage::Initialized).await?;
+                main_inbox
+                    .send(AsyncMessage::Initialized { id: block_id })
+                    .await?;
                 break;
             }
             AsyncMessage::StreamOutputInit { src_port, writer } => {
@@ -330,10 +429,11 @@ pub(crate) async fn run_block(
                 src_port,
                 dst_port,
                 dst_inbox,
+                dst_queue,
             } => {
                 block
                     .message_output_mut(src_port)
-                    .connect(dst_port, dst_inbox);
+                    .connect(dst_port, dst_inbox, dst_queue);
             }
             t => warn!(
                 "{} unhandled message during init {:?}",
@@ -346,6 +446,22 @@ pub(crate) async fn run_block(
     let inbox = inbox.peekable();
     futures::pin_mut!(inbox);
 
+    let queues: Vec<Arc<MessageQueue>> = (0..block.message_input_names().len())
+        .map(|i| block.message_input_queue(i))
+        .collect();
+    let (high_ports, normal_ports): (Vec<usize>, Vec<usize>) =
+        (0..queues.len()).partition(|i| block.message_input_priority(*i) == MessagePriority::High);
+    let mut next_port = 0;
+    let max_messages = block.max_messages_per_work();
+    let mut timed = TimedMessages::default();
+
+    // A block that is terminated by an upstream message output first handles the
+    // messages that are still queued. Blocks built with drain_on_terminate then
+    // get one more call to work with io.finished set (see BlockMetaBuilder).
+    let drain = block.drains_on_terminate();
+    let mut terminated = false;
+    let mut flush = false;
+
     // main loop
     loop {
         // ================== non blocking
@@ -357,6 +473,7 @@ pub(crate) async fn run_block(
                 }
                 Some(Some(AsyncMessage::StreamOutputDone { .. })) => {
                     work_io.finished = true;
+                    flush = false;
                 }
                 Some(Some(AsyncMessage::Call { port_id, data })) => {
                     if block.message_input_is_async(port_id) {
@@ -366,17 +483,42 @@ pub(crate) async fn run_block(
                     }
                 }
                 Some(Some(AsyncMessage::Callback { port_id, data, tx })) => {
+                    // errors are returned to the caller instead of stopping the block
                     let res = {
                         if block.message_input_is_async(port_id) {
-                            block.call_async_handler(port_id, data).await?
+                            block.call_async_handler(port_id, data).await
                         } else {
-                            block.call_sync_handler(port_id, data)?
+                            block.call_sync_handler(port_id, data)
                         }
                     };
+                    if let Err(ref e) = res {
+                        debug!(
+                            "{} message handler {} failed: {:#}",
+                            block.instance_name().unwrap(),
+                            port_id,
+                            e
+                        );
+                    }
 
-                    tx.send(res).unwrap();
+                    if tx.send(res).is_err() {
+                        debug!(
+                            "{} callback result not received",
+                            block.instance_name().unwrap()
+                        );
+                    }
+                }
+                Some(Some(AsyncMessage::CallAt {
+                    port_id,
+                    data,
+                    time,
+                })) => {
+                    timed.push(&block, port_id, data, time);
+                }
+                Some(Some(AsyncMessage::Terminate)) => {
+                    work_io.finished = true;
+                    terminated = true;
+                    flush = drain;
                 }
-                Some(Some(AsyncMessage::Terminate)) => work_io.finished = true,
                 Some(Some(t)) => warn!("block unhandled message in main loop {:?}", t),
                 _ => break,
             }
@@ -384,9 +526,92 @@ pub(crate) async fn run_block(
             work_io.call_again = true;
         }
 
+        // ================== queued messages
+        // high priority inputs first, then the others round robin, so that no input
+        // starves the others; only handle what is queued now and at most
+        // max_messages_per_work, so that a fast sender cannot starve work
+        let mut budget = max_messages.unwrap_or(usize::MAX);
+        let ports: Vec<usize> = high_ports
+            .iter()
+            .chain(normal_ports[next_port..].iter())
+            .chain(normal_ports[..next_port].iter())
+            .copied()
+            .collect();
+        if !normal_ports.is_empty() {
+            next_port = (next_port + 1) % normal_ports.len();
+        }
+        for port_id in ports {
+            let q = &queues[port_id];
+            let n = q.len().min(budget);
+            if n == 0 {
+                continue;
+            }
+            budget -= n;
+
+            if block.message_input_is_batch(port_id) {
+                let mut batch = Vec::new();
+                for _ in 0..n {
+                    match q.pop_entry() {
+                        Some((p, None)) => batch.push(p),
+                        Some((p, Some(time))) => timed.push(&block, port_id, p, time),
+                        None => break,
+                    }
+                }
+                if !batch.is_empty() {
+                    block.call_batch_handler(port_id, batch)?;
+                }
+            } else {
+                for _ in 0..n {
+                    let data = match q.pop_entry() {
+                        Some((p, None)) => p,
+                        Some((p, Some(time))) => {
+                            timed.push(&block, port_id, p, time);
+                            continue;
+                        }
+                        None => break,
+                    };
+                    if block.message_input_is_async(port_id) {
+                        block.call_async_handler(port_id, data).await?;
+                    } else {
+                        block.call_sync_handler(port_id, data)?;
+                    }
+                }
+            }
+            work_io.call_again = true;
+        }
+        let pending = queues.iter().any(|q| !q.is_empty());
+        if pending {
+            work_io.call_again = true;
+        }
+
+        // ================== timed messages
+        for m in timed.take_due(&block) {
+            if block.message_input_is_async(m.port_id) {
+                block.call_async_handler(m.port_id, m.data).await?;
+            } else {
+                block.call_sync_handler(m.port_id, m.data)?;
+            }
+            work_io.call_again = true;
+        }
+
         // ================== shutdown
-        if work_io.finished {
+        // terminated by upstream, handle what is still queued before shutting down
+        if work_io.finished && terminated && !flush && pending {
+            futures_lite::future::yield_now().await;
+            continue;
+        }
+        if work_io.finished && !flush {
             debug!("{} terminating ", block.instance_name().unwrap());
+            if !timed.is_empty() {
+                debug!(
+                    "{} dropping {} timed messages that are not due",
+                    block.instance_name().unwrap(),
+                    timed.messages.len()
+                );
+            }
+            for q in queues.iter() {
+                q.close();
+            }
             join_all(
                 block
                     .stream_inputs_mut()
@@ -424,10 +649,11 @@ pub(crate) async fn run_block(
 
         // ================== blocking
         if !work_io.call_again {
+            let timer = timed.timer();
             if let Some(f) = work_io.block_on.take() {
                 let p = inbox.as_mut().peek();
 
-                match future::select(f, p).await {
+                match future::select(f, future::select(p, timer)).await {
                     Either::Left(_) => {
                         work_io.call_again = true;
                     }
@@ -437,20 +663,212 @@ pub(crate) async fn run_block(
                     }
                 };
             } else {
-                inbox.as_mut().peek().await;
+                future::select(inbox.as_mut().peek(), timer).await;
                 continue;
             }
         }
 
         // ================== work
         work_io.call_again = false;
+        flush = flush && pending;
+        timed.set_limits(&mut block);
         match &mut block {
             Block::Sync(b) => b.work(&mut work_io)?,
             Block::Async(b) => b.work(&mut work_io).await?,
         }
+        if timed.clear_limits(&mut block) {
+            work_io.call_again = true;
+        }
 
         futures_lite::future::yield_now().await;
     }
 
     Ok(())
 }
+
+struct TimedMessage {
+    port_id: usize,
+    data: Pmt,
+    // None, if due right away
+    time: Option<MessageTime>,
+}
+
+// Timed messages are held by the receiving block until they are due. Messages
+// at an item offset limit the stream ports, so that work stops right at the
+// offset. If a block cannot make progress with the limit, although its inputs
+// have the items and its outputs the space to reach the offset (e.g., because
+// it needs more items at once), the limit is lifted for one work call and the
+// message is delivered late. If it lacks items or space, it waits for them.
+#[derive(Default)]
+struct TimedMessages {
+    messages: Vec<TimedMessage>,
+    limited_inputs: Vec<(usize, u64)>,
+    limited_outputs: Vec<(usize, u64)>,
+    // all stream ports had the items or space to reach the limit
+    room: bool,
+    relax: bool,
+}
+
+impl TimedMessages {
+    fn push(&mut self, block: &Block, port_id: usize, data: Pmt, time: MessageTime) {
+        let time = match time {
+            MessageTime::InputItem { port, .. } if port >= block.stream_inputs().len() => {
+                warn!(
+                    "{} timed message for unknown stream input {}, delivering it now",
+                    block.instance_name().unwrap(),
+                    port
+                );
+                None
+            }
+            MessageTime::OutputItem { port, .. } if port >= block.stream_outputs().len() => {
+                warn!(
+                    "{} timed message for unknown stream output {}, delivering it now",
+                    block.instance_name().unwrap(),
+                    port
+                );
+                None
+            }
+            #[cfg(target_arch = "wasm32")]
+            MessageTime::At(_) => {
+                warn!("timed messages at wall-clock instants are not supported on wasm, delivering it now");
+                None
+            }
+            t => Some(t),
+        };
+        self.messages.push(TimedMessage {
+            port_id,
+            data,
+            time,
+        });
+    }
+
+    fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    fn is_due(block: &Block, time: &Option<MessageTime>) -> bool {
+        match time {
+            None => true,
+            Some(MessageTime::At(t)) => *t <= SystemTime::now(),
+            Some(MessageTime::InputItem { port, item }) => {
+                block.stream_input(*port).items_consumed() >= *item
+            }
+            Some(MessageTime::OutputItem { port, item }) => {
+                block.stream_output(*port).items_produced() >= *item
+            }
+        }
+    }
+
+    fn take_due(&mut self, block: &Block) -> Vec<TimedMessage> {
+        let mut due = Vec::new();
+        let mut i = 0;
+        while i < self.messages.len() {
+            if Self::is_due(block, &self.messages[i].time) {
+                due.push(self.messages.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        due
+    }
+
+    // set before work, so that it does not go past the next item offset
+    fn set_limits(&mut self, block: &mut Block) {
+        if self.relax {
+            self.relax = false;
+            return;
+        }
+        let mut inputs: Vec<(usize, u64)> = Vec::new();
+        let mut outputs: Vec<(usize, u64)> = Vec::new();
+        for m in self.messages.iter() {
+            let (offsets, port, item) = match m.time {
+                Some(MessageTime::InputItem { port, item }) => (&mut inputs, port, item),
+                Some(MessageTime::OutputItem { port, item }) => (&mut outputs, port, item),
+                _ => continue,
+            };
+            match offsets.iter_mut().find(|(p, _)| *p == port) {
+                Some((_, i)) => *i = (*i).min(item),
+                None => offsets.push((port, item)),
+            }
+        }
+
+        let mut reach: Option<usize> = None;
+        for (port, item) in inputs {
+            let i = block.stream_input_mut(port);
+            let remaining = item.saturating_sub(i.items_consumed()) as usize;
+            if i.slice::<u8>().len() / i.item_size() > remaining {
+                i.set_limit(Some(remaining));
+                self.limited_inputs.push((port, i.items_consumed()));
+                reach = Some(reach.map_or(remaining, |r| r.min(remaining)));
+            }
+        }
+        for (port, item) in outputs {
+            let o = block.stream_output_mut(port);
+            let remaining = item.saturating_sub(o.items_produced()) as usize;
+            if o.slice::<u8>().len() / o.item_size() > remaining {
+                o.set_limit(Some(remaining));
+                self.limited_outputs.push((port, o.items_produced()));
+                reach = Some(reach.map_or(remaining, |r| r.min(remaining)));
+            }
+        }
+
+        // a stall is only due to the limit, if the block was not short of items
+        // or space anyway
+        if let Some(r) = reach {
+            self.room = block
+                .stream_inputs_mut()
+                .iter_mut()
+                .all(|i| i.finished() || i.slice::<u8>().len() / i.item_size() >= r)
+                && block
+                    .stream_outputs_mut()
+                    .iter_mut()
+                    .all(|o| o.slice::<u8>().len() / o.item_size() >= r);
+        }
+    }
+
+    // returns true, if the block stalled due to the limits and the next call to
+    // work is without them
+    fn clear_limits(&mut self, block: &mut Block) -> bool {
+        let mut stalled = false;
+        for (port, before) in self.limited_inputs.drain(..) {
+            let i = block.stream_input_mut(port);
+            i.set_limit(None);
+            stalled |= i.items_consumed() == before;
+        }
+        for (port, before) in self.limited_outputs.drain(..) {
+            let o = block.stream_output_mut(port);
+            o.set_limit(None);
+            stalled |= o.items_produced() == before;
+        }
+        if stalled && self.room {
+            debug!(
+                "{} cannot make progress up to the offset of a timed message, delivering it late",
+                block.instance_name().unwrap()
+            );
+            self.relax = true;
+        }
+        self.relax
+    }
+
+    // fires when the next message at a wall-clock instant is due
+    fn timer(&self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let next = self
+                .messages
+                .iter()
+                .filter_map(|m| match m.time {
+                    Some(MessageTime::At(t)) => Some(t),
+                    _ => None,
+                })
+                .min();
+            if let Some(t) = next {
+                let d = t
+                    .duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO);
+                return Box::pin(FutureExt::map(Timer::after(d), |_| ()));
+            }
+        }
+        Box::pin(future::pending())
+    }
+}
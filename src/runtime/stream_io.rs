@@ -11,6 +11,8 @@ pub struct StreamInput {
     name: String,
     item_size: usize,
     reader: Option<BufferReader>,
+    n_consumed: u64,
+    limit: Option<usize>,
 }
 
 impl StreamInput {
@@ -19,6 +21,8 @@ impl StreamInput {
             name: name.to_string(),
             item_size,
             reader: None,
+            n_consumed: 0,
+            limit: None,
         }
     }
 
@@ -39,10 +43,23 @@ impl StreamInput {
             return;
         }
         self.reader.as_mut().unwrap().consume(amount);
+        self.n_consumed += amount as u64;
+    }
+
+    pub fn items_consumed(&self) -> u64 {
+        self.n_consumed
+    }
+
+    // used by the runtime to stop work at the stream offset of a timed message
+    pub(crate) fn set_limit(&mut self, items: Option<usize>) {
+        self.limit = items;
     }
 
     pub fn slice<T>(&mut self) -> &'static mut [T] {
-        let (ptr, len) = self.reader.as_mut().unwrap().bytes();
+        let (ptr, mut len) = self.reader.as_mut().unwrap().bytes();
+        if let Some(l) = self.limit {
+            len = len.min(l * self.item_size);
+        }
 
         unsafe { slice::from_raw_parts_mut(ptr as *mut T, len / mem::size_of::<T>()) }
     }
@@ -60,8 +77,10 @@ impl StreamInput {
         self.reader.as_mut().unwrap().finish();
     }
 
+    // not finished while the slice is cut at the offset of a timed message, since
+    // the block does not see all items that are left
     pub fn finished(&self) -> bool {
-        self.reader.as_ref().unwrap().finished()
+        self.limit.is_none() && self.reader.as_ref().unwrap().finished()
     }
 }
 
@@ -70,6 +89,8 @@ pub struct StreamOutput {
     name: String,
     item_size: usize,
     writer: Option<BufferWriter>,
+    n_produced: u64,
+    limit: Option<usize>,
 }
 
 impl StreamOutput {
@@ -78,6 +99,8 @@ impl StreamOutput {
             name: name.to_string(),
             item_size,
             writer: None,
+            n_produced: 0,
+            limit: None,
         }
     }
 
@@ -114,11 +137,24 @@ impl StreamOutput {
         if amount == 0 {
             return;
         }
-        self.writer.as_mut().unwrap().produce(amount)
+        self.writer.as_mut().unwrap().produce(amount);
+        self.n_produced += amount as u64;
+    }
+
+    pub fn items_produced(&self) -> u64 {
+        self.n_produced
+    }
+
+    // used by the runtime to stop work at the stream offset of a timed message
+    pub(crate) fn set_limit(&mut self, items: Option<usize>) {
+        self.limit = items;
     }
 
     pub fn slice<T>(&mut self) -> &'static mut [T] {
-        let (ptr, len) = self.writer.as_mut().unwrap().bytes();
+        let (ptr, mut len) = self.writer.as_mut().unwrap().bytes();
+        if let Some(l) = self.limit {
+            len = len.min(l * self.item_size);
+        }
 
         unsafe { slice::from_raw_parts_mut(ptr.cast::<T>(), len / mem::size_of::<T>()) }
     }
@@ -6,6 +6,8 @@ use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
 use crate::runtime::AsyncMessage;
 use crate::runtime::Block;
+use crate::runtime::PmtKind;
+use serde::{Deserialize, Serialize};
 use slab::Slab;
 use std::any::{Any, TypeId};
 use std::cmp::{Eq, PartialEq};
@@ -315,6 +317,222 @@ impl Topology {
     pub fn block_mut(&mut self, id: usize) -> Option<&mut Block> {
         self.blocks.get_mut(id).and_then(|v| v.as_mut())
     }
+
+    pub fn description(&self) -> FlowgraphDescription {
+        let blocks = self
+            .blocks
+            .iter()
+            .filter_map(|(id, b)| {
+                let b = b.as_ref()?;
+                Some(BlockDescription {
+                    id,
+                    type_name: b.type_name().to_string(),
+                    instance_name: b.instance_name().unwrap_or_default().to_string(),
+                    stream_inputs: b
+                        .stream_inputs()
+                        .iter()
+                        .map(|i| StreamPortDescription {
+                            name: i.name().to_string(),
+                            item_size: i.item_size(),
+                        })
+                        .collect(),
+                    stream_outputs: b
+                        .stream_outputs()
+                        .iter()
+                        .map(|o| StreamPortDescription {
+                            name: o.name().to_string(),
+                            item_size: o.item_size(),
+                        })
+                        .collect(),
+                    message_inputs: b
+                        .message_input_names()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, name)| HandlerDescription {
+                            name,
+                            kinds: b.message_input_kinds(i),
+                        })
+                        .collect(),
+                    message_outputs: b
+                        .message_outputs()
+                        .iter()
+                        .map(|o| o.name().to_string())
+                        .collect(),
+                })
+            })
+            .collect();
+
+        let mut stream_edges: Vec<EdgeDescription> = self
+            .stream_edges
+            .iter()
+            .flat_map(|((src, src_port, _), v)| {
+                v.iter().map(move |(dst, dst_port)| EdgeDescription {
+                    src_block: *src,
+                    src_port: *src_port,
+                    dst_block: *dst,
+                    dst_port: *dst_port,
+                })
+            })
+            .collect();
+        // stream edges are kept in a HashMap
+        stream_edges.sort_by_key(|e| (e.src_block, e.src_port, e.dst_block, e.dst_port));
+
+        let message_edges = self
+            .message_edges
+            .iter()
+            .map(|(src, src_port, dst, dst_port)| EdgeDescription {
+                src_block: *src,
+                src_port: *src_port,
+                dst_block: *dst,
+                dst_port: *dst_port,
+            })
+            .collect();
+
+        FlowgraphDescription {
+            blocks,
+            stream_edges,
+            message_edges,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StreamPortDescription {
+    pub name: String,
+    pub item_size: usize,
+}
+
+// kinds is empty if the block does not declare them (see MessageIoBuilder::input_kinds)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HandlerDescription {
+    pub name: String,
+    pub kinds: Vec<PmtKind>,
+}
+
+// Ports are indices into the port lists of the block description, i.e., stream
+// outputs and inputs for stream edges and message outputs and inputs (handlers)
+// for message edges.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeDescription {
+    pub src_block: usize,
+    pub src_port: usize,
+    pub dst_block: usize,
+    pub dst_port: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BlockDescription {
+    pub id: usize,
+    pub type_name: String,
+    pub instance_name: String,
+    pub stream_inputs: Vec<StreamPortDescription>,
+    pub stream_outputs: Vec<StreamPortDescription>,
+    pub message_inputs: Vec<HandlerDescription>,
+    pub message_outputs: Vec<String>,
+}
+
+// Snapshot of the topology, so that blocks and their message handlers can be
+// described and addressed while the flowgraph is running (and the blocks are
+// moved to their tasks).
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct FlowgraphDescription {
+    pub blocks: Vec<BlockDescription>,
+    pub stream_edges: Vec<EdgeDescription>,
+    pub message_edges: Vec<EdgeDescription>,
+}
+
+impl FlowgraphDescription {
+    // Blocks are given by id, instance name, or type name (if there is only one
+    // block of this type).
+    pub fn block(&self, block: &str) -> Result<&BlockDescription> {
+        match block.parse::<usize>() {
+            Ok(id) => self.block_by_id(id),
+            Err(_) => self.block_by_name(block),
+        }
+    }
+
+    // Handlers are given by id or name.
+    pub(crate) fn resolve(&self, block: &str, handler: &str) -> Result<(usize, usize)> {
+        let b = self.block(block)?;
+        let handler_id = match handler.parse::<usize>() {
+            Ok(id) if id < b.message_inputs.len() => id,
+            _ => b
+                .message_inputs
+                .iter()
+                .position(|h| h.name == handler)
+                .with_context(|| {
+                    format!(
+                        "block {} has no message handler {} (handlers: {})",
+                        b.instance_name,
+                        handler,
+                        b.message_inputs
+                            .iter()
+                            .map(|h| h.name.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                })?,
+        };
+        Ok((b.id, handler_id))
+    }
+
+    // Message outputs are given by id or name.
+    pub(crate) fn resolve_output(&self, block: &str, output: &str) -> Result<(usize, usize)> {
+        let b = self.block(block)?;
+        let output_id = match output.parse::<usize>() {
+            Ok(id) if id < b.message_outputs.len() => id,
+            _ => b
+                .message_outputs
+                .iter()
+                .position(|o| o == output)
+                .with_context(|| {
+                    format!(
+                        "block {} has no message output {} (outputs: {})",
+                        b.instance_name,
+                        output,
+                        b.message_outputs.join(", ")
+                    )
+                })?,
+        };
+        Ok((b.id, output_id))
+    }
+
+    pub(crate) fn check_output(&self, block_id: usize, output_id: usize) -> Result<()> {
+        self.resolve_output(&block_id.to_string(), &output_id.to_string())
+            .map(|_| ())
+    }
+
+    pub(crate) fn check(&self, block_id: usize, handler_id: usize) -> Result<()> {
+        self.resolve(&block_id.to_string(), &handler_id.to_string())
+            .map(|_| ())
+    }
+
+    fn block_by_id(&self, id: usize) -> Result<&BlockDescription> {
+        self.blocks
+            .iter()
+            .find(|b| b.id == id)
+            .with_context(|| format!("block {} not found", id))
+    }
+
+    fn block_by_name(&self, name: &str) -> Result<&BlockDescription> {
+        if let Some(b) = self.blocks.iter().find(|b| b.instance_name == name) {
+            return Ok(b);
+        }
+        let v: Vec<&BlockDescription> =
+            self.blocks.iter().filter(|b| b.type_name == name).collect();
+        match v.len() {
+            0 => bail!("block {} not found", name),
+            1 => Ok(v[0]),
+            _ => bail!(
+                "block type {} is ambiguous, use an instance name ({})",
+                name,
+                v.iter()
+                    .map(|b| b.instance_name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        }
+    }
 }
 
 impl Default for Topology {
@@ -0,0 +1,128 @@
+use anyhow::Result;
+
+use futuresdr::blocks::adsb::crc24;
+use futuresdr::blocks::adsb::Decoder;
+use futuresdr::blocks::adsb::DecoderBuilder;
+use futuresdr::blocks::adsb::Demodulator;
+use futuresdr::blocks::adsb::Magnitude;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::MessageDebug;
+use futuresdr::blocks::MessageDebugBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const IDENTIFICATION: &str = "8D4840D6202CC371C32CE0576098";
+const POSITION_ODD: &str = "8D40621D58C386435CC412692AD6";
+const POSITION_EVEN: &str = "8D40621D58C382D690C8AC2863A7";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+// 2 Msps rtl_sdr style IQ: preamble, PPM encoded bits, and some silence
+fn modulate(frames: &[Vec<u8>]) -> Vec<u8> {
+    let mut mag = vec![false; 100];
+    for f in frames {
+        let mut preamble = [false; 16];
+        for p in [0, 2, 7, 9] {
+            preamble[p] = true;
+        }
+        mag.extend_from_slice(&preamble);
+        for i in 0..f.len() * 8 {
+            let bit = f[i / 8] & (0x80 >> (i % 8)) != 0;
+            mag.push(bit);
+            mag.push(!bit);
+        }
+        mag.extend_from_slice(&[false; 100]);
+    }
+
+    mag.iter()
+        .flat_map(|high| if *high { [255u8, 128] } else { [128u8, 128] })
+        .collect()
+}
+
+#[test]
+fn adsb_crc() {
+    for f in [IDENTIFICATION, POSITION_ODD, POSITION_EVEN] {
+        assert_eq!(crc24(&hex(f)), 0);
+    }
+
+    let mut frame = hex(IDENTIFICATION);
+    frame[6] ^= 0x04;
+    assert_ne!(crc24(&frame), 0);
+}
+
+#[test]
+fn adsb_decode() -> Result<()> {
+    let mut corrupted = hex(IDENTIFICATION);
+    corrupted[7] ^= 0x10;
+
+    let frames = vec![
+        hex(IDENTIFICATION),
+        corrupted,
+        hex(POSITION_ODD),
+        hex(POSITION_EVEN),
+    ];
+
+    let path = std::env::temp_dir().join(format!("futuresdr-adsb-{}.bin", std::process::id()));
+    std::fs::write(&path, modulate(&frames))?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSource::new(1, path.to_str().unwrap().to_string()));
+    let mag = fg.add_block(Magnitude::new());
+    let demod = fg.add_block(Demodulator::new());
+    let decoder = fg.add_block(DecoderBuilder::new().build());
+    let snk = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+
+    fg.connect_stream(src, "out", mag, "in")?;
+    fg.connect_stream(mag, "out", demod, "in")?;
+    fg.connect_message(demod, "out", decoder, "in")?;
+    fg.connect_message(decoder, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+    std::fs::remove_file(&path)?;
+
+    let demod = fg.block_async::<Demodulator>(demod).unwrap();
+    assert_eq!(demod.frames(), 4);
+
+    let decoder = fg.block_async::<Decoder>(decoder).unwrap();
+    assert_eq!(decoder.decoded(), 4);
+    assert_eq!(decoder.corrected(), 1);
+    assert_eq!(decoder.crc_errors(), 0);
+
+    let snk = fg.block_async::<MessageDebug>(snk).unwrap();
+    let messages: Vec<serde_json::Value> = snk
+        .messages()
+        .iter()
+        .map(|m| match m {
+            Pmt::String(s) => serde_json::from_str(s).unwrap(),
+            _ => panic!("unexpected message {:?}", m),
+        })
+        .collect();
+    assert_eq!(messages.len(), 4);
+
+    for m in &messages[0..2] {
+        assert_eq!(m["icao"], "4840d6");
+        assert_eq!(m["type"], "identification");
+        assert_eq!(m["callsign"], "KLM1023");
+    }
+
+    assert_eq!(messages[2]["icao"], "40621d");
+    assert_eq!(messages[2]["cpr_format"], "odd");
+    assert!(messages[2]["latitude"].is_null());
+
+    let position = &messages[3];
+    assert_eq!(position["type"], "position");
+    assert_eq!(position["cpr_format"], "even");
+    assert_eq!(position["altitude"], 38000);
+    let lat = position["latitude"].as_f64().unwrap();
+    let lon = position["longitude"].as_f64().unwrap();
+    assert!((lat - 52.2572).abs() < 1e-3);
+    assert!((lon - 3.9194).abs() < 1e-3);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anyhow::Result;
+
+use futuresdr::blocks::audio::FileSource;
+use futuresdr::blocks::wav::write_header;
+use futuresdr::blocks::wav::SampleFormat;
+use futuresdr::blocks::wav::WavSpec;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn audio_file_source_finishes() -> Result<()> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 8000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+    let samples: Vec<i16> = (0..10_000).map(|i| (i % 1000) as i16 * 10).collect();
+    let mut data = Vec::new();
+    write_header(&mut data, &spec, samples.len() as u64 * 2)?;
+    data.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+
+    let path = std::env::temp_dir().join(format!(
+        "futuresdr-audio-file-source-{}.wav",
+        std::process::id()
+    ));
+    std::fs::write(&path, data)?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSource::new(path.to_str().unwrap()));
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    // the source only produces decoded samples and finishes at the end of the file
+    fg = Runtime::new().run(fg)?;
+    std::fs::remove_file(&path)?;
+
+    let snk = fg.block_async::<VectorSink<f32>>(snk).unwrap();
+    let items = snk.items();
+    assert_eq!(items.len(), samples.len());
+    for (a, b) in items.iter().zip(samples.iter()) {
+        assert!((a - *b as f32 / i16::MAX as f32).abs() < 1e-3);
+    }
+    Ok(())
+}
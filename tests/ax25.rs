@@ -0,0 +1,99 @@
+use anyhow::Result;
+
+use futuresdr::blocks::ax25::hdlc;
+use futuresdr::blocks::ax25::Address;
+use futuresdr::blocks::ax25::Decoder;
+use futuresdr::blocks::ax25::DecoderBuilder;
+use futuresdr::blocks::ax25::DemodulatorBuilder;
+use futuresdr::blocks::ax25::Frame;
+use futuresdr::blocks::ax25::Modulator;
+use futuresdr::blocks::ax25::ModulatorBuilder;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::blocks::MessageDebug;
+use futuresdr::blocks::MessageDebugBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const PACKET: &str = "N0CALL-7>APRS,WIDE1-1*,WIDE2-1:!4903.50N/07201.75W-Test 001234";
+
+#[test]
+fn ax25_fcs() {
+    assert_eq!(hdlc::fcs(b"123456789"), 0x906e);
+}
+
+#[test]
+fn ax25_frame() -> Result<()> {
+    let frame: Frame = PACKET.parse()?;
+    assert_eq!(frame.source, Address::new("N0CALL", 7)?);
+    assert_eq!(frame.destination, Address::new("APRS", 0)?);
+    assert_eq!(frame.digipeaters.len(), 2);
+    assert!(frame.digipeaters[0].repeated);
+    assert!(!frame.digipeaters[1].repeated);
+
+    let bytes = frame.to_bytes();
+    assert_eq!(&bytes[0..7], &[0x82, 0xa0, 0xa4, 0xa6, 0x40, 0x40, 0xe0]);
+    assert_eq!(Frame::parse(&bytes)?, frame);
+    assert_eq!(Frame::parse(&bytes)?.to_string(), PACKET);
+
+    assert!("N0CALL>APRS".parse::<Frame>().is_err());
+    assert!("N0CALL-16>APRS:foo".parse::<Frame>().is_err());
+    assert!(Frame::parse(&bytes[0..10]).is_err());
+    Ok(())
+}
+
+#[test]
+fn ax25_hdlc() {
+    let frame = vec![0xff; 20];
+    let bits = hdlc::encode(&frame, 4);
+
+    let mut deframer = hdlc::Deframer::new();
+    let frames: Vec<Vec<u8>> = bits.iter().filter_map(|b| deframer.push(*b)).collect();
+    assert_eq!(frames, vec![frame]);
+}
+
+fn loopback(sample_rate: f32) -> Result<()> {
+    let n_frames = 5;
+
+    let mut fg = Flowgraph::new();
+    let src =
+        fg.add_block(MessageBurstBuilder::new(Pmt::String(PACKET.to_string()), n_frames).build());
+    let modulator = fg.add_block(ModulatorBuilder::new(sample_rate).build());
+    let channel = fg.add_block(Apply::new(|i: &f32| -> f32 { 0.1 * i }));
+    let demodulator = fg.add_block(DemodulatorBuilder::new(sample_rate).build());
+    let decoder = fg.add_block(DecoderBuilder::new().build());
+    let snk = fg.add_block(
+        MessageDebugBuilder::new()
+            .print(false)
+            .store(n_frames as usize)
+            .build(),
+    );
+
+    fg.connect_message(src, "out", modulator, "in")?;
+    fg.connect_stream(modulator, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", demodulator, "in")?;
+    fg.connect_message(demodulator, "out", decoder, "in")?;
+    fg.connect_message(decoder, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let modulator = fg.block_async::<Modulator>(modulator).unwrap();
+    assert_eq!(modulator.frames(), n_frames);
+    let decoder = fg.block_async::<Decoder>(decoder).unwrap();
+    assert_eq!(decoder.decoded(), n_frames);
+
+    let snk = fg.block_async::<MessageDebug>(snk).unwrap();
+    assert_eq!(snk.received(), n_frames);
+    assert!(snk
+        .messages()
+        .iter()
+        .all(|m| *m == Pmt::String(PACKET.to_string())));
+    Ok(())
+}
+
+#[test]
+fn ax25_loopback() -> Result<()> {
+    loopback(48000.0)?;
+    loopback(44100.0)
+}
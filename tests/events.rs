@@ -0,0 +1,171 @@
+use anyhow::{bail, Result};
+use async_io::block_on;
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::time::Duration;
+
+use futuresdr::blocks::MessageSinkBuilder;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::runtime::AsyncKernel;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::EventSubscription;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphEvent;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+// fails in work
+struct Failing;
+
+impl Failing {
+    #[allow(clippy::new_ret_no_self)]
+    fn new() -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Failing").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().build(),
+            Failing,
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Failing {
+    async fn work(
+        &mut self,
+        _io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        bail!("broken")
+    }
+}
+
+#[test]
+fn events_lifecycle_and_messages() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::U32(7), Duration::from_millis(50))
+            .n_messages(3)
+            .build(),
+    );
+    let snk = fg.add_block(MessageSinkBuilder::new().build());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, handle) = rt.start(fg);
+    let events = handle.subscribe(EventSubscription::new().messages(src, 0))?;
+    // the stream ends with the flowgraph
+    let events: Vec<FlowgraphEvent> = block_on(events.collect());
+    block_on(task)?;
+
+    for block in [src, snk] {
+        let lifecycle: Vec<&FlowgraphEvent> = events
+            .iter()
+            .filter(|e| e.block() == Some(block) && !matches!(e, FlowgraphEvent::Message { .. }))
+            .collect();
+        assert_eq!(
+            lifecycle,
+            vec![
+                &FlowgraphEvent::Initialized { block },
+                &FlowgraphEvent::Finished { block }
+            ]
+        );
+    }
+    let messages: Vec<&FlowgraphEvent> = events
+        .iter()
+        .filter(|e| matches!(e, FlowgraphEvent::Message { .. }))
+        .collect();
+    assert_eq!(messages.len(), 3);
+    assert!(messages.iter().all(|m| **m
+        == FlowgraphEvent::Message {
+            block: src,
+            port: 0,
+            data: Pmt::U32(7)
+        }));
+    assert!(handle
+        .subscribe(EventSubscription::new().messages(src, 1))
+        .is_err());
+
+    // late subscribers get the last lifecycle event of each block
+    let events: Vec<FlowgraphEvent> =
+        block_on(handle.subscribe(EventSubscription::new())?.collect());
+    assert_eq!(
+        events,
+        vec![
+            FlowgraphEvent::Finished { block: src },
+            FlowgraphEvent::Finished { block: snk }
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn events_metrics() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(20))
+            .n_messages(10)
+            .build(),
+    );
+    let snk = fg.add_block(MessageSinkBuilder::new().build());
+    fg.connect_message(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, handle) = rt.start(fg);
+    let events = handle.subscribe(EventSubscription::new().metrics(Duration::from_millis(10)))?;
+    let events: Vec<FlowgraphEvent> = block_on(events.collect());
+    block_on(task)?;
+
+    let received: Vec<u64> = events
+        .iter()
+        .filter_map(|e| match e {
+            FlowgraphEvent::Metrics { blocks } => {
+                assert_eq!(blocks.len(), 2);
+                assert!(blocks[src].message_inputs.is_empty());
+                assert_eq!(blocks[snk].block, snk);
+                assert_eq!(blocks[snk].message_inputs[0].name, "in");
+                Some(blocks[snk].message_inputs[0].received)
+            }
+            _ => None,
+        })
+        .collect();
+    assert!(received.len() > 5);
+    assert!(received.windows(2).all(|w| w[0] <= w[1]));
+    assert!(*received.last().unwrap() > 0);
+    Ok(())
+}
+
+#[test]
+fn events_error() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let failing = fg.add_block(Failing::new());
+
+    let rt = Runtime::new();
+    let (task, handle) = rt.start(fg);
+    // the flowgraph does not terminate when a block fails
+    task.detach();
+    let mut events = handle.subscribe(EventSubscription::new())?;
+    block_on(async move {
+        loop {
+            match events.next().await {
+                Some(FlowgraphEvent::Error { block, error }) => {
+                    assert_eq!(block, failing);
+                    assert!(error.contains("broken"));
+                    break;
+                }
+                Some(FlowgraphEvent::Initialized { .. }) => {}
+                e => panic!("unexpected event {:?}", e),
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+
+use futuresdr::blocks::fec::BlockInterleaver;
+use futuresdr::blocks::fec::ConvolutionalCode;
+use futuresdr::blocks::fec::ConvolutionalEncoderBuilder;
+use futuresdr::blocks::fec::InterleaverBuilder;
+use futuresdr::blocks::fec::ReedSolomon;
+use futuresdr::blocks::fec::ReedSolomonDecoder;
+use futuresdr::blocks::fec::ReedSolomonDecoderBuilder;
+use futuresdr::blocks::fec::ReedSolomonEncoderBuilder;
+use futuresdr::blocks::fec::Viterbi;
+use futuresdr::blocks::fec::ViterbiDecoderBuilder;
+use futuresdr::blocks::fec::{PUNCTURE_2_3, PUNCTURE_3_4};
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn random_bits(n: usize) -> Vec<u8> {
+    (0..n).map(|_| rand::random::<bool>() as u8).collect()
+}
+
+fn soft(bits: &[u8]) -> Vec<f32> {
+    bits.iter()
+        .map(|b| if *b == 1 { 1.0 } else { -1.0 })
+        .collect()
+}
+
+#[test]
+fn fec_convolutional() -> Result<()> {
+    let code = ConvolutionalCode::k7();
+    assert_eq!(code.encode(&[0; 10]), vec![0; 32]);
+    // impulse response interleaves both polynomials
+    assert_eq!(
+        code.encode(&[1]),
+        vec![1, 1, 0, 1, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1]
+    );
+    assert_eq!(code.encoded_len(100), 212);
+
+    let code = code.puncturing(&PUNCTURE_3_4)?;
+    assert_eq!(code.encoded_len(102), 144);
+    assert_eq!(code.encode(&random_bits(102)).len(), 144);
+
+    assert!(ConvolutionalCode::new(7, vec![0o200]).is_err());
+    assert!(ConvolutionalCode::k7()
+        .puncturing(&[true, true, false])
+        .is_err());
+    assert!(ConvolutionalCode::k7()
+        .puncturing(&[true, true, false, false])
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn fec_viterbi() -> Result<()> {
+    for pattern in [&[true, true][..], &PUNCTURE_2_3[..], &PUNCTURE_3_4[..]] {
+        let code = ConvolutionalCode::k7().puncturing(pattern)?;
+        let viterbi = Viterbi::new(code.clone());
+
+        let bits = random_bits(500);
+        let mut s = soft(&code.encode(&bits));
+        assert_eq!(viterbi.decode(&s), bits);
+
+        // sparse bit errors and weak soft bits
+        for i in (7..s.len()).step_by(61) {
+            s[i] = -s[i];
+        }
+        for i in (3..s.len()).step_by(5) {
+            s[i] *= 0.5;
+        }
+        assert_eq!(viterbi.decode(&s), bits);
+    }
+    Ok(())
+}
+
+#[test]
+fn fec_reed_solomon() -> Result<()> {
+    let rs = ReedSolomon::new();
+    assert_eq!(rs.max_data_len(), 223);
+
+    let data: Vec<u8> = (0..223).map(|_| rand::random()).collect();
+    let codeword = rs.encode(&data)?;
+    assert_eq!(codeword.len(), 255);
+    assert_eq!(&codeword[..223], &data[..]);
+
+    let mut c = codeword.clone();
+    assert_eq!(rs.decode(&mut c)?, 0);
+
+    for i in 0..16 {
+        c[i * 15 + 3] ^= 0x5a + i as u8;
+    }
+    assert_eq!(rs.decode(&mut c)?, 16);
+    assert_eq!(c, codeword);
+
+    // beyond the correction capability
+    for i in 0..17 {
+        c[i * 14] ^= 0xff;
+    }
+    assert!(rs.decode(&mut c).is_err());
+
+    // shortened code
+    let codeword = rs.encode(b"FutureSDR")?;
+    let mut c = codeword.clone();
+    c[0] = 0;
+    c[20] ^= 1;
+    assert_eq!(rs.decode(&mut c)?, 2);
+    assert_eq!(c, codeword);
+
+    assert!(rs.encode(&[0; 224]).is_err());
+    Ok(())
+}
+
+#[test]
+fn fec_block_interleaver() -> Result<()> {
+    let interleaver = BlockInterleaver::new(2, 3)?;
+    let data: Vec<u32> = (0..12).collect();
+    let interleaved = interleaver.interleave(&data)?;
+    assert_eq!(interleaved, vec![0, 3, 1, 4, 2, 5, 6, 9, 7, 10, 8, 11]);
+    assert_eq!(interleaver.deinterleave(&interleaved)?, data);
+    assert!(interleaver.interleave(&data[..5]).is_err());
+    Ok(())
+}
+
+#[test]
+fn fec_stream_blocks() -> Result<()> {
+    let block_len = 98;
+    let code = ConvolutionalCode::k7().puncturing(&PUNCTURE_2_3)?;
+    let encoded_len = code.encoded_len(block_len);
+    assert_eq!(encoded_len, 13 * 12);
+    let bits = random_bits(20 * block_len);
+
+    // burst errors, spread by the interleaver
+    let mut n = 0;
+    let channel = move |b: &u8| -> f32 {
+        n += 1;
+        let s = if *b == 1 { 1.0 } else { -1.0 };
+        if n % 200 < 3 {
+            -s
+        } else {
+            s
+        }
+    };
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(bits.clone()));
+    let enc = fg.add_block(
+        ConvolutionalEncoderBuilder::new(block_len)
+            .code(code.clone())
+            .build(),
+    );
+    let interleaver = fg.add_block(InterleaverBuilder::<u8>::new(13, 12).build()?);
+    let channel = fg.add_block(Apply::new(channel));
+    let deinterleaver = fg.add_block(
+        InterleaverBuilder::<f32>::new(13, 12)
+            .deinterleave(true)
+            .build()?,
+    );
+    let dec = fg.add_block(ViterbiDecoderBuilder::new(block_len).code(code).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+
+    fg.connect_stream(src, "out", enc, "in")?;
+    fg.connect_stream(enc, "out", interleaver, "in")?;
+    fg.connect_stream(interleaver, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", deinterleaver, "in")?;
+    fg.connect_stream(deinterleaver, "out", dec, "in")?;
+    fg.connect_stream(dec, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u8>>(snk).unwrap();
+    assert_eq!(snk.items(), &bits);
+
+    Ok(())
+}
+
+#[test]
+fn fec_pdu_blocks() -> Result<()> {
+    let n_messages = 10;
+    let data: Vec<u8> = (0..500).map(|i| i as u8).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MessageBurstBuilder::new(Pmt::Blob(data), n_messages).build());
+    let enc = fg.add_block(ReedSolomonEncoderBuilder::new().build());
+    let dec = fg.add_block(ReedSolomonDecoderBuilder::new().build());
+    let snk = fg.add_block(MessageSink::new());
+
+    fg.connect_message(src, "out", enc, "in")?;
+    fg.connect_message(enc, "out", dec, "in")?;
+    fg.connect_message(dec, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let dec = fg.block_async::<ReedSolomonDecoder>(dec).unwrap();
+    assert_eq!(dec.decoded(), n_messages);
+    assert_eq!(dec.failed(), 0);
+    let snk = fg.block_async::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), n_messages);
+
+    Ok(())
+}
@@ -0,0 +1,131 @@
+use anyhow::Result;
+use async_io::block_on;
+use std::path::PathBuf;
+
+use futuresdr::blocks::FileSink;
+use futuresdr::blocks::FileSinkBuilder;
+use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::NullSourceBuilder;
+use futuresdr::blocks::ThrottleBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn temp_dir(name: &str) -> Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!(
+        "futuresdr-file-sink-{}-{}",
+        name,
+        std::process::id()
+    ));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+fn read_u32(file: &str) -> Result<Vec<u32>> {
+    Ok(std::fs::read(file)?
+        .chunks_exact(4)
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect())
+}
+
+#[test]
+fn file_sink_rotate() -> Result<()> {
+    let dir = temp_dir("rotate")?;
+    let pattern = dir.join("capture-{seq}-{time}.bin");
+    let items: Vec<u32> = (0..1000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let snk = fg.add_block(
+        FileSinkBuilder::new(4, pattern.to_str().unwrap())
+            .rotate_size(1002)
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<FileSink>(snk).unwrap();
+    assert_eq!(snk.n_written(), 1000);
+    assert_eq!(snk.files().len(), 4);
+
+    let mut read = Vec::new();
+    for (i, f) in snk.files().iter().enumerate() {
+        assert!(f.contains(&format!("capture-{}-2", i)));
+        assert!(f.ends_with("Z.bin"));
+        let r = read_u32(f)?;
+        assert_eq!(r.len(), 250);
+        read.extend(r);
+    }
+    assert_eq!(read, items);
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn file_sink_max_items() -> Result<()> {
+    let dir = temp_dir("max-items")?;
+    let file = dir.join("capture.bin");
+    let file = file.to_str().unwrap();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new((0..1000).collect::<Vec<u32>>()));
+    let snk = fg.add_block(FileSinkBuilder::new(4, file).max_items(300).build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<FileSink>(snk).unwrap();
+    assert_eq!(snk.n_written(), 300);
+    assert_eq!(snk.files(), &vec![file.to_string()]);
+    assert_eq!(read_u32(file)?, (0..300).collect::<Vec<u32>>());
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
+
+#[test]
+fn file_sink_record() -> Result<()> {
+    let dir = temp_dir("record")?;
+    let file = dir.join("capture.bin");
+    let file = file.to_str().unwrap().to_string();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSourceBuilder::new(4).build());
+    let throttle = fg.add_block(ThrottleBuilder::new(4, 1e6).build());
+    let head = fg.add_block(HeadBuilder::new(4, 300_000).build());
+    let snk = fg.add_block(FileSinkBuilder::new(4, &file).recording(false).build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = block_on(async move {
+        assert_eq!(handle.callback(snk, 0, Pmt::Null).await?, Pmt::U32(0));
+        let start = Pmt::String("start".to_string());
+        assert_eq!(handle.callback(snk, 0, start.clone()).await?, Pmt::U32(1));
+        assert_eq!(handle.callback(snk, 0, Pmt::U32(0)).await?, Pmt::U32(0));
+        assert_eq!(handle.callback(snk, 0, start).await?, Pmt::U32(1));
+        task.await
+    })?;
+
+    let snk = fg.block_async::<FileSink>(snk).unwrap();
+    assert!(snk.n_written() > 0);
+    assert!(snk.n_written() <= 300_000);
+    assert!(!snk.files().is_empty());
+    assert_eq!(snk.files()[0], file);
+    if snk.files().len() > 1 {
+        assert_eq!(snk.files()[1], format!("{}.1", file));
+    }
+
+    let n: usize = snk
+        .files()
+        .iter()
+        .map(|f| std::fs::metadata(f).unwrap().len() as usize / 4)
+        .sum();
+    assert_eq!(n, snk.n_written());
+
+    std::fs::remove_dir_all(dir)?;
+    Ok(())
+}
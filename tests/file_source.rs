@@ -0,0 +1,137 @@
+use anyhow::Result;
+use async_io::block_on;
+use std::path::PathBuf;
+
+use futuresdr::blocks::FileSourceBuilder;
+use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSinkBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+// n u32 items, followed by a partial item
+fn write_file(name: &str, n: u32) -> Result<PathBuf> {
+    let path = std::env::temp_dir().join(format!(
+        "futuresdr-file-source-{}-{}.bin",
+        name,
+        std::process::id()
+    ));
+    let mut data: Vec<u8> = (0..n).flat_map(|i| i.to_ne_bytes()).collect();
+    data.extend_from_slice(&[0xff, 0xff]);
+    std::fs::write(&path, data)?;
+    Ok(path)
+}
+
+fn read(src: FileSourceBuilder, head: Option<u64>) -> Result<Vec<u32>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(src.build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    if let Some(n) = head {
+        let head = fg.add_block(HeadBuilder::new(4, n).build());
+        fg.connect_stream(src, "out", head, "in")?;
+        fg.connect_stream(head, "out", snk, "in")?;
+    } else {
+        fg.connect_stream(src, "out", snk, "in")?;
+    }
+
+    fg = Runtime::new().run(fg)?;
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    Ok(snk.items().clone())
+}
+
+#[test]
+fn file_source_items() -> Result<()> {
+    let path = write_file("items", 10_000)?;
+    let name = path.to_str().unwrap().to_string();
+
+    let items = read(FileSourceBuilder::new(4, name.clone()), None)?;
+    assert_eq!(items, (0..10_000).collect::<Vec<u32>>());
+
+    let items = read(FileSourceBuilder::new(4, name.clone()).offset(10), None)?;
+    assert_eq!(items, (10..10_000).collect::<Vec<u32>>());
+
+    let items = read(
+        FileSourceBuilder::new(4, name.clone())
+            .offset(10)
+            .length(100),
+        None,
+    )?;
+    assert_eq!(items, (10..110).collect::<Vec<u32>>());
+
+    let items = read(
+        FileSourceBuilder::new(4, name).length(1_000_000),
+        Some(5_000),
+    )?;
+    assert_eq!(items, (0..5_000).collect::<Vec<u32>>());
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn file_source_repeat() -> Result<()> {
+    let path = write_file("repeat", 10)?;
+    let name = path.to_str().unwrap().to_string();
+
+    let items = read(
+        FileSourceBuilder::new(4, name.clone()).repeat(true),
+        Some(35),
+    )?;
+    assert_eq!(items, (0..35).map(|i| i % 10).collect::<Vec<u32>>());
+
+    let items = read(
+        FileSourceBuilder::new(4, name)
+            .offset(2)
+            .length(3)
+            .repeat(true),
+        Some(7),
+    )?;
+    assert_eq!(items, vec![2, 3, 4, 2, 3, 4, 2]);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn file_source_seek() -> Result<()> {
+    let path = write_file("seek", 1000)?;
+    let name = path.to_str().unwrap().to_string();
+    let n_items = 10_000_000;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSourceBuilder::new(4, name).repeat(true).build());
+    let head = fg.add_block(HeadBuilder::new(4, n_items).build());
+    let snk = fg.add_block(NullSinkBuilder::new(4).build());
+    fg.connect_stream(src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    block_on(async move {
+        assert_eq!(handle.callback(src, 0, Pmt::U64(500)).await?, Pmt::U64(500));
+        // out of range
+        assert!(handle.callback(src, 0, Pmt::U64(2000)).await.is_err());
+        assert!(handle.callback(src, 0, Pmt::U32(1001)).await.is_err());
+        // the end of the file is a valid target, the source wraps around
+        assert_eq!(
+            handle.callback(src, 0, Pmt::U32(1000)).await?,
+            Pmt::U64(1000)
+        );
+        assert!(matches!(
+            handle.callback(src, 0, Pmt::Null).await?,
+            Pmt::U64(_)
+        ));
+
+        let fg = task.await?;
+        let snk = fg.block_async::<NullSink>(snk).unwrap();
+        assert_eq!(snk.n_received(), n_items as usize);
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
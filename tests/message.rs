@@ -0,0 +1,216 @@
+use anyhow::Result;
+use async_io::block_on;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::time::Duration;
+use std::time::Instant;
+
+use futuresdr::blocks::MessageDebug;
+use futuresdr::blocks::MessageDebugBuilder;
+use futuresdr::blocks::MessageFilter;
+use futuresdr::blocks::MessageFilterBuilder;
+use futuresdr::blocks::MessageRateLimiter;
+use futuresdr::blocks::MessageRateLimiterBuilder;
+use futuresdr::blocks::MessageRouter;
+use futuresdr::blocks::MessageRouterBuilder;
+use futuresdr::blocks::MessageSourceBuilder;
+use futuresdr::runtime::AsyncKernel;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::PmtKind;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+// posts the messages and finishes
+struct Messages {
+    messages: Vec<Pmt>,
+}
+
+impl Messages {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(messages: Vec<Pmt>) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Messages").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Messages { messages },
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Messages {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for m in self.messages.drain(..) {
+            mio.post(0, m).await;
+        }
+        io.finished = true;
+        Ok(())
+    }
+}
+
+fn stored(fg: &Flowgraph, id: usize) -> Vec<Pmt> {
+    fg.block_async::<MessageDebug>(id).unwrap().messages()
+}
+
+#[test]
+fn message_debug() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::U32(1), Duration::from_millis(10))
+            .n_messages(5)
+            .build(),
+    );
+    let debug = fg.add_block(MessageDebugBuilder::new().print(false).store(3).build());
+    fg.connect_message(src, "out", debug, "in")?;
+    let all = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    fg.connect_message(src, "out", all, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = block_on(async move {
+        handle
+            .call_by_name("MessageDebug_1", "in", Pmt::String("foo".to_string()))
+            .await?;
+        let m = handle
+            .callback_by_name("MessageDebug_1", "messages", Pmt::Null)
+            .await?;
+        match m {
+            Pmt::VecPmt(v) => assert!(v.contains(&Pmt::String("foo".to_string()))),
+            _ => panic!("unexpected response {:?}", m),
+        }
+        handle
+            .call_by_name("MessageDebug_0", "clear", Pmt::Null)
+            .await?;
+        task.await
+    })?;
+
+    let d = fg.block_async::<MessageDebug>(debug).unwrap();
+    assert_eq!(d.received(), 5);
+    // only the last ones are kept, and some of them might have been cleared
+    assert!(d.messages().len() <= 3);
+    assert!(d.messages().iter().all(|m| *m == Pmt::U32(1)));
+    let all = fg.block_async::<MessageDebug>(all).unwrap();
+    assert_eq!(all.received(), 6);
+    assert_eq!(all.messages().len(), 6);
+    Ok(())
+}
+
+#[test]
+fn message_filter() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Messages::new((0..10u32).map(Pmt::U32).collect()));
+    let filter =
+        fg.add_block(MessageFilterBuilder::new(|p| matches!(p, Pmt::U32(i) if i % 2 == 0)).build());
+    let debug = fg.add_block(MessageDebugBuilder::new().print(false).store(100).build());
+    fg.connect_message(src, "out", filter, "in")?;
+    fg.connect_message(filter, "out", debug, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let expected: Vec<Pmt> = vec![0u32, 2, 4, 6, 8].into_iter().map(Pmt::U32).collect();
+    assert_eq!(stored(&fg, debug), expected);
+    let filter = fg.block_async::<MessageFilter>(filter).unwrap();
+    assert_eq!(filter.passed(), 5);
+    assert_eq!(filter.dropped(), 5);
+    Ok(())
+}
+
+#[test]
+fn message_router() -> Result<()> {
+    let mut freq = HashMap::new();
+    freq.insert("freq".to_string(), Pmt::Double(100e6));
+    let mut gain = HashMap::new();
+    gain.insert("gain".to_string(), Pmt::Double(20.0));
+    let messages = vec![
+        Pmt::U32(1),
+        Pmt::String("foo".to_string()),
+        Pmt::MapStrPmt(freq.clone()),
+        Pmt::U64(2),
+        Pmt::MapStrPmt(gain.clone()),
+        Pmt::Null,
+        Pmt::Bool(true),
+    ];
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Messages::new(messages));
+    let router = fg.add_block(
+        MessageRouterBuilder::new()
+            .kind(PmtKind::U32, "int")
+            .kind(PmtKind::U64, "int")
+            .key("freq", "freq")
+            .kind(PmtKind::String, "string")
+            .default_output(true)
+            .build(),
+    );
+    let int = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    let freq_snk = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    let string = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    let default = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    fg.connect_message(src, "out", router, "in")?;
+    fg.connect_message(router, "int", int, "in")?;
+    fg.connect_message(router, "freq", freq_snk, "in")?;
+    fg.connect_message(router, "string", string, "in")?;
+    fg.connect_message(router, "default", default, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    assert_eq!(stored(&fg, int), vec![Pmt::U32(1), Pmt::U64(2)]);
+    assert_eq!(stored(&fg, freq_snk), vec![Pmt::MapStrPmt(freq)]);
+    assert_eq!(stored(&fg, string), vec![Pmt::String("foo".to_string())]);
+    assert_eq!(
+        stored(&fg, default),
+        vec![Pmt::MapStrPmt(gain), Pmt::Null, Pmt::Bool(true)]
+    );
+    let router = fg.block_async::<MessageRouter>(router).unwrap();
+    assert_eq!(router.dropped(), 0);
+    Ok(())
+}
+
+#[test]
+fn message_rate_limiter() -> Result<()> {
+    // delay: 10 messages at 50/s with a burst of 5 take at least 100ms
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Messages::new((0..10u32).map(Pmt::U32).collect()));
+    let limiter = fg.add_block(MessageRateLimiterBuilder::new(50.0).burst(5).build());
+    let debug = fg.add_block(MessageDebugBuilder::new().print(false).store(100).build());
+    fg.connect_message(src, "out", limiter, "in")?;
+    fg.connect_message(limiter, "out", debug, "in")?;
+
+    let start = Instant::now();
+    fg = Runtime::new().run(fg)?;
+    assert!(start.elapsed() >= Duration::from_millis(100));
+    let expected: Vec<Pmt> = (0..10u32).map(Pmt::U32).collect();
+    assert_eq!(stored(&fg, debug), expected);
+
+    // drop: only the burst passes
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Messages::new((0..10u32).map(Pmt::U32).collect()));
+    let limiter = fg.add_block(
+        MessageRateLimiterBuilder::new(1.0)
+            .burst(3)
+            .drop(true)
+            .build(),
+    );
+    let debug = fg.add_block(MessageDebugBuilder::new().print(false).store(100).build());
+    fg.connect_message(src, "out", limiter, "in")?;
+    fg.connect_message(limiter, "out", debug, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let expected: Vec<Pmt> = (0..3u32).map(Pmt::U32).collect();
+    assert_eq!(stored(&fg, debug), expected);
+    let limiter = fg.block_async::<MessageRateLimiter>(limiter).unwrap();
+    assert_eq!(limiter.dropped(), 7);
+    Ok(())
+}
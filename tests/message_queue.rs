@@ -0,0 +1,304 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::runtime::AsyncKernel;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::MessagePriority;
+use futuresdr::runtime::OverflowPolicy;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+// posts 0..n as fast as possible
+struct Counter {
+    n: u32,
+}
+
+impl Counter {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(n: u32) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Counter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Counter { n },
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Counter {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for i in 0..self.n {
+            mio.post(0, Pmt::U32(i)).await;
+        }
+        io.finished = true;
+        Ok(())
+    }
+}
+
+// stalls on the first message, so that the queue fills up
+struct SlowSink {
+    values: Arc<Mutex<Vec<u32>>>,
+}
+
+impl SlowSink {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(values: Arc<Mutex<Vec<u32>>>, depth: usize, policy: OverflowPolicy) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("SlowSink").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_sync_input(
+                    "in",
+                    |block: &mut SlowSink,
+                     _mio: &mut MessageIo<SlowSink>,
+                     _meta: &mut BlockMeta,
+                     p: Pmt| {
+                        let mut v = block.values.lock().unwrap();
+                        if v.is_empty() {
+                            std::thread::sleep(Duration::from_millis(200));
+                        }
+                        if let Pmt::U32(i) = p {
+                            v.push(i);
+                        }
+                        Ok(Pmt::Null)
+                    },
+                )
+                .input_queue("in", depth, policy)
+                .build(),
+            SlowSink { values },
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for SlowSink {}
+
+// finishes right away, without handling messages
+struct Quitter;
+
+#[async_trait]
+impl AsyncKernel for Quitter {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        io.finished = true;
+        Ok(())
+    }
+}
+
+fn run(n: u32, depth: usize, policy: OverflowPolicy) -> Result<(Vec<u32>, u64, u64)> {
+    let values = Arc::new(Mutex::new(Vec::new()));
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Counter::new(n));
+    let snk = fg.add_block(SlowSink::new(values.clone(), depth, policy));
+    fg.connect_message(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let m = fg.message_input_metrics(snk).unwrap();
+    assert_eq!(m.len(), 1);
+    assert_eq!(m[0].name, "in");
+    assert_eq!(m[0].depth, depth);
+    assert_eq!(m[0].policy, policy);
+    assert_eq!(m[0].queued, 0);
+
+    let v = values.lock().unwrap().clone();
+    Ok((v, m[0].received, m[0].dropped))
+}
+
+#[test]
+fn queue_block() -> Result<()> {
+    let (v, received, dropped) = run(1000, 2, OverflowPolicy::Block)?;
+    assert_eq!(v, (0..1000).collect::<Vec<u32>>());
+    assert_eq!(received, 1000);
+    assert_eq!(dropped, 0);
+    Ok(())
+}
+
+#[test]
+fn queue_drop_newest() -> Result<()> {
+    let (v, received, dropped) = run(100, 4, OverflowPolicy::DropNewest)?;
+    // the first message might be handled before the burst arrives
+    assert!(v.len() == 4 || v.len() == 5);
+    assert_eq!(v, (0..v.len() as u32).collect::<Vec<u32>>());
+    assert_eq!(received, 100);
+    assert_eq!(dropped, 100 - v.len() as u64);
+    Ok(())
+}
+
+#[test]
+fn queue_drop_oldest() -> Result<()> {
+    let (v, received, dropped) = run(100, 4, OverflowPolicy::DropOldest)?;
+    assert!(v == vec![96, 97, 98, 99] || v == vec![0, 96, 97, 98, 99]);
+    assert_eq!(received, 100);
+    assert_eq!(dropped, 100 - v.len() as u64);
+    Ok(())
+}
+
+#[test]
+fn queue_disconnected() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MessageBurstBuilder::new(Pmt::Null, 100_000).build());
+    let snk = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Quitter").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "in",
+                |_: &mut Quitter, _: &mut MessageIo<Quitter>, _: &mut BlockMeta, _: Pmt| {
+                    Ok(Pmt::Null)
+                },
+            )
+            .input_queue("in", 1, OverflowPolicy::Block)
+            .build(),
+        Quitter,
+    ));
+    fg.connect_message(src, "out", snk, "in")?;
+
+    // the burst must not hang or panic when the receiver is gone
+    fg = Runtime::new().run(fg)?;
+    let m = fg.message_input_metrics(snk).unwrap();
+    assert!(m[0].received <= 100_000);
+    Ok(())
+}
+
+// records batch sizes and (input, value) pairs
+struct Recorder {
+    log: Arc<Mutex<Vec<(&'static str, u32)>>>,
+    batches: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Recorder {
+    fn record(&mut self, input: &'static str, p: Pmt) {
+        let mut log = self.log.lock().unwrap();
+        if log.is_empty() {
+            std::thread::sleep(Duration::from_millis(200));
+        }
+        if let Pmt::U32(i) = p {
+            log.push((input, i));
+        }
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Recorder {}
+
+#[test]
+fn batch_input() -> Result<()> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let batches = Arc::new(Mutex::new(Vec::new()));
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Counter::new(1000));
+    let snk = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Recorder").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new()
+            .add_batch_input(
+                "in",
+                |block: &mut Recorder,
+                 _mio: &mut MessageIo<Recorder>,
+                 _meta: &mut BlockMeta,
+                 p: Vec<Pmt>| {
+                    block.batches.lock().unwrap().push(p.len());
+                    for p in p {
+                        block.record("in", p);
+                    }
+                    Ok(Pmt::Null)
+                },
+            )
+            .max_messages_per_work(10)
+            .build(),
+        Recorder {
+            log: log.clone(),
+            batches: batches.clone(),
+        },
+    ));
+    fg.connect_message(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+
+    let log = log.lock().unwrap();
+    let values: Vec<u32> = log.iter().map(|(_, v)| *v).collect();
+    assert_eq!(values, (0..1000).collect::<Vec<u32>>());
+    let batches = batches.lock().unwrap();
+    assert!(batches.iter().all(|b| *b > 0 && *b <= 10));
+    // the burst queues up while the first batch stalls
+    assert!(batches.len() < 1000);
+    Ok(())
+}
+
+#[test]
+fn input_priority() -> Result<()> {
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let mut fg = Flowgraph::new();
+    let data = fg.add_block(Counter::new(50));
+    let ctrl = fg.add_block(Counter::new(5));
+    let snk = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Recorder").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "data",
+                |block: &mut Recorder,
+                 _mio: &mut MessageIo<Recorder>,
+                 _meta: &mut BlockMeta,
+                 p: Pmt| {
+                    block.record("data", p);
+                    Ok(Pmt::Null)
+                },
+            )
+            .add_sync_input(
+                "ctrl",
+                |block: &mut Recorder,
+                 _mio: &mut MessageIo<Recorder>,
+                 _meta: &mut BlockMeta,
+                 p: Pmt| {
+                    block.record("ctrl", p);
+                    Ok(Pmt::Null)
+                },
+            )
+            .input_priority("ctrl", MessagePriority::High)
+            .max_messages_per_work(1)
+            .build(),
+        Recorder {
+            log: log.clone(),
+            batches: Arc::new(Mutex::new(Vec::new())),
+        },
+    ));
+    fg.connect_message(data, "out", snk, "data")?;
+    fg.connect_message(ctrl, "out", snk, "ctrl")?;
+    Runtime::new().run(fg)?;
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 55);
+    // after the first (stalling) message, all control messages overtake the queued data
+    let n_ctrl = log[1..].iter().take_while(|(i, _)| *i == "ctrl").count();
+    assert_eq!(
+        n_ctrl,
+        log[1..].iter().filter(|(i, _)| *i == "ctrl").count()
+    );
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use num_complex::Complex;
+use std::collections::VecDeque;
+use std::f32::consts::FRAC_1_SQRT_2;
+
+use futuresdr::blocks::ofdm::CarrierAllocatorBuilder;
+use futuresdr::blocks::ofdm::CyclicPrefixRemoverBuilder;
+use futuresdr::blocks::ofdm::CyclicPrefixerBuilder;
+use futuresdr::blocks::ofdm::EqualizerBuilder;
+use futuresdr::blocks::ofdm::FrameFormat;
+use futuresdr::blocks::ofdm::SchmidlCox;
+use futuresdr::blocks::ofdm::SchmidlCoxBuilder;
+use futuresdr::blocks::ofdm::SerializerBuilder;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn qpsk(n: usize) -> Vec<Complex<f32>> {
+    (0..n)
+        .map(|_| {
+            let re = if rand::random::<bool>() { 1.0 } else { -1.0 };
+            let im = if rand::random::<bool>() { 1.0 } else { -1.0 };
+            Complex::new(re, im) * FRAC_1_SQRT_2
+        })
+        .collect()
+}
+
+#[test]
+fn ofdm_frame_format() {
+    let format = FrameFormat::default();
+    assert_eq!(format.data_carriers().len(), 48);
+    assert_eq!(format.frame_len(), 12 * 80);
+    assert_eq!(format.bin(-1), 63);
+    assert_eq!(format.bin(26), 26);
+
+    // sync symbol repeats after half a symbol
+    let sync = format.sync_symbol();
+    assert!(sync.iter().step_by(2).any(|x| x.norm() > 0.0));
+    assert!(sync.iter().skip(1).step_by(2).all(|x| x.norm() == 0.0));
+
+    let c = Complex::new(1.0, 0.0);
+    assert!(FrameFormat::new(64, 16, vec![1, 1], vec![], vec![], 1).is_err());
+    assert!(FrameFormat::new(64, 16, vec![1, 40], vec![], vec![], 1).is_err());
+    assert!(FrameFormat::new(64, 16, vec![1, 2], vec![3], vec![], 1).is_err());
+    assert!(FrameFormat::new(64, 80, vec![1, 2], vec![3], vec![c], 1).is_err());
+    assert!(FrameFormat::new(64, 16, vec![1, 2], vec![3], vec![c], 1).is_ok());
+}
+
+#[test]
+fn ofdm_allocator_serializer() -> Result<()> {
+    let format = FrameFormat::default();
+    // last frame is padded with zeros
+    let symbols = qpsk(2 * format.n_symbols() * format.data_carriers().len() - 5);
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(symbols.clone()));
+    let alloc = fg.add_block(CarrierAllocatorBuilder::new().build());
+    let eq = fg.add_block(EqualizerBuilder::new().build());
+    let ser = fg.add_block(SerializerBuilder::new().build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex<f32>>::new().build());
+
+    fg.connect_stream(src, "out", alloc, "in")?;
+    fg.connect_stream(alloc, "out", eq, "in")?;
+    fg.connect_stream(eq, "out", ser, "in")?;
+    fg.connect_stream(ser, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<Complex<f32>>>(snk).unwrap();
+    let items = snk.items();
+    assert_eq!(items.len(), symbols.len() + 5);
+    for (a, b) in items.iter().zip(symbols.iter()) {
+        assert!((a - b).norm() < 1e-4);
+    }
+    assert!(items[symbols.len()..].iter().all(|x| x.norm() < 1e-4));
+
+    Ok(())
+}
+
+#[test]
+fn ofdm_loopback() -> Result<()> {
+    let format = FrameFormat::default();
+    let n_frames = 3;
+    let symbols = qpsk(n_frames * format.n_symbols() * format.data_carriers().len());
+
+    // delay, two-path channel within the cyclic prefix, frequency offset
+    let mut delay: VecDeque<Complex<f32>> = vec![Complex::new(0.0, 0.0); 37].into();
+    let taps = [
+        Complex::new(0.8, 0.3),
+        Complex::new(0.0, 0.0),
+        Complex::new(-0.2, 0.25),
+    ];
+    let mut n = 0;
+    let channel = move |i: &Complex<f32>| -> Complex<f32> {
+        delay.push_back(*i);
+        let y: Complex<f32> = taps
+            .iter()
+            .zip(delay.iter().rev())
+            .map(|(h, x)| h * x)
+            .sum();
+        delay.pop_front();
+        n += 1;
+        y * Complex::from_polar(1.0, 0.02 * n as f32)
+    };
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(symbols.clone()));
+    let alloc = fg.add_block(CarrierAllocatorBuilder::new().build());
+    let prefixer = fg.add_block(CyclicPrefixerBuilder::new().padding(200).build());
+    let channel = fg.add_block(Apply::new(channel));
+    let sync = fg.add_block(SchmidlCoxBuilder::new().build());
+    let remover = fg.add_block(CyclicPrefixRemoverBuilder::new().build());
+    let eq = fg.add_block(EqualizerBuilder::new().build());
+    let ser = fg.add_block(SerializerBuilder::new().build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex<f32>>::new().build());
+
+    fg.connect_stream(src, "out", alloc, "in")?;
+    fg.connect_stream(alloc, "out", prefixer, "in")?;
+    fg.connect_stream(prefixer, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", remover, "in")?;
+    fg.connect_stream(remover, "out", eq, "in")?;
+    fg.connect_stream(eq, "out", ser, "in")?;
+    fg.connect_stream(ser, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let sync = fg.block_sync::<SchmidlCox>(sync).unwrap();
+    assert_eq!(sync.frames(), n_frames as u64);
+
+    let snk = fg.block_async::<VectorSink<Complex<f32>>>(snk).unwrap();
+    let items = snk.items();
+    assert_eq!(items.len(), symbols.len());
+    for (a, b) in items.iter().zip(symbols.iter()) {
+        assert!((a - b).norm() < 0.2, "{} != {}", a, b);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use anyhow::Result;
+use async_io::block_on;
+use async_io::Timer;
+use num_complex::Complex;
+use std::io::{Read, Write};
+use std::time::Duration;
+
+use futuresdr::blocks::rtl_tcp::{
+    Command, DongleInfo, RtlTcpSink, RtlTcpSinkBuilder, RtlTcpSource, RtlTcpSourceBuilder,
+    TUNER_E4000,
+};
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::ThrottleBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn rtl_tcp_protocol() -> Result<()> {
+    let info = DongleInfo {
+        tuner_type: TUNER_E4000,
+        gain_count: 14,
+    };
+    let b = info.to_bytes();
+    assert_eq!(&b[..], b"RTL0\0\0\0\x01\0\0\0\x0e");
+    assert_eq!(DongleInfo::parse(&b)?, info);
+    assert!(DongleInfo::parse(b"RTL1\0\0\0\x01\0\0\0\x0e").is_err());
+
+    let cmds = [
+        Command::Frequency(433_920_000),
+        Command::SampleRate(2_048_000),
+        Command::GainMode(true),
+        Command::Gain(-10),
+        Command::FreqCorrection(-42),
+        Command::Agc(false),
+        Command::BiasTee(true),
+    ];
+    for c in cmds {
+        assert_eq!(Command::parse(&c.to_bytes()), Some(c));
+    }
+    assert_eq!(
+        Command::Frequency(100_000_000).to_bytes(),
+        [0x01, 0x05, 0xf5, 0xe1, 0x00]
+    );
+    assert_eq!(Command::parse(&[0xff, 0, 0, 0, 0]), None);
+    Ok(())
+}
+
+#[test]
+fn rtl_tcp_loopback() -> Result<()> {
+    let addr = {
+        let l = std::net::TcpListener::bind("127.0.0.1:0")?;
+        l.local_addr()?.to_string()
+    };
+
+    // values that survive the u8 quantization
+    let items: Vec<Complex<f32>> = (0..100_000u32)
+        .map(|i| {
+            let re = (i % 256) as f32;
+            let im = (i / 256 % 256) as f32;
+            Complex::new((re - 127.5) / 127.5, (im - 127.5) / 127.5)
+        })
+        .collect();
+
+    let mut server = Flowgraph::new();
+    let src = server.add_block(VectorSource::new(items.clone()));
+    let throttle = server.add_block(ThrottleBuilder::new(8, 200_000.0).build());
+    let rtl_snk = server.add_block(RtlTcpSinkBuilder::new(&addr).build());
+    let freq = server.add_block(MessageSink::new());
+    server.connect_stream(src, "out", throttle, "in")?;
+    server.connect_stream(throttle, "out", rtl_snk, "in")?;
+    server.connect_message(rtl_snk, "freq", freq, "in")?;
+
+    let mut client = Flowgraph::new();
+    let rtl_src = client.add_block(
+        RtlTcpSourceBuilder::new(&addr)
+            .freq(100_000_000)
+            .sample_rate(2_048_000)
+            .gain(Some(20.0))
+            .build(),
+    );
+    let snk = client.add_block(VectorSinkBuilder::<Complex<f32>>::new().build());
+    client.connect_stream(rtl_src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (server_task, _) = rt.start(server);
+    let (server, client) = block_on(async {
+        Timer::after(Duration::from_millis(100)).await;
+        let (client_task, mut handle) = rt.start(client);
+        let f = Pmt::U32(433_920_000);
+        assert_eq!(handle.callback(rtl_src, 0, f.clone()).await?, f);
+        Ok::<_, anyhow::Error>((server_task.await?, client_task.await?))
+    })?;
+
+    let snk = client.block_async::<VectorSink<Complex<f32>>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    let rtl_src = client.block_async::<RtlTcpSource>(rtl_src).unwrap();
+    assert_eq!(
+        rtl_src.dongle_info(),
+        Some(DongleInfo {
+            tuner_type: 5,
+            gain_count: 29
+        })
+    );
+
+    let rtl_snk = server.block_async::<RtlTcpSink>(rtl_snk).unwrap();
+    assert_eq!(
+        rtl_snk.commands(),
+        &vec![
+            Command::SampleRate(2_048_000),
+            Command::Frequency(100_000_000),
+            Command::GainMode(true),
+            Command::Gain(200),
+            Command::Frequency(433_920_000),
+        ]
+    );
+    let freq = server.block_async::<MessageSink>(freq).unwrap();
+    assert_eq!(freq.received(), 2);
+    Ok(())
+}
+
+#[test]
+fn rtl_tcp_source_idle() -> Result<()> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?.to_string();
+
+    let server = std::thread::spawn(move || -> Result<[u8; 5]> {
+        let (mut s, _) = listener.accept()?;
+        let info = DongleInfo {
+            tuner_type: TUNER_E4000,
+            gain_count: 14,
+        };
+        s.write_all(&info.to_bytes())?;
+        // no samples until the command arrived
+        let mut cmd = [0u8; 5];
+        s.read_exact(&mut cmd)?;
+        // a sample split across writes
+        s.write_all(&[0, 255, 255])?;
+        s.flush()?;
+        std::thread::sleep(Duration::from_millis(50));
+        s.write_all(&[0])?;
+        Ok(cmd)
+    });
+
+    let mut fg = Flowgraph::new();
+    let rtl_src = fg.add_block(RtlTcpSourceBuilder::new(&addr).build());
+    let snk = fg.add_block(VectorSinkBuilder::<Complex<f32>>::new().build());
+    fg.connect_stream(rtl_src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        // invalid sample rate
+        assert!(handle.callback(rtl_src, 1, Pmt::Bool(true)).await.is_err());
+        let f = Pmt::U32(433_920_000);
+        assert_eq!(handle.callback(rtl_src, 0, f.clone()).await?, f);
+        task.await
+    })?;
+
+    let cmd: [u8; 5] = server.join().unwrap()?;
+    assert_eq!(Command::parse(&cmd), Some(Command::Frequency(433_920_000)));
+    let snk = fg.block_async::<VectorSink<Complex<f32>>>(snk).unwrap();
+    assert_eq!(
+        snk.items(),
+        &vec![Complex::new(-1.0, 1.0), Complex::new(1.0, -1.0)]
+    );
+    Ok(())
+}
@@ -1,14 +1,36 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use async_io::block_on;
+use async_trait::async_trait;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
 use std::iter::repeat_with;
+use std::time::Duration;
 
 use futuresdr::blocks::CopyBuilder;
 use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::MessageCopyBuilder;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSinkBuilder;
+use futuresdr::blocks::MessageSourceBuilder;
 use futuresdr::blocks::NullSourceBuilder;
 use futuresdr::blocks::VectorSink;
 use futuresdr::blocks::VectorSinkBuilder;
 use futuresdr::blocks::VectorSourceBuilder;
+use futuresdr::runtime::AsyncKernel;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::EdgeDescription;
 use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::PmtKind;
 use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::StreamPortDescription;
+use futuresdr::runtime::WorkIo;
 
 #[test]
 fn flowgraph() -> Result<()> {
@@ -109,3 +131,346 @@ fn fg_rand_vec_multi_snk() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn fg_call_by_name() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(20))
+            .n_messages(10)
+            .build(),
+    );
+    let copy = fg.add_block(MessageCopyBuilder::new().build());
+    let snk0 = fg.add_block(MessageSinkBuilder::new().build());
+    let snk1 = fg.add_block(MessageSinkBuilder::new().build());
+
+    fg.connect_message(src, "out", copy, "in")?;
+    fg.connect_message(copy, "out", snk0, "in")?;
+    fg.connect_message(src, "out", snk1, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    block_on(async move {
+        // instance name, type name (unique), and ids as strings
+        assert!(matches!(
+            handle
+                .callback_by_name("MessageSink_1", "in", Pmt::Null)
+                .await?,
+            Pmt::U64(_)
+        ));
+        handle.call_by_name("MessageCopy", "in", Pmt::Null).await?;
+        assert!(matches!(
+            handle
+                .callback_by_name(&snk0.to_string(), "0", Pmt::Null)
+                .await?,
+            Pmt::U64(_)
+        ));
+
+        let e = handle
+            .callback_by_name("MessageSink", "in", Pmt::Null)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("ambiguous"));
+        let e = handle
+            .call_by_name("Foo_0", "in", Pmt::Null)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("not found"));
+        let e = handle
+            .call_by_name("MessageSink_0", "foo", Pmt::Null)
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("no message handler foo"));
+        assert!(handle.call(snk0, 1, Pmt::Null).await.is_err());
+        assert!(handle.call(42, 0, Pmt::Null).await.is_err());
+
+        let fg = task.await?;
+        // 10 from the source, 1 from the copy, and 1 direct call each
+        let snk0 = fg.block_async::<MessageSink>(snk0).unwrap();
+        assert_eq!(snk0.received(), 12);
+        let snk1 = fg.block_async::<MessageSink>(snk1).unwrap();
+        assert_eq!(snk1.received(), 11);
+        Ok::<(), anyhow::Error>(())
+    })
+}
+
+struct Fallible;
+
+#[async_trait]
+impl AsyncKernel for Fallible {}
+
+#[test]
+fn fg_callback_error() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(50))
+            .n_messages(2)
+            .build(),
+    );
+    let fallible = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Fallible").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "in",
+                |_: &mut Fallible, _: &mut MessageIo<Fallible>, _: &mut BlockMeta, p: Pmt| match p {
+                    Pmt::String(s) => bail!("cannot handle {}", s),
+                    Pmt::Null => Ok(Pmt::Null),
+                    p => Ok(Pmt::U32(u32::try_from(p)? + 1)),
+                },
+            )
+            .build(),
+        Fallible,
+    ));
+    fg.connect_message(src, "out", fallible, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    block_on(async move {
+        let e = handle
+            .callback(fallible, 0, Pmt::String("foo".to_string()))
+            .await
+            .unwrap_err();
+        assert!(e.to_string().contains("cannot handle foo"));
+        let e = handle
+            .callback(fallible, 0, Pmt::Double(1.0))
+            .await
+            .unwrap_err();
+        assert!(e
+            .chain()
+            .any(|c| c.is::<futuresdr_pmt::PmtConversionError>()));
+        // the block is still running
+        assert_eq!(
+            handle.callback(fallible, 0, Pmt::U32(1)).await?,
+            Pmt::U32(2)
+        );
+        task.await?;
+        Ok::<(), anyhow::Error>(())
+    })
+}
+
+#[test]
+fn fg_description() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSourceBuilder::<u16>::new(vec![1, 2, 3]).build());
+    let copy = fg.add_block(CopyBuilder::new(2).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u16>::new().build());
+    let msg_src = fg.add_block(
+        MessageSourceBuilder::new(Pmt::Null, Duration::from_millis(10))
+            .n_messages(1)
+            .build(),
+    );
+    let fallible = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Fallible").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "in",
+                |_: &mut Fallible, _: &mut MessageIo<Fallible>, _: &mut BlockMeta, p: Pmt| Ok(p),
+            )
+            .add_sync_input(
+                "ctrl",
+                |_: &mut Fallible, _: &mut MessageIo<Fallible>, _: &mut BlockMeta, p: Pmt| Ok(p),
+            )
+            .input_kinds("ctrl", &[PmtKind::U32, PmtKind::Double])
+            .build(),
+        Fallible,
+    ));
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", snk, "in")?;
+    fg.connect_message(msg_src, "out", fallible, "ctrl")?;
+
+    let d = fg.description().unwrap();
+    assert_eq!(d.blocks.len(), 5);
+    let c = d.block("Copy")?;
+    assert_eq!(c.id, copy);
+    assert_eq!(c.instance_name, "Copy_0");
+    assert_eq!(
+        c.stream_inputs,
+        vec![StreamPortDescription {
+            name: "in".to_string(),
+            item_size: 2
+        }]
+    );
+    assert_eq!(c.stream_outputs[0].item_size, 2);
+    let f = d.block(&fallible.to_string())?;
+    assert_eq!(f.type_name, "Fallible");
+    assert!(f.stream_inputs.is_empty());
+    assert_eq!(f.message_inputs[0].name, "in");
+    assert!(f.message_inputs[0].kinds.is_empty());
+    assert_eq!(
+        f.message_inputs[1].kinds,
+        vec![PmtKind::U32, PmtKind::Double]
+    );
+    assert_eq!(d.block("MessageSource_0")?.message_outputs, vec!["out"]);
+    assert!(d.block("Head").is_err());
+
+    let edge = |src_block, dst_block, dst_port| EdgeDescription {
+        src_block,
+        src_port: 0,
+        dst_block,
+        dst_port,
+    };
+    assert_eq!(d.stream_edges, vec![edge(src, copy, 0), edge(copy, snk, 0)]);
+    assert_eq!(d.message_edges, vec![edge(msg_src, fallible, 1)]);
+
+    // the description is serialized by the control port
+    let json = serde_json::to_value(&d)?;
+    assert_eq!(
+        json["blocks"][fallible]["message_inputs"][1]["kinds"],
+        serde_json::json!(["U32", "Double"])
+    );
+
+    let rt = Runtime::new();
+    let (task, handle) = rt.start(fg);
+    assert_eq!(*handle.description(), d);
+    block_on(task)?;
+    Ok(())
+}
+
+// posts all messages at once and finishes, which terminates the receivers
+struct Burst {
+    n: u32,
+}
+
+#[async_trait]
+impl AsyncKernel for Burst {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for i in 0..self.n {
+            mio.post(0, Pmt::U32(i)).await;
+        }
+        io.finished = true;
+        Ok(())
+    }
+}
+
+// outputs one queued item per call to work and drains its queue when terminated
+struct Drain {
+    items: VecDeque<u32>,
+    terminated: bool,
+    n_terminated_calls: usize,
+}
+
+#[async_trait]
+impl AsyncKernel for Drain {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if io.finished {
+            self.n_terminated_calls += 1;
+            self.terminated = true;
+            io.finished = false;
+        }
+
+        let o = sio.output(0).slice::<u32>();
+        if !o.is_empty() {
+            if let Some(i) = self.items.pop_front() {
+                o[0] = i;
+                sio.output(0).produce(1);
+            }
+        }
+
+        if self.terminated && self.items.is_empty() {
+            io.finished = true;
+        } else {
+            io.call_again = !self.items.is_empty();
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn fg_terminate_drains() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Burst").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new().add_output("out").build(),
+        Burst { n: 10 },
+    ));
+    let drain = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Drain").drain_on_terminate().build(),
+        StreamIoBuilder::new().add_output("out", 4).build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "in",
+                |b: &mut Drain, _: &mut MessageIo<Drain>, _: &mut BlockMeta, p: Pmt| {
+                    b.items.push_back(u32::try_from(p)?);
+                    Ok(Pmt::Null)
+                },
+            )
+            .build(),
+        Drain {
+            items: VecDeque::new(),
+            terminated: false,
+            n_terminated_calls: 0,
+        },
+    ));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_message(src, "out", drain, "in")?;
+    fg.connect_stream(drain, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    // all messages are handled and work is called with io.finished set once the
+    // source terminated, so that the queued items still come out
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(*snk.items(), (0..10).collect::<Vec<u32>>());
+    let drain = fg.block_async::<Drain>(drain).unwrap();
+    assert_eq!(drain.n_terminated_calls, 1);
+    Ok(())
+}
+
+#[test]
+fn fg_terminate_handles_queued() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Burst").build(),
+        StreamIoBuilder::new().build(),
+        MessageIoBuilder::new().add_output("out").build(),
+        Burst { n: 10 },
+    ));
+    let drain = fg.add_block(Block::new_async(
+        BlockMetaBuilder::new("Drain").build(),
+        StreamIoBuilder::new().add_output("out", 4).build(),
+        MessageIoBuilder::new()
+            .add_sync_input(
+                "in",
+                |b: &mut Drain, _: &mut MessageIo<Drain>, _: &mut BlockMeta, p: Pmt| {
+                    b.items.push_back(u32::try_from(p)?);
+                    Ok(Pmt::Null)
+                },
+            )
+            .max_messages_per_work(1)
+            .build(),
+        Drain {
+            items: VecDeque::new(),
+            terminated: false,
+            n_terminated_calls: 0,
+        },
+    ));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_message(src, "out", drain, "in")?;
+    fg.connect_stream(drain, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    // without drain_on_terminate, the block handles all queued messages, but
+    // work is not called anymore
+    let drain = fg.block_async::<Drain>(drain).unwrap();
+    assert_eq!(drain.n_terminated_calls, 0);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    let mut items = snk.items().clone();
+    items.extend(drain.items.iter());
+    assert_eq!(items, (0..10).collect::<Vec<u32>>());
+    Ok(())
+}
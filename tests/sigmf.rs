@@ -0,0 +1,218 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use futuresdr::blocks::sigmf::paths;
+use futuresdr::blocks::sigmf::Annotation;
+use futuresdr::blocks::sigmf::Capture;
+use futuresdr::blocks::sigmf::Datatype;
+use futuresdr::blocks::sigmf::Description;
+use futuresdr::blocks::sigmf::SampleFormat;
+use futuresdr::blocks::sigmf::SigMfFormat;
+use futuresdr::blocks::sigmf::SigMfSink;
+use futuresdr::blocks::sigmf::SigMfSinkBuilder;
+use futuresdr::blocks::sigmf::SigMfSourceBuilder;
+use futuresdr::blocks::MessageDebug;
+use futuresdr::blocks::MessageDebugBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn base(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("futuresdr-sigmf-{}-{}", name, std::process::id()))
+}
+
+fn remove(base: &PathBuf) -> Result<()> {
+    let (meta, data) = paths(base);
+    std::fs::remove_file(meta)?;
+    std::fs::remove_file(data)?;
+    Ok(())
+}
+
+#[test]
+fn sigmf_datatype() -> Result<()> {
+    let d: Datatype = "cf32_le".parse()?;
+    assert!(d.complex);
+    assert_eq!(d.format, SampleFormat::F32);
+    assert!(!d.big_endian);
+    assert_eq!(d.item_size(), 8);
+    assert_eq!(d.to_string(), "cf32_le");
+
+    let d: Datatype = "ru8".parse()?;
+    assert_eq!(d.item_size(), 1);
+    assert_eq!(d.to_string(), "ru8");
+    assert_eq!(d, u8::DATATYPE);
+
+    assert_eq!("ci16_be".parse::<Datatype>()?.to_string(), "ci16_be");
+    assert!("cf32".parse::<Datatype>().is_err());
+    assert!("ru8_le".parse::<Datatype>().is_err());
+    assert!("xf32_le".parse::<Datatype>().is_err());
+
+    #[cfg(target_endian = "little")]
+    assert_eq!(Complex32::DATATYPE.to_string(), "cf32_le");
+
+    let (meta, data) = paths("/tmp/foo.sigmf-data");
+    assert_eq!(meta, PathBuf::from("/tmp/foo.sigmf-meta"));
+    assert_eq!(data, PathBuf::from("/tmp/foo.sigmf-data"));
+    assert_eq!(paths("/tmp/foo").0, PathBuf::from("/tmp/foo.sigmf-meta"));
+    Ok(())
+}
+
+#[test]
+fn sigmf_description() -> Result<()> {
+    let json = r#"{
+        "global": {
+            "core:datatype": "ci16_le",
+            "core:version": "1.0.0",
+            "core:sample_rate": 1000000.0,
+            "vendor:gain": 20
+        },
+        "captures": [
+            { "core:sample_start": 0, "core:frequency": 915000000.0 }
+        ],
+        "annotations": [
+            { "core:sample_start": 10, "core:sample_count": 5, "core:label": "burst" }
+        ]
+    }"#;
+    let d: Description = serde_json::from_str(json)?;
+    assert_eq!(d.global.datatype.to_string(), "ci16_le");
+    assert_eq!(d.global.sample_rate, Some(1e6));
+    assert_eq!(d.global.extra["vendor:gain"], 20);
+    assert_eq!(d.captures[0].frequency, Some(915e6));
+    assert_eq!(d.annotations[0].label.as_deref(), Some("burst"));
+
+    let d2: Description = serde_json::from_str(&serde_json::to_string(&d)?)?;
+    assert_eq!(d, d2);
+    Ok(())
+}
+
+#[test]
+fn sigmf_loopback() -> Result<()> {
+    let path = base("loopback");
+    let samples: Vec<Complex32> = (0..10000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(samples.clone()));
+    let snk = fg.add_block(
+        SigMfSinkBuilder::<Complex32>::new(&path)
+            .sample_rate(250e3)
+            .frequency(2.45e9)
+            .author("test")
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+    Runtime::new().run(fg)?;
+
+    let d = Description::from_file(paths(&path).0)?;
+    assert_eq!(d.global.datatype, Complex32::DATATYPE);
+    assert_eq!(d.global.sample_rate, Some(250e3));
+    assert_eq!(d.global.author.as_deref(), Some("test"));
+    assert_eq!(d.captures.len(), 1);
+    assert_eq!(d.captures[0].frequency, Some(2.45e9));
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(SigMfSourceBuilder::<Complex32>::new(&path).build()?);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<Complex32>>(snk).unwrap();
+    assert_eq!(snk.items(), &samples);
+
+    assert!(SigMfSourceBuilder::<f32>::new(&path).build().is_err());
+    assert!(SigMfSourceBuilder::<Complex32>::new(base("missing"))
+        .build()
+        .is_err());
+
+    remove(&path)
+}
+
+#[test]
+fn sigmf_metadata() -> Result<()> {
+    let path = base("metadata");
+    let (meta, data) = paths(&path);
+
+    let samples: Vec<i16> = (0..1000).collect();
+    std::fs::write(
+        &data,
+        samples
+            .iter()
+            .flat_map(|s| s.to_le_bytes())
+            .collect::<Vec<u8>>(),
+    )?;
+
+    let mut d = Description::new(i16::DATATYPE);
+    for (start, freq) in [(0, 100e6), (600, 101e6)] {
+        let mut c = Capture::new(start);
+        c.frequency = Some(freq);
+        d.captures.push(c);
+    }
+    for (start, label) in [(700, "b"), (100, "a"), (1000, "end")] {
+        let mut a = Annotation::new(start);
+        a.label = Some(label.to_string());
+        d.annotations.push(a);
+    }
+    d.to_file(&meta)?;
+
+    let copy = base("metadata-copy");
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(SigMfSourceBuilder::<i16>::new(&data).build()?);
+    let snk = fg.add_block(VectorSinkBuilder::<i16>::new().build());
+    let copy_snk = fg.add_block(SigMfSinkBuilder::<i16>::new(&copy).build());
+    let capture_collector = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    let annotation_collector =
+        fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg.connect_stream(src, "out", copy_snk, "in")?;
+    fg.connect_message(src, "captures", capture_collector, "in")?;
+    fg.connect_message(src, "annotations", annotation_collector, "in")?;
+    fg.connect_message(src, "captures", copy_snk, "captures")?;
+    fg.connect_message(src, "annotations", copy_snk, "annotations")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<i16>>(snk).unwrap();
+    assert_eq!(snk.items(), &samples);
+
+    let captures: Vec<Capture> = fg
+        .block_async::<MessageDebug>(capture_collector)
+        .unwrap()
+        .messages()
+        .iter()
+        .map(|m| match m {
+            Pmt::String(s) => serde_json::from_str(s).unwrap(),
+            _ => panic!("unexpected message {:?}", m),
+        })
+        .collect();
+    assert_eq!(captures, d.captures);
+
+    let labels: Vec<String> = fg
+        .block_async::<MessageDebug>(annotation_collector)
+        .unwrap()
+        .messages()
+        .iter()
+        .map(|m| match m {
+            Pmt::String(s) => serde_json::from_str::<Annotation>(s)
+                .unwrap()
+                .label
+                .unwrap(),
+            _ => panic!("unexpected message {:?}", m),
+        })
+        .collect();
+    assert_eq!(labels, vec!["a", "b", "end"]);
+
+    let copy_snk = fg.block_async::<SigMfSink<i16>>(copy_snk).unwrap();
+    assert_eq!(copy_snk.n_written(), 1000);
+    d.sort();
+    let c = Description::from_file(paths(&copy).0)?;
+    assert_eq!(c.captures, d.captures);
+    assert_eq!(c.annotations, d.annotations);
+
+    remove(&copy)?;
+    remove(&path)
+}
@@ -0,0 +1,191 @@
+use anyhow::Result;
+use async_io::block_on;
+use async_io::Timer;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::TcpClientPolicy;
+use futuresdr::blocks::TcpSink;
+use futuresdr::blocks::TcpSinkBuilder;
+use futuresdr::blocks::TcpSource;
+use futuresdr::blocks::TcpSourceBuilder;
+use futuresdr::blocks::ThrottleBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn local_addr() -> Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.to_string())
+}
+
+fn to_u32(b: &[u8]) -> Vec<u32> {
+    b.chunks_exact(4)
+        .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+#[test]
+fn tcp_client_server() -> Result<()> {
+    let addr = local_addr()?;
+    let items: Vec<u32> = (0..100_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let tcp_snk = fg.add_block(TcpSinkBuilder::new(&addr).item_size(4).build());
+    let tcp_src = fg.add_block(
+        TcpSourceBuilder::new(&addr)
+            .client(true)
+            .reconnect(false)
+            .item_size(4)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", tcp_snk, "in")?;
+    fg.connect_stream(tcp_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn tcp_fan_out() -> Result<()> {
+    let addr = local_addr()?;
+    let items: Vec<u32> = (0..10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let throttle = fg.add_block(ThrottleBuilder::new(4, 50_000.0).build());
+    let tcp_snk = fg.add_block(TcpSinkBuilder::new(&addr).item_size(4).build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", tcp_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let (fg, received) = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let clients = (0..2)
+            .map(|_| {
+                let mut s = TcpStream::connect(&addr)?;
+                Ok(std::thread::spawn(move || {
+                    let mut b = Vec::new();
+                    s.read_to_end(&mut b).unwrap();
+                    b
+                }))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let fg = task.await?;
+        let received: Vec<Vec<u32>> = clients
+            .into_iter()
+            .map(|c| to_u32(&c.join().unwrap()))
+            .collect();
+        Ok::<_, anyhow::Error>((fg, received))
+    })?;
+
+    let tcp_snk = fg.block_async::<TcpSink>(tcp_snk).unwrap();
+    assert_eq!(tcp_snk.n_connections(), 2);
+    assert_eq!(received[0], items);
+    // the second client might miss the first items
+    assert!(!received[1].is_empty());
+    assert!(items.ends_with(&received[1]));
+    Ok(())
+}
+
+#[test]
+fn tcp_drop_slow_client() -> Result<()> {
+    let addr = local_addr()?;
+    let n_items = 4_000_000;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new((0..n_items).collect::<Vec<u32>>()));
+    let tcp_snk = fg.add_block(
+        TcpSinkBuilder::new(&addr)
+            .item_size(4)
+            .policy(TcpClientPolicy::Drop)
+            .queue_size(1024)
+            .build(),
+    );
+    fg.connect_stream(src, "out", tcp_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let (fg, fast, slow) = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let mut slow = TcpStream::connect(&addr)?;
+        let mut fast = TcpStream::connect(&addr)?;
+        let fast = std::thread::spawn(move || {
+            let mut b = Vec::new();
+            fast.read_to_end(&mut b).unwrap();
+            b
+        });
+        // the client that does not read does not stall the flowgraph
+        let fg = task.await?;
+        let mut b = Vec::new();
+        slow.read_to_end(&mut b)?;
+        Ok::<_, anyhow::Error>((fg, to_u32(&fast.join().unwrap()), to_u32(&b)))
+    })?;
+
+    let tcp_snk = fg.block_async::<TcpSink>(tcp_snk).unwrap();
+    assert_eq!(tcp_snk.n_connections(), 2);
+    assert!(tcp_snk.n_dropped() > 0);
+    assert!(slow.len() < n_items as usize);
+    // only complete items are dropped
+    for r in [fast, slow] {
+        assert!(!r.is_empty());
+        assert!(r.windows(2).all(|w| w[0] < w[1]));
+        assert!(*r.last().unwrap() < n_items);
+    }
+    Ok(())
+}
+
+#[test]
+fn tcp_reconnect() -> Result<()> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?.to_string();
+
+    let mut fg = Flowgraph::new();
+    let tcp_src = fg.add_block(
+        TcpSourceBuilder::new(&addr)
+            .client(true)
+            .reconnect(true)
+            .item_size(4)
+            .build(),
+    );
+    let head = fg.add_block(HeadBuilder::new(4, 4).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(tcp_src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let server = std::thread::spawn(move || -> Result<()> {
+        // one item and half an item, which is dropped
+        let (mut s, _) = listener.accept()?;
+        s.write_all(&1u32.to_ne_bytes())?;
+        s.write_all(&[0, 0])?;
+        drop(s);
+
+        // items split across writes
+        let (mut s, _) = listener.accept()?;
+        drop(listener);
+        let b: Vec<u8> = [2u32, 3, 4].iter().flat_map(|v| v.to_ne_bytes()).collect();
+        s.write_all(&b[..5])?;
+        s.flush()?;
+        std::thread::sleep(Duration::from_millis(50));
+        s.write_all(&b[5..])?;
+        Ok(())
+    });
+
+    fg = Runtime::new().run(fg)?;
+    server.join().unwrap()?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![1, 2, 3, 4]);
+    let tcp_src = fg.block_async::<TcpSource>(tcp_src).unwrap();
+    assert_eq!(tcp_src.n_connections(), 2);
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+
+use futuresdr::blocks::ThrottleBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn throttle(items: Vec<u32>, rate: f64) -> Result<Vec<u32>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items));
+    let throttle = fg.add_block(ThrottleBuilder::new(4, rate).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    Ok(snk.items().clone())
+}
+
+// The input is finished long before the throttle forwarded all items. The
+// throttle finishes once the remaining input (in bytes) is forwarded, not when
+// it forwarded a fraction of it, e.g., one of four items of four bytes.
+#[test]
+fn throttle_finishes_after_last_item() -> Result<()> {
+    assert_eq!(throttle(vec![1, 2, 3, 4], 10.0)?, vec![1, 2, 3, 4]);
+
+    let items: Vec<u32> = (0..20_000).collect();
+    assert_eq!(throttle(items.clone(), 50_000.0)?, items);
+    Ok(())
+}
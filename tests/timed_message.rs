@@ -0,0 +1,440 @@
+use anyhow::Result;
+use async_io::Timer;
+use async_trait::async_trait;
+use std::cmp;
+use std::time::{Duration, Instant, SystemTime};
+
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::AsyncKernel;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::MessageQueue;
+use futuresdr::runtime::MessageTime;
+use futuresdr::runtime::OverflowPolicy;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::WorkIo;
+
+// copies u32 once opened, in multiples of chunk, and records the item count
+// when marked
+struct Tagger {
+    open: bool,
+    n: u64,
+    marks: Vec<(u64, Instant)>,
+    finish_after: Option<usize>,
+    chunk: usize,
+    pause: Option<Duration>,
+}
+
+impl Tagger {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(open: bool, finish_after: Option<usize>) -> Block {
+        Self::with_chunk(open, finish_after, 1)
+    }
+
+    fn with_chunk(open: bool, finish_after: Option<usize>, chunk: usize) -> Block {
+        Self::build(open, finish_after, chunk, None)
+    }
+
+    fn with_pause(open: bool, pause: Duration) -> Block {
+        Self::build(open, None, 1, Some(pause))
+    }
+
+    fn build(
+        open: bool,
+        finish_after: Option<usize>,
+        chunk: usize,
+        pause: Option<Duration>,
+    ) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Tagger").drain_on_terminate().build(),
+            StreamIoBuilder::new()
+                .add_input("in", 4)
+                .add_output("out", 4)
+                .build(),
+            MessageIoBuilder::new()
+                .add_sync_input(
+                    "open",
+                    |block: &mut Tagger,
+                     _mio: &mut MessageIo<Tagger>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        block.open = true;
+                        Ok(Pmt::Null)
+                    },
+                )
+                .add_sync_input(
+                    "mark",
+                    |block: &mut Tagger,
+                     _mio: &mut MessageIo<Tagger>,
+                     _meta: &mut BlockMeta,
+                     _p: Pmt| {
+                        block.marks.push((block.n, Instant::now()));
+                        Ok(Pmt::Null)
+                    },
+                )
+                .build(),
+            Tagger {
+                open,
+                n: 0,
+                marks: Vec::new(),
+                finish_after,
+                chunk,
+                pause,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Tagger {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(n) = self.finish_after {
+            if self.marks.len() >= n {
+                io.finished = true;
+                return Ok(());
+            }
+        }
+
+        let i = sio.input(0).slice::<u32>();
+        let o = sio.output(0).slice::<u32>();
+
+        // waits while the output is full, so that downstream frees space during the call
+        if let Some(d) = self.pause {
+            if o.is_empty() {
+                Timer::after(d).await;
+            }
+        }
+
+        let mut m = 0;
+        if self.open {
+            m = cmp::min(i.len(), o.len());
+            m -= m % self.chunk;
+            o[..m].copy_from_slice(&i[..m]);
+            sio.input(0).consume(m);
+            sio.output(0).produce(m);
+            self.n += m as u64;
+        }
+
+        // keeps running when terminated by the block posting the marks, until the
+        // end of the stream
+        io.finished = sio.input(0).finished() && m == i.len();
+        Ok(())
+    }
+}
+
+#[test]
+fn timed_item_offset() -> Result<()> {
+    let items: Vec<u32> = (0..100_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let gate = fg.add_block(Tagger::new(false, None));
+    let tagger = fg.add_block(Tagger::new(true, None));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", gate, "in")?;
+    fg.connect_stream(gate, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = async_io::block_on(async move {
+        handle
+            .call_at_by_name(
+                "Tagger_1",
+                "mark",
+                Pmt::Null,
+                MessageTime::InputItem {
+                    port: 0,
+                    item: 1234,
+                },
+            )
+            .await?;
+        handle
+            .call_at_by_name(
+                "Tagger_1",
+                "mark",
+                Pmt::Null,
+                MessageTime::OutputItem {
+                    port: 0,
+                    item: 54_321,
+                },
+            )
+            .await?;
+        // past the end of the stream, never delivered
+        handle
+            .call_at_by_name(
+                "Tagger_1",
+                "mark",
+                Pmt::Null,
+                MessageTime::InputItem {
+                    port: 0,
+                    item: 1_000_000,
+                },
+            )
+            .await?;
+        // the data only starts to flow after the timed messages are scheduled
+        handle.call_by_name("Tagger_0", "open", Pmt::Null).await?;
+        task.await
+    })?;
+
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    let marks: Vec<u64> = tagger.marks.iter().map(|m| m.0).collect();
+    assert_eq!(marks, vec![1234, 54_321]);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn timed_wall_clock() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(vec![0u32; 10]));
+    // never opened, so the tagger is idle until the timed message is due
+    let gate = fg.add_block(Tagger::new(false, None));
+    let tagger = fg.add_block(Tagger::new(true, Some(2)));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", gate, "in")?;
+    fg.connect_stream(gate, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let start = Instant::now();
+    let fg = async_io::block_on(async move {
+        let now = SystemTime::now();
+        handle
+            .call_at(
+                tagger,
+                1,
+                Pmt::Null,
+                MessageTime::At(now + Duration::from_millis(200)),
+            )
+            .await?;
+        handle
+            .call_at(
+                tagger,
+                1,
+                Pmt::Null,
+                MessageTime::At(now + Duration::from_millis(100)),
+            )
+            .await?;
+        task.await
+    })?;
+
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    assert_eq!(tagger.marks.len(), 2);
+    assert!(tagger.marks[0].1 - start >= Duration::from_millis(100));
+    assert!(tagger.marks[1].1 - start >= Duration::from_millis(200));
+    assert!(tagger.marks[1].1 - start < Duration::from_secs(2));
+    Ok(())
+}
+
+#[test]
+fn timed_upstream_finished() -> Result<()> {
+    let items: Vec<u32> = (0..1000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let tagger = fg.add_block(Tagger::new(false, None));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = async_io::block_on(async move {
+        // the source is done before the tagger is opened
+        Timer::after(Duration::from_millis(100)).await;
+        handle
+            .call_at(
+                tagger,
+                1,
+                Pmt::Null,
+                MessageTime::InputItem { port: 0, item: 500 },
+            )
+            .await?;
+        handle.call(tagger, 0, Pmt::Null).await?;
+        task.await
+    })?;
+
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    let marks: Vec<u64> = tagger.marks.iter().map(|m| m.0).collect();
+    assert_eq!(marks, vec![500]);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn timed_stalled_offset() -> Result<()> {
+    let items: Vec<u32> = (0..10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let gate = fg.add_block(Tagger::new(false, None));
+    // cannot stop at the offset, since it only copies multiples of 1000 items
+    let tagger = fg.add_block(Tagger::with_chunk(true, None, 1000));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", gate, "in")?;
+    fg.connect_stream(gate, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = async_io::block_on(async move {
+        handle
+            .call_at(
+                tagger,
+                1,
+                Pmt::Null,
+                MessageTime::InputItem {
+                    port: 0,
+                    item: 1234,
+                },
+            )
+            .await?;
+        handle.call(gate, 0, Pmt::Null).await?;
+        task.await
+    })?;
+
+    // delivered late, after the next chunk
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    assert_eq!(tagger.marks.len(), 1);
+    assert!(tagger.marks[0].0 > 1234);
+    assert_eq!(tagger.marks[0].0 % 1000, 0);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+// posts a timed mark for each offset and finishes
+struct Marker {
+    offsets: Vec<u64>,
+}
+
+impl Marker {
+    #[allow(clippy::new_ret_no_self)]
+    fn new(offsets: Vec<u64>) -> Block {
+        Block::new_async(
+            BlockMetaBuilder::new("Marker").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Marker { offsets },
+        )
+    }
+}
+
+#[async_trait]
+impl AsyncKernel for Marker {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        for item in self.offsets.drain(..) {
+            mio.post_at(0, Pmt::Null, MessageTime::InputItem { port: 0, item })
+                .await;
+        }
+        io.finished = true;
+        Ok(())
+    }
+}
+
+#[test]
+fn timed_post_at() -> Result<()> {
+    let items: Vec<u32> = (0..10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let gate = fg.add_block(Tagger::new(false, None));
+    let tagger = fg.add_block(Tagger::new(true, None));
+    let marker = fg.add_block(Marker::new(vec![4321, 100]));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", gate, "in")?;
+    fg.connect_stream(gate, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", snk, "in")?;
+    fg.connect_message(marker, "out", tagger, "mark")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = async_io::block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        handle.call(gate, 0, Pmt::Null).await?;
+        task.await
+    })?;
+
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    let marks: Vec<u64> = tagger.marks.iter().map(|m| m.0).collect();
+    assert_eq!(marks, vec![100, 4321]);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn timed_backpressure() -> Result<()> {
+    let items: Vec<u32> = (0..100_000).collect();
+    let offsets: Vec<u64> = (1..100).map(|i| i * 997).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let gate = fg.add_block(Tagger::new(false, None));
+    let tagger = fg.add_block(Tagger::with_pause(true, Duration::from_millis(200)));
+    let downstream = fg.add_block(Tagger::new(false, None));
+    let marker = fg.add_block(Marker::new(offsets.clone()));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", gate, "in")?;
+    fg.connect_stream(gate, "out", tagger, "in")?;
+    fg.connect_stream(tagger, "out", downstream, "in")?;
+    fg.connect_stream(downstream, "out", snk, "in")?;
+    fg.connect_message(marker, "out", tagger, "mark")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    let fg = async_io::block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        handle.call(gate, 0, Pmt::Null).await?;
+        // the output of the tagger is full before the next offset; free it, while
+        // the tagger is in a call to work that does not make progress
+        Timer::after(Duration::from_millis(100)).await;
+        handle.call(downstream, 0, Pmt::Null).await?;
+        task.await
+    })?;
+
+    let tagger = fg.block_async::<Tagger>(tagger).unwrap();
+    let marks: Vec<u64> = tagger.marks.iter().map(|m| m.0).collect();
+    assert_eq!(marks, offsets);
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn timed_queue_overflow() {
+    let q = MessageQueue::new(2, OverflowPolicy::DropNewest);
+    let time = MessageTime::At(SystemTime::now());
+    async_io::block_on(async {
+        assert!(q.push(Pmt::U32(0)).await);
+        assert!(q.push_at(Pmt::U32(1), time).await);
+        assert!(q.push_at(Pmt::U32(2), time).await);
+    });
+    assert_eq!(q.len(), 2);
+    assert_eq!(q.dropped(), 1);
+}
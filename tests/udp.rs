@@ -0,0 +1,138 @@
+use anyhow::Result;
+use async_io::block_on;
+use async_io::Timer;
+use std::time::Duration;
+
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::UdpSinkBuilder;
+use futuresdr::blocks::UdpSource;
+use futuresdr::blocks::UdpSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn local_addr() -> Result<String> {
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0")?;
+    Ok(socket.local_addr()?.to_string())
+}
+
+#[test]
+fn udp_stream() -> Result<()> {
+    let addr = local_addr()?;
+    let items: Vec<u32> = (0..10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let udp_snk = fg.add_block(
+        UdpSinkBuilder::new(&addr)
+            .item_size(4)
+            .payload_size(1002)
+            .sequence_numbers(true)
+            .eof(true)
+            .build(),
+    );
+    let udp_src = fg.add_block(
+        UdpSourceBuilder::new(&addr)
+            .item_size(4)
+            .sequence_numbers(true)
+            .eof(true)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", udp_snk, "in")?;
+    fg.connect_stream(udp_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    let udp_src = fg.block_async::<UdpSource>(udp_src).unwrap();
+    assert_eq!(udp_src.n_lost(), 0);
+    // 250 items per datagram and the end of stream
+    assert_eq!(udp_src.n_received(), 41);
+    Ok(())
+}
+
+#[test]
+fn udp_pdu() -> Result<()> {
+    let addr = local_addr()?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MessageBurstBuilder::new(Pmt::Blob(vec![1, 2, 3]), 20).build());
+    let udp_snk = fg.add_block(UdpSinkBuilder::new(&addr).pdu(true).eof(true).build());
+    let udp_src = fg.add_block(UdpSourceBuilder::new(&addr).pdu(true).eof(true).build());
+    let snk = fg.add_block(MessageSink::new());
+    fg.connect_message(src, "out", udp_snk, "in")?;
+    fg.connect_message(udp_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), 20);
+    Ok(())
+}
+
+#[test]
+fn udp_sequence_numbers() -> Result<()> {
+    let addr = local_addr()?;
+
+    let mut fg = Flowgraph::new();
+    let udp_src = fg.add_block(
+        UdpSourceBuilder::new(&addr)
+            .sequence_numbers(true)
+            .eof(true)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    fg.connect_stream(udp_src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let fg = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0")?;
+        for seq in [0u32, 1, 3, 4, 5, 9] {
+            let mut d = seq.to_be_bytes().to_vec();
+            d.push(seq as u8);
+            socket.send_to(&d, &addr)?;
+        }
+        socket.send_to(&10u32.to_be_bytes(), &addr)?;
+        task.await
+    })?;
+
+    let snk = fg.block_async::<VectorSink<u8>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![0, 1, 3, 4, 5, 9]);
+    let udp_src = fg.block_async::<UdpSource>(udp_src).unwrap();
+    assert_eq!(udp_src.n_lost(), 4);
+    Ok(())
+}
+
+#[test]
+fn udp_partial_items() -> Result<()> {
+    let addr = local_addr()?;
+
+    let mut fg = Flowgraph::new();
+    let udp_src = fg.add_block(UdpSourceBuilder::new(&addr).item_size(4).eof(true).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(udp_src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let fg = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let socket = std::net::UdpSocket::bind("127.0.0.1:0")?;
+        // the partial item at the end of the first datagram is not joined with the second
+        let mut d: Vec<u8> = [1u32, 2].iter().flat_map(|i| i.to_ne_bytes()).collect();
+        d.extend_from_slice(&[0xff, 0xff]);
+        socket.send_to(&d, &addr)?;
+        socket.send_to(&3u32.to_ne_bytes(), &addr)?;
+        socket.send_to(&[], &addr)?;
+        task.await
+    })?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![1, 2, 3]);
+    Ok(())
+}
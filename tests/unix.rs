@@ -0,0 +1,117 @@
+#![cfg(unix)]
+
+use anyhow::Result;
+use std::io::Write;
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use futuresdr::blocks::FifoSinkBuilder;
+use futuresdr::blocks::FifoSourceBuilder;
+use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::UnixSinkBuilder;
+use futuresdr::blocks::UnixSource;
+use futuresdr::blocks::UnixSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn temp_path(name: &str) -> PathBuf {
+    let p = std::env::temp_dir().join(format!("futuresdr-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_file(&p);
+    p
+}
+
+#[test]
+fn unix_client_server() -> Result<()> {
+    let path = temp_path("client-server.sock");
+    let path = path.to_str().unwrap();
+    let items: Vec<u32> = (0..100_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let unix_snk = fg.add_block(UnixSinkBuilder::new(path).item_size(4).build());
+    let unix_src = fg.add_block(
+        UnixSourceBuilder::new(path)
+            .client(true)
+            .reconnect(false)
+            .item_size(4)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", unix_snk, "in")?;
+    fg.connect_stream(unix_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    // the server removes its socket file
+    assert!(!std::path::Path::new(path).exists());
+    Ok(())
+}
+
+#[test]
+fn unix_reconnect() -> Result<()> {
+    let path = temp_path("reconnect.sock");
+    let listener = UnixListener::bind(&path)?;
+
+    let mut fg = Flowgraph::new();
+    let unix_src = fg.add_block(
+        UnixSourceBuilder::new(path.to_str().unwrap())
+            .client(true)
+            .reconnect(true)
+            .item_size(4)
+            .build(),
+    );
+    let head = fg.add_block(HeadBuilder::new(4, 3).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(unix_src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let server = std::thread::spawn(move || -> Result<()> {
+        let (mut s, _) = listener.accept()?;
+        s.write_all(&1u32.to_ne_bytes())?;
+        drop(s);
+
+        let (mut s, _) = listener.accept()?;
+        let b: Vec<u8> = [2u32, 3].iter().flat_map(|v| v.to_ne_bytes()).collect();
+        s.write_all(&b[..3])?;
+        s.flush()?;
+        std::thread::sleep(Duration::from_millis(50));
+        s.write_all(&b[3..])?;
+        Ok(())
+    });
+
+    fg = Runtime::new().run(fg)?;
+    server.join().unwrap()?;
+    let _ = std::fs::remove_file(&path);
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![1, 2, 3]);
+    let unix_src = fg.block_async::<UnixSource>(unix_src).unwrap();
+    assert_eq!(unix_src.n_connections(), 2);
+    Ok(())
+}
+
+#[test]
+fn fifo_loopback() -> Result<()> {
+    let path = temp_path("loopback.fifo");
+    let path = path.to_str().unwrap();
+    let items: Vec<u32> = (0..100_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let fifo_snk = fg.add_block(FifoSinkBuilder::new(path).item_size(4).build());
+    let fifo_src = fg.add_block(FifoSourceBuilder::new(path).item_size(4).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", fifo_snk, "in")?;
+    fg.connect_stream(fifo_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+    std::fs::remove_file(path)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
@@ -0,0 +1,185 @@
+use anyhow::Result;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use futuresdr::blocks::wav::read_header;
+use futuresdr::blocks::wav::write_header;
+use futuresdr::blocks::wav::SampleFormat;
+use futuresdr::blocks::wav::WavSinkBuilder;
+use futuresdr::blocks::wav::WavSource;
+use futuresdr::blocks::wav::WavSourceBuilder;
+use futuresdr::blocks::wav::WavSpec;
+use futuresdr::blocks::wav::HEADER_LEN;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("futuresdr-wav-{}-{}.wav", name, std::process::id()))
+}
+
+#[test]
+fn wav_header() -> Result<()> {
+    let spec = WavSpec {
+        channels: 2,
+        sample_rate: 48000,
+        bits_per_sample: 24,
+        sample_format: SampleFormat::Int,
+    };
+    let mut h = Vec::new();
+    write_header(&mut h, &spec, 600)?;
+    assert_eq!(h.len() as u64, HEADER_LEN);
+    h.extend_from_slice(&[0; 600]);
+
+    let header = read_header(&mut Cursor::new(&h))?;
+    assert_eq!(header.spec, spec);
+    assert_eq!(header.data_offset, HEADER_LEN);
+    assert_eq!(header.data_len, 600);
+
+    // WAVE_FORMAT_EXTENSIBLE with float samples, preceded by an odd sized chunk
+    let mut h = Vec::new();
+    h.extend_from_slice(b"RIFF\0\0\0\0WAVE");
+    h.extend_from_slice(b"LIST\x03\0\0\0abc\0");
+    h.extend_from_slice(b"fmt \x28\0\0\0");
+    h.extend_from_slice(&0xfffeu16.to_le_bytes());
+    h.extend_from_slice(&1u16.to_le_bytes());
+    h.extend_from_slice(&8000u32.to_le_bytes());
+    h.extend_from_slice(&32000u32.to_le_bytes());
+    h.extend_from_slice(&4u16.to_le_bytes());
+    h.extend_from_slice(&32u16.to_le_bytes());
+    h.extend_from_slice(&22u16.to_le_bytes());
+    h.extend_from_slice(&32u16.to_le_bytes());
+    h.extend_from_slice(&4u32.to_le_bytes());
+    h.extend_from_slice(&3u16.to_le_bytes());
+    h.extend_from_slice(&[0; 14]);
+    // streaming writers do not know the length
+    h.extend_from_slice(b"data\xff\xff\xff\xff");
+    h.extend_from_slice(&[0; 42]);
+
+    let header = read_header(&mut Cursor::new(&h))?;
+    assert_eq!(header.spec.sample_format, SampleFormat::Float);
+    assert_eq!(header.spec.bits_per_sample, 32);
+    assert_eq!(header.spec.channels, 1);
+    assert_eq!(header.spec.sample_rate, 8000);
+    assert_eq!(header.data_len, 40);
+
+    assert!(read_header(&mut Cursor::new(b"RIFF\0\0\0\0WAVX")).is_err());
+    assert!(read_header(&mut Cursor::new(&h[..40])).is_err());
+    Ok(())
+}
+
+#[test]
+fn wav_loopback() -> Result<()> {
+    let n_frames = 5000;
+    let left: Vec<f32> = (0..n_frames).map(|i| (i as f32 * 0.01).sin()).collect();
+    let right: Vec<f32> = (0..n_frames)
+        .map(|i| 0.5 * (i as f32 * 0.003).cos())
+        .collect();
+    let interleaved: Vec<f32> = left
+        .iter()
+        .zip(right.iter())
+        .flat_map(|(l, r)| [*l, *r])
+        .collect();
+
+    let formats = [
+        (SampleFormat::Int, 8, 1.0 / 64.0),
+        (SampleFormat::Int, 16, 1e-4),
+        (SampleFormat::Int, 24, 1e-6),
+        (SampleFormat::Int, 32, 1e-6),
+        (SampleFormat::Float, 32, 0.0),
+        (SampleFormat::Float, 64, 0.0),
+    ];
+
+    for (format, bits, tolerance) in formats {
+        let path = temp_file(&format!("loopback-{}", bits));
+        let file = path.to_str().unwrap();
+
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(VectorSource::new(interleaved.clone()));
+        let snk = fg.add_block(
+            WavSinkBuilder::new(file, 48000, 2)
+                .sample_format(format)
+                .bits_per_sample(bits)
+                .build()?,
+        );
+        fg.connect_stream(src, "out", snk, "in")?;
+        Runtime::new().run(fg)?;
+
+        assert_eq!(
+            std::fs::metadata(&path)?.len(),
+            HEADER_LEN + n_frames * 2 * bits as u64 / 8
+        );
+
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(WavSourceBuilder::new(file).interleaved(false).build()?);
+        let snk0 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+        let snk1 = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+        fg.connect_stream(src, "out0", snk0, "in")?;
+        fg.connect_stream(src, "out1", snk1, "in")?;
+        fg = Runtime::new().run(fg)?;
+
+        let src = fg.block_async::<WavSource>(src).unwrap();
+        assert_eq!(src.spec().sample_rate, 48000);
+        assert_eq!(src.spec().bits_per_sample, bits);
+        assert_eq!(src.n_frames(), n_frames);
+
+        for (snk, expected) in [(snk0, &left), (snk1, &right)] {
+            let snk = fg.block_async::<VectorSink<f32>>(snk).unwrap();
+            assert_eq!(snk.items().len(), expected.len());
+            for (a, b) in snk.items().iter().zip(expected.iter()) {
+                assert!(
+                    (a - b).abs() <= tolerance,
+                    "{:?} {} {} {}",
+                    format,
+                    bits,
+                    a,
+                    b
+                );
+            }
+        }
+
+        std::fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+#[test]
+fn wav_channels() -> Result<()> {
+    let path = temp_file("channels");
+    let file = path.to_str().unwrap();
+    let a: Vec<f32> = (0..1001).map(|i| i as f32 / 2048.0).collect();
+    let b: Vec<f32> = (0..1000).map(|i| -(i as f32) / 2048.0).collect();
+
+    let mut fg = Flowgraph::new();
+    let src0 = fg.add_block(VectorSource::new(a.clone()));
+    let src1 = fg.add_block(VectorSource::new(b.clone()));
+    let snk = fg.add_block(
+        WavSinkBuilder::new(file, 8000, 2)
+            .interleaved(false)
+            .build()?,
+    );
+    fg.connect_stream(src0, "out", snk, "in0")?;
+    fg.connect_stream(src1, "out", snk, "in1")?;
+    Runtime::new().run(fg)?;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(WavSourceBuilder::new(file).build()?);
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<f32>>(snk).unwrap();
+    let expected: Vec<f32> = a.iter().zip(b.iter()).flat_map(|(a, b)| [*a, *b]).collect();
+    assert_eq!(snk.items(), &expected);
+
+    assert!(WavSinkBuilder::new(file, 8000, 1)
+        .bits_per_sample(12)
+        .build()
+        .is_err());
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
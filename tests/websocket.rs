@@ -0,0 +1,171 @@
+use anyhow::Result;
+use async_io::block_on;
+use async_io::Timer;
+use async_tungstenite::tungstenite::Message;
+use futures::future::{select, Either};
+use futures::FutureExt;
+use futures::SinkExt;
+use futures::StreamExt;
+use std::time::Duration;
+
+use futuresdr::blocks::MessageDebug;
+use futuresdr::blocks::MessageDebugBuilder;
+use futuresdr::blocks::ThrottleBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::WebsocketSink;
+use futuresdr::blocks::WebsocketSinkBuilder;
+use futuresdr::blocks::WebsocketSourceBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+fn free_port() -> Result<u32> {
+    let l = std::net::TcpListener::bind("127.0.0.1:0")?;
+    Ok(l.local_addr()?.port() as u32)
+}
+
+#[test]
+fn websocket_stream() -> Result<()> {
+    let port = free_port()?;
+    let items: Vec<f32> = (0..100_000).map(|i| i as f32).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let ws_snk = fg.add_block(WebsocketSinkBuilder::<f32>::new(port).build());
+    let ws_src = fg.add_block(
+        WebsocketSourceBuilder::new(0)
+            .connect(&format!("ws://127.0.0.1:{}/", port))
+            .item_size(4)
+            .build(),
+    );
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", ws_snk, "in")?;
+    fg.connect_stream(ws_src, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn websocket_multiple_clients() -> Result<()> {
+    let port = free_port()?;
+    let items: Vec<u32> = (0..20_000).collect();
+    let url = format!("ws://127.0.0.1:{}/", port);
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let throttle = fg.add_block(ThrottleBuilder::new(4, 100_000.0).build());
+    let ws_snk = fg.add_block(WebsocketSinkBuilder::<u32>::new(port).build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", ws_snk, "in")?;
+
+    let mut snks = Vec::new();
+    for u in [url.clone(), url.clone(), format!("{}?policy=drop", url)] {
+        let ws_src = fg.add_block(
+            WebsocketSourceBuilder::new(0)
+                .connect(&u)
+                .item_size(4)
+                .build(),
+        );
+        let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+        fg.connect_stream(ws_src, "out", snk, "in")?;
+        snks.push(snk);
+    }
+    fg = Runtime::new().run(fg)?;
+
+    let ws_snk = fg.block_async::<WebsocketSink<u32>>(ws_snk).unwrap();
+    assert_eq!(ws_snk.n_clients(), 3);
+
+    for (i, snk) in snks.into_iter().enumerate() {
+        let received = fg.block_async::<VectorSink<u32>>(snk).unwrap().items();
+        assert!(!received.is_empty());
+        if i < 2 {
+            // clients connecting later miss the first items
+            assert!(items.ends_with(received));
+        } else {
+            // dropping client only gets complete frames, but maybe not all
+            assert!(received.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn websocket_idle_handshake() -> Result<()> {
+    let port = free_port()?;
+    let items: Vec<u8> = (0..=255).cycle().take(10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let throttle = fg.add_block(ThrottleBuilder::new(1, 20_000.0).build());
+    let ws_snk = fg.add_block(WebsocketSinkBuilder::<u8>::new(port).build());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", ws_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let received = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let addr = format!("127.0.0.1:{}", port);
+        // connects, but does not start the handshake
+        let _idle = async_net::TcpStream::connect(&addr).await?;
+
+        let stream = async_net::TcpStream::connect(&addr).await?;
+        let handshake = async_tungstenite::client_async(format!("ws://{}/", addr), stream);
+        let timeout = Timer::after(Duration::from_secs(5));
+        let (mut ws, _) = match select(handshake.boxed(), timeout).await {
+            Either::Left((ws, _)) => ws?,
+            Either::Right(_) => anyhow::bail!("handshake stalled by the idle client"),
+        };
+
+        let mut received = Vec::new();
+        while let Some(m) = ws.next().await {
+            match m? {
+                Message::Binary(b) => received.extend_from_slice(&b),
+                Message::Close(_) => break,
+                _ => {}
+            }
+        }
+        task.await?;
+        Ok(received)
+    })?;
+
+    // the client misses the items sent before it connected
+    assert!(!received.is_empty());
+    assert!(items.ends_with(&received));
+    Ok(())
+}
+
+#[test]
+fn websocket_pdu() -> Result<()> {
+    let port = free_port()?;
+
+    let mut fg = Flowgraph::new();
+    let ws_src = fg.add_block(WebsocketSourceBuilder::new(port).pdu(true).build());
+    let snk = fg.add_block(MessageDebugBuilder::new().print(false).store(10).build());
+    fg.connect_message(ws_src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start(fg);
+    let fg = block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+        let stream = async_net::TcpStream::connect(format!("127.0.0.1:{}", port)).await?;
+        let (mut ws, _) =
+            async_tungstenite::client_async(format!("ws://127.0.0.1:{}/", port), stream).await?;
+        ws.send(Message::Binary(vec![1, 2, 3])).await?;
+        ws.send(Message::Text("hello".to_string())).await?;
+        ws.close(None).await?;
+        task.await
+    })?;
+
+    let snk = fg.block_async::<MessageDebug>(snk).unwrap();
+    assert_eq!(
+        snk.messages(),
+        vec![Pmt::Blob(vec![1, 2, 3]), Pmt::String("hello".to_string())]
+    );
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use anyhow::Result;
+use async_io::block_on;
+
+use futuresdr::blocks::zeromq::decode;
+use futuresdr::blocks::zeromq::encode;
+use futuresdr::blocks::zeromq::Format;
+use futuresdr::blocks::zeromq::PullSourceBuilder;
+use futuresdr::blocks::zeromq::PushSinkBuilder;
+use futuresdr::blocks::zeromq::RepMessageServerBuilder;
+use futuresdr::blocks::zeromq::ReqMessageClientBuilder;
+use futuresdr::blocks::HeadBuilder;
+use futuresdr::blocks::MessageCopy;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn zeromq_format() -> Result<()> {
+    let pmts = [
+        Pmt::Null,
+        Pmt::U32(42),
+        Pmt::U64(u64::MAX),
+        Pmt::Double(1.5),
+        Pmt::String("foo".to_string()),
+        Pmt::Blob(vec![1, 2, 3]),
+        Pmt::VecF32(vec![1.0, -2.0]),
+    ];
+
+    for format in [Format::Json, Format::Binary] {
+        for p in pmts.iter() {
+            assert_eq!(&decode(&encode(p, format)?, format)?, p);
+        }
+        assert!(decode(&[0xff, 0x00], format).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn zeromq_push_pull() -> Result<()> {
+    let address = "inproc://futuresdr-push-pull";
+    let items: Vec<u32> = (0..10_000).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::new(items.clone()));
+    let push = fg.add_block(PushSinkBuilder::new(4).address(address).build());
+    let pull = fg.add_block(PullSourceBuilder::new(4).address(address).build());
+    let head = fg.add_block(HeadBuilder::new(4, items.len() as u64).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", push, "in")?;
+    fg.connect_stream(pull, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.block_async::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &items);
+    Ok(())
+}
+
+#[test]
+fn zeromq_req_rep() -> Result<()> {
+    let address = "inproc://futuresdr-req-rep";
+
+    // the server replies with the request
+    let mut fg = Flowgraph::new();
+    let rep = fg.add_block(
+        RepMessageServerBuilder::new()
+            .address(address)
+            .reply(true)
+            .format(Format::Binary)
+            .build(),
+    );
+    let echo = fg.add_block(MessageCopy::new());
+    let req = fg.add_block(
+        ReqMessageClientBuilder::new()
+            .address(address)
+            .format(Format::Binary)
+            .build(),
+    );
+    fg.connect_message(rep, "out", echo, "in")?;
+    fg.connect_message(echo, "out", rep, "reply")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    block_on(async move {
+        for p in [Pmt::U32(1), Pmt::String("foo".to_string())] {
+            assert_eq!(handle.callback(req, 0, p.clone()).await?, p);
+        }
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    // the server and the client do not finish
+    task.detach();
+    Ok(())
+}
+
+#[test]
+fn zeromq_req_timeout() -> Result<()> {
+    let address = "inproc://futuresdr-req-timeout";
+
+    // the server does not reply
+    let mut fg = Flowgraph::new();
+    let _rep = fg.add_block(
+        RepMessageServerBuilder::new()
+            .address(address)
+            .reply(true)
+            .build(),
+    );
+    let req = fg.add_block(
+        ReqMessageClientBuilder::new()
+            .address(address)
+            .timeout(100)
+            .build(),
+    );
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start(fg);
+    block_on(async move {
+        assert_eq!(handle.callback(req, 0, Pmt::U32(1)).await?, Pmt::Null);
+        Ok::<(), anyhow::Error>(())
+    })?;
+
+    task.detach();
+    Ok(())
+}
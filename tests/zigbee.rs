@@ -0,0 +1,61 @@
+use anyhow::Result;
+use num_complex::Complex;
+
+use futuresdr::blocks::zigbee::crc16;
+use futuresdr::blocks::zigbee::DemodulatorBuilder;
+use futuresdr::blocks::zigbee::Mac;
+use futuresdr::blocks::zigbee::MacBuilder;
+use futuresdr::blocks::zigbee::Modulator;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::MessageBurstBuilder;
+use futuresdr::blocks::MessageSink;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn zigbee_crc() {
+    assert_eq!(crc16(b"123456789"), 0x2189);
+
+    let mut frame = b"FutureSDR".to_vec();
+    let crc = crc16(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    assert_eq!(crc16(&frame), 0);
+}
+
+#[test]
+fn zigbee_loopback() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let n_frames = 20;
+    let rotation = Complex::from_polar(0.8, 2.0);
+
+    let src =
+        fg.add_block(MessageBurstBuilder::new(Pmt::Blob(b"FutureSDR".to_vec()), n_frames).build());
+    let mac_tx = fg.add_block(MacBuilder::new().build());
+    let modulator = fg.add_block(Modulator::new());
+    let channel = fg.add_block(Apply::new(move |i: &Complex<f32>| -> Complex<f32> {
+        i * rotation
+    }));
+    let demodulator = fg.add_block(DemodulatorBuilder::new().build());
+    let mac_rx = fg.add_block(MacBuilder::new().build());
+    let snk = fg.add_block(MessageSink::new());
+
+    fg.connect_message(src, "out", mac_tx, "tx")?;
+    fg.connect_message(mac_tx, "tx", modulator, "in")?;
+    fg.connect_stream(modulator, "out", channel, "in")?;
+    fg.connect_stream(channel, "out", demodulator, "in")?;
+    fg.connect_message(demodulator, "out", mac_rx, "rx")?;
+    fg.connect_message(mac_rx, "rx", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let mac = fg.block_async::<Mac>(mac_rx).unwrap();
+    assert_eq!(mac.crc_errors(), 0);
+    assert_eq!(mac.received(), n_frames);
+
+    let snk = fg.block_async::<MessageSink>(snk).unwrap();
+    assert_eq!(snk.received(), n_frames);
+
+    Ok(())
+}